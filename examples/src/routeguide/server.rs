@@ -4,8 +4,8 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use futures::{Stream, StreamExt};
-use tokio::sync::mpsc;
-use tokio_stream::wrappers::ReceiverStream;
+use tonic::codec::FlushMode;
+use tonic::server::{response_channel, ResponseStream};
 use tonic::transport::Server;
 use tonic::{Request, Response, Status};
 
@@ -37,7 +37,7 @@ impl RouteGuide for RouteGuideService {
         Ok(Response::new(Feature::default()))
     }
 
-    type ListFeaturesStream = ReceiverStream<Result<Feature, Status>>;
+    type ListFeaturesStream = ResponseStream<Feature>;
 
     async fn list_features(
         &self,
@@ -45,21 +45,26 @@ impl RouteGuide for RouteGuideService {
     ) -> Result<Response<Self::ListFeaturesStream>, Status> {
         println!("ListFeatures = {:?}", request);
 
-        let (tx, rx) = mpsc::channel(4);
+        let (tx, rx) = response_channel(4);
         let features = self.features.clone();
 
         tokio::spawn(async move {
             for feature in &features[..] {
                 if in_range(feature.location.as_ref().unwrap(), request.get_ref()) {
                     println!("  => send {:?}", feature);
-                    tx.send(Ok(feature.clone())).await.unwrap();
+                    tx.send(feature.clone()).await?;
                 }
             }
 
             println!(" /// done sending");
+            Ok::<_, Status>(())
         });
 
-        Ok(Response::new(ReceiverStream::new(rx)))
+        // Features are sent in bulk, hand them to the transport in batches.
+        let mut response = Response::new(rx);
+        response.set_flush_mode(FlushMode::Coalesce);
+
+        Ok(response)
     }
 
     async fn record_route(
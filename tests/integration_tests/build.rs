@@ -1,4 +1,6 @@
 fn main() {
     tonic_build::compile_protos("proto/test.proto").unwrap();
     tonic_build::compile_protos("proto/stream.proto").unwrap();
+    tonic_build::compile_protos("proto/bidi.proto").unwrap();
+    tonic_build::compile_protos("proto/wide.proto").unwrap();
 }
@@ -0,0 +1,286 @@
+//! A transport wrapper that injects faults, for testing resilience.
+
+use bytes::BytesMut;
+use std::{
+    collections::VecDeque,
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    time::Sleep,
+};
+
+const FRAME_HEADER_LEN: usize = 9;
+
+// A GOAWAY frame with `NO_ERROR`. It would let streams that are already open
+// complete, but the client opens no new streams on the connection after it,
+// so calls that have not been sent yet fail.
+const GO_AWAY: [u8; 17] = [
+    0, 0, 8, // length
+    7, // type
+    0, // flags
+    0, 0, 0, 0, // stream id
+    0x7f, 0xff, 0xff, 0xff, // last stream id
+    0, 0, 0, 0, // error code
+];
+
+/// A fault injected by a [`FaultyTransport`].
+#[derive(Debug, Clone)]
+pub enum Fault {
+    /// Let the given number of HTTP/2 frames through.
+    Forward(usize),
+    /// Drop the given number of HTTP/2 frames, as if they were lost.
+    Drop(usize),
+    /// Hold back the next read for the given duration.
+    Delay(Duration),
+    /// Inject a `GOAWAY` frame, as if the server was shutting down.
+    GoAway,
+    /// Fail all further reads and writes with `ConnectionReset`.
+    Reset,
+}
+
+/// Builder for a [`FaultyTransport`].
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    script: VecDeque<Fault>,
+    latency: Option<Duration>,
+}
+
+impl Builder {
+    /// Delay every read by `latency`.
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Let `frames` HTTP/2 frames through before the next fault.
+    pub fn forward(self, frames: usize) -> Self {
+        self.then(Fault::Forward(frames))
+    }
+
+    /// Drop the next `frames` HTTP/2 frames.
+    pub fn drop(self, frames: usize) -> Self {
+        self.then(Fault::Drop(frames))
+    }
+
+    /// Hold back the next read for `duration`.
+    pub fn delay(self, duration: Duration) -> Self {
+        self.then(Fault::Delay(duration))
+    }
+
+    /// Inject a `GOAWAY` frame.
+    pub fn go_away(self) -> Self {
+        self.then(Fault::GoAway)
+    }
+
+    /// Reset the connection.
+    pub fn reset(self) -> Self {
+        self.then(Fault::Reset)
+    }
+
+    /// Append `fault` to the script.
+    pub fn then(mut self, fault: Fault) -> Self {
+        self.script.push_back(fault);
+        self
+    }
+
+    /// Wrap `inner` in a transport that plays this script.
+    pub fn build<T>(self, inner: T) -> FaultyTransport<T> {
+        FaultyTransport {
+            inner,
+            script: self.script,
+            latency: self.latency,
+            sleep: None,
+            pending: BytesMut::new(),
+            ready: BytesMut::new(),
+            reset: false,
+        }
+    }
+}
+
+/// Wraps the client side of a connection and injects faults into it.
+///
+/// Faults are played in order on the read side, that is on the data sent by
+/// the server. Once the script is done all data is passed through, still
+/// subject to the configured latency. Writes are passed through until the
+/// connection is reset.
+///
+/// This is meant to be used with an in-memory transport such as
+/// [`tokio::io::duplex`] and [`Endpoint::connect_with_connector`], where the
+/// first bytes read are a frame boundary.
+///
+/// [`Endpoint::connect_with_connector`]: tonic::transport::Endpoint::connect_with_connector
+#[derive(Debug)]
+pub struct FaultyTransport<T> {
+    inner: T,
+    script: VecDeque<Fault>,
+    latency: Option<Duration>,
+    sleep: Option<Pin<Box<Sleep>>>,
+    // read from `inner` but not released yet
+    pending: BytesMut,
+    // released and waiting to be read
+    ready: BytesMut,
+    reset: bool,
+}
+
+impl FaultyTransport<()> {
+    /// Create a new [`Builder`].
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+}
+
+impl<T> FaultyTransport<T>
+where
+    T: AsyncRead + Unpin,
+{
+    fn poll_sleep(&mut self, cx: &mut Context<'_>, duration: Duration) -> Poll<()> {
+        let sleep = self
+            .sleep
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(duration)));
+
+        futures_util::ready!(sleep.as_mut().poll(cx));
+        self.sleep = None;
+
+        Poll::Ready(())
+    }
+
+    fn poll_latency(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        match self.latency {
+            Some(latency) => self.poll_sleep(cx, latency),
+            None => Poll::Ready(()),
+        }
+    }
+
+    // Read more data from `inner`, returns `0` on EOF.
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        let mut data = [0; 8 * 1024];
+        let mut buf = ReadBuf::new(&mut data);
+
+        futures_util::ready!(Pin::new(&mut self.inner).poll_read(cx, &mut buf))?;
+        self.pending.extend_from_slice(buf.filled());
+
+        Poll::Ready(Ok(buf.filled().len()))
+    }
+
+    fn next_frame_len(&self) -> Option<usize> {
+        if self.pending.len() < FRAME_HEADER_LEN {
+            return None;
+        }
+
+        let len = FRAME_HEADER_LEN
+            + ((self.pending[0] as usize) << 16
+                | (self.pending[1] as usize) << 8
+                | self.pending[2] as usize);
+
+        if self.pending.len() < len {
+            None
+        } else {
+            Some(len)
+        }
+    }
+}
+
+impl<T> AsyncRead for FaultyTransport<T>
+where
+    T: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.reset {
+                return Poll::Ready(Err(io::ErrorKind::ConnectionReset.into()));
+            }
+
+            if !this.ready.is_empty() {
+                let len = std::cmp::min(buf.remaining(), this.ready.len());
+                buf.put_slice(&this.ready.split_to(len));
+                return Poll::Ready(Ok(()));
+            }
+
+            match this.script.front().cloned() {
+                Some(Fault::Forward(0)) => {
+                    this.script.pop_front();
+                }
+                Some(Fault::Forward(frames)) => {
+                    if let Some(len) = this.next_frame_len() {
+                        futures_util::ready!(this.poll_latency(cx));
+                        this.ready = this.pending.split_to(len);
+                        this.script[0] = Fault::Forward(frames - 1);
+                    } else if futures_util::ready!(this.poll_fill(cx))? == 0 {
+                        return Poll::Ready(Ok(()));
+                    }
+                }
+                Some(Fault::Drop(0)) => {
+                    this.script.pop_front();
+                }
+                Some(Fault::Drop(frames)) => {
+                    if let Some(len) = this.next_frame_len() {
+                        let _ = this.pending.split_to(len);
+                        this.script[0] = Fault::Drop(frames - 1);
+                    } else if futures_util::ready!(this.poll_fill(cx))? == 0 {
+                        return Poll::Ready(Ok(()));
+                    }
+                }
+                Some(Fault::Delay(duration)) => {
+                    futures_util::ready!(this.poll_sleep(cx, duration));
+                    this.script.pop_front();
+                }
+                Some(Fault::GoAway) => {
+                    this.script.pop_front();
+                    this.ready.extend_from_slice(&GO_AWAY);
+                }
+                Some(Fault::Reset) => {
+                    this.script.pop_front();
+                    this.reset = true;
+                }
+                None => {
+                    if this.pending.is_empty() && futures_util::ready!(this.poll_fill(cx))? == 0 {
+                        return Poll::Ready(Ok(()));
+                    }
+
+                    futures_util::ready!(this.poll_latency(cx));
+                    this.ready = this.pending.split();
+                }
+            }
+        }
+    }
+}
+
+impl<T> AsyncWrite for FaultyTransport<T>
+where
+    T: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.reset {
+            return Poll::Ready(Err(io::ErrorKind::ConnectionReset.into()));
+        }
+
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if self.reset {
+            return Poll::Ready(Err(io::ErrorKind::ConnectionReset.into()));
+        }
+
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
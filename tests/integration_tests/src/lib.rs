@@ -1,4 +1,8 @@
 pub mod pb {
     tonic::include_proto!("test");
     tonic::include_proto!("stream");
+    tonic::include_proto!("bidi");
+    tonic::include_proto!("wide");
 }
+
+pub mod faulty;
@@ -0,0 +1,52 @@
+use futures_util::FutureExt;
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::{
+    thread::{self, ThreadId},
+    time::Duration,
+};
+use tokio::sync::oneshot;
+use tonic::{transport::Server, Request, Response, Status};
+
+#[tokio::test]
+async fn runs_blocking_method_off_runtime() {
+    struct Svc(ThreadId);
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            // The test runtime is single threaded, so any other thread is
+            // one of the blocking pool's.
+            if thread::current().id() == self.0 {
+                return Err(Status::internal("handler ran on the runtime thread"));
+            }
+
+            thread::sleep(Duration::from_millis(10));
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let svc = test_server::TestServer::new(Svc(thread::current().id()));
+
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .blocking_method("/test.Test/UnaryCall")
+            .add_service(svc)
+            .serve_with_shutdown("127.0.0.1:1346".parse().unwrap(), rx.map(drop))
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = test_client::TestClient::connect("http://127.0.0.1:1346")
+        .await
+        .unwrap();
+
+    client.unary_call(Input {}).await.unwrap();
+
+    tx.send(()).unwrap();
+
+    jh.await.unwrap();
+}
@@ -0,0 +1,93 @@
+use hyper::{Body, Request as HyperRequest, Response as HyperResponse};
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::task::{Context, Poll};
+use tokio::{net::TcpListener, sync::mpsc};
+use tonic::{
+    body::BoxBody,
+    server::CallLabels,
+    transport::{Endpoint, NamedService, Server},
+    Request, Response, Status,
+};
+use tower_service::Service;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Tenant(String);
+
+#[tokio::test]
+async fn layer_reads_labels_set_by_handler() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            let labels = req.extensions().get::<CallLabels>().unwrap();
+            labels.insert(Tenant("acme".to_string()));
+
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let svc = Metrics {
+        inner: test_server::TestServer::new(Svc),
+        tx,
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+
+    client.unary_call(Input {}).await.unwrap();
+    assert_eq!(rx.recv().await.unwrap(), Some(Tenant("acme".to_string())));
+}
+
+/// Records the tenant of each call once it completes.
+#[derive(Clone)]
+struct Metrics<S> {
+    inner: S,
+    tx: mpsc::UnboundedSender<Option<Tenant>>,
+}
+
+impl<S> Service<HyperRequest<Body>> for Metrics<S>
+where
+    S: Service<HyperRequest<Body>, Response = HyperResponse<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = futures::future::BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: HyperRequest<Body>) -> Self::Future {
+        let labels = req.extensions().get::<CallLabels>().cloned().unwrap();
+        let response = self.inner.call(req);
+        let tx = self.tx.clone();
+
+        Box::pin(async move {
+            let response = response.await?;
+            tx.send(labels.get::<Tenant>()).unwrap();
+            Ok(response)
+        })
+    }
+}
+
+impl<S: NamedService> NamedService for Metrics<S> {
+    const NAME: &'static str = S::NAME;
+}
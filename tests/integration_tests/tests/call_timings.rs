@@ -0,0 +1,43 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::{client::CallTimings, transport::Server, Request, Response, Status};
+
+#[tokio::test]
+async fn reports_call_timings() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_client::TestClient::connect(format!("http://localhost:{}", port))
+        .await
+        .unwrap();
+
+    // the connection was opened by `connect`, so the first call reports it
+    let response = client.unary_call(Input {}).await.unwrap();
+    let timings = *response.extensions().get::<CallTimings>().unwrap();
+    assert!(timings.resolve().is_some());
+    assert!(timings.connect().is_some());
+    assert!(timings.total().unwrap() >= timings.first_byte());
+
+    let response = client.unary_call(Input {}).await.unwrap();
+    let timings = *response.extensions().get::<CallTimings>().unwrap();
+    assert_eq!(timings.resolve(), None);
+    assert_eq!(timings.connect(), None);
+    assert!(timings.total().is_some());
+}
@@ -0,0 +1,165 @@
+use integration_tests::pb::{
+    test_client, test_server, test_stream_client, test_stream_server, Input, InputStream, Output,
+    OutputStream,
+};
+use std::{net::SocketAddr, time::Duration};
+use tokio::{net::TcpListener, sync::mpsc};
+use tonic::{
+    transport::{Channel, Server},
+    CancellationToken, Code, Request, Response, Status,
+};
+
+type Stream<T> = std::pin::Pin<
+    Box<dyn futures::Stream<Item = std::result::Result<T, Status>> + Send + Sync + 'static>,
+>;
+
+#[tokio::test]
+async fn cancels_spawned_downstream_calls() {
+    struct Front {
+        downstream: Channel,
+        tx: mpsc::UnboundedSender<Result<(), Code>>,
+    }
+
+    #[tonic::async_trait]
+    impl test_server::Test for Front {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            let token = CancellationToken::current().expect("handlers run with a token");
+            let mut client = test_stream_client::TestStreamClient::new(self.downstream.clone());
+            let tx = self.tx.clone();
+
+            tokio::spawn(token.scope(async move {
+                let result = client.stream_call(InputStream {}).await;
+                tx.send(result.map(drop).map_err(|status| status.code()))
+                    .unwrap();
+            }));
+
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    struct Downstream;
+
+    #[tonic::async_trait]
+    impl test_stream_server::TestStream for Downstream {
+        type StreamCallStream = Stream<OutputStream>;
+
+        async fn stream_call(
+            &self,
+            _: Request<InputStream>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(Response::new(Box::pin(futures::stream::empty())))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let front = test_server::TestServer::new(Front {
+        downstream: channel(addr),
+        tx,
+    });
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(front)
+            .add_service(test_stream_server::TestStreamServer::new(Downstream))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_client::TestClient::new(channel(addr));
+
+    let mut request = Request::new(Input {});
+    request.set_timeout(Duration::from_millis(200));
+    let status = client.unary_call(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::Cancelled);
+
+    let downstream = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .expect("downstream call should be cancelled with the inbound call");
+    assert_eq!(downstream, Some(Err(Code::Cancelled)));
+}
+
+#[tokio::test]
+async fn cancels_spawned_downstream_streams() {
+    struct Front {
+        downstream: Channel,
+        tx: mpsc::UnboundedSender<Result<(), Code>>,
+    }
+
+    #[tonic::async_trait]
+    impl test_server::Test for Front {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            let token = CancellationToken::current().expect("handlers run with a token");
+            let mut client = test_stream_client::TestStreamClient::new(self.downstream.clone());
+            let tx = self.tx.clone();
+
+            tokio::spawn(token.scope(async move {
+                let result = match client.stream_call(InputStream {}).await {
+                    Ok(response) => response.into_inner().message().await.map(drop),
+                    Err(status) => Err(status),
+                };
+                tx.send(result.map_err(|status| status.code())).unwrap();
+            }));
+
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    struct Downstream;
+
+    #[tonic::async_trait]
+    impl test_stream_server::TestStream for Downstream {
+        type StreamCallStream = Stream<OutputStream>;
+
+        async fn stream_call(
+            &self,
+            _: Request<InputStream>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            // the call returns right away, but its messages never come
+            Ok(Response::new(Box::pin(futures::stream::pending())))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let front = test_server::TestServer::new(Front {
+        downstream: channel(addr),
+        tx,
+    });
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(front)
+            .add_service(test_stream_server::TestStreamServer::new(Downstream))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_client::TestClient::new(channel(addr));
+
+    let mut request = Request::new(Input {});
+    request.set_timeout(Duration::from_millis(200));
+    let status = client.unary_call(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::Cancelled);
+
+    let downstream = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .expect("downstream stream should be cancelled with the inbound call");
+    assert_eq!(downstream, Some(Err(Code::Cancelled)));
+}
+
+fn channel(addr: SocketAddr) -> Channel {
+    Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect_lazy()
+        .unwrap()
+}
@@ -1,5 +1,6 @@
 use futures_util::FutureExt;
 use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::oneshot;
@@ -90,3 +91,101 @@ async fn connect_lazy_reconnects_after_first_failure() {
 
     jh.await.unwrap();
 }
+
+#[tokio::test]
+async fn connect_with_resolver_overrides() {
+    let (tx, rx) = oneshot::channel();
+    let sender = Arc::new(Mutex::new(Some(tx)));
+    let svc = test_server::TestServer::new(Svc(sender));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_shutdown("127.0.0.1:1340".parse().unwrap(), rx.map(drop))
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut overrides = HashMap::new();
+    overrides.insert(
+        "routeguide.test".to_string(),
+        "127.0.0.1:1340".parse().unwrap(),
+    );
+
+    let channel = Endpoint::from_static("http://routeguide.test")
+        .with_resolver_overrides(overrides)
+        .connect()
+        .await
+        .unwrap();
+
+    let mut client = TestClient::new(channel);
+
+    client.unary_call(Request::new(Input {})).await.unwrap();
+
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn wait_until_ready() {
+    let (tx, rx) = oneshot::channel();
+    let sender = Arc::new(Mutex::new(Some(tx)));
+    let svc = test_server::TestServer::new(Svc(sender));
+
+    let channel = Endpoint::from_static("http://127.0.0.1:1345")
+        .connect_lazy()
+        .unwrap();
+
+    // The server is not running yet
+    channel
+        .wait_until_ready(Duration::from_millis(200))
+        .await
+        .unwrap_err();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_shutdown("127.0.0.1:1345".parse().unwrap(), rx.map(drop))
+            .await
+            .unwrap();
+    });
+
+    channel
+        .wait_until_ready(Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let mut client = TestClient::new(channel);
+    client.unary_call(Request::new(Input {})).await.unwrap();
+
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn sheds_requests_over_max_pending() {
+    // With no endpoints to balance over every request stays queued.
+    let (channel, _tx) = tonic::transport::Channel::balance_channel::<usize>(1);
+    let channel = channel.max_pending_requests(2);
+
+    let mut queued = Vec::new();
+    for _ in 0..2 {
+        let mut client = TestClient::new(channel.clone());
+        queued.push(tokio::spawn(
+            async move { client.unary_call(Input {}).await },
+        ));
+    }
+
+    while channel.balance_queue_len() < 2 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let mut client = TestClient::new(channel.clone());
+    let err = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(err.code(), tonic::Code::ResourceExhausted);
+    assert_eq!(channel.balance_queue_len(), 2);
+
+    for handle in queued {
+        handle.abort();
+    }
+}
@@ -0,0 +1,90 @@
+use futures_util::FutureExt;
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::{io, time::Duration};
+use tokio::{
+    io::AsyncWriteExt,
+    net::{TcpListener, TcpStream},
+    sync::{mpsc, oneshot},
+};
+use tokio_stream::{wrappers::TcpListenerStream, StreamExt};
+use tonic::{
+    transport::{server::ConnectionErrorKind, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn reports_connection_errors() {
+    let (errors_tx, mut errors_rx) = mpsc::unbounded_channel();
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .on_connection_error(move |err| {
+                errors_tx.send((err.kind(), err.remote_addr())).unwrap();
+            })
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_shutdown("127.0.0.1:1347".parse().unwrap(), rx.map(drop))
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Not the HTTP/2 connection preface.
+    let mut stream = TcpStream::connect("127.0.0.1:1347").await.unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+
+    let (kind, remote_addr) = errors_rx.recv().await.unwrap();
+    assert_eq!(kind, ConnectionErrorKind::Connection);
+    assert_eq!(remote_addr, Some(stream.local_addr().unwrap()));
+
+    tx.send(()).unwrap();
+
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn keeps_serving_after_accept_errors() {
+    let (errors_tx, mut errors_rx) = mpsc::unbounded_channel();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let failed_accept = tokio_stream::once(Err(io::Error::other("too many open files")));
+    let incoming = failed_accept.chain(TcpListenerStream::new(listener));
+
+    tokio::spawn(async move {
+        Server::builder()
+            .on_connection_error(move |err| {
+                errors_tx.send(err.kind()).unwrap();
+            })
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(incoming)
+            .await
+            .unwrap();
+    });
+
+    assert_eq!(errors_rx.recv().await, Some(ConnectionErrorKind::Accept));
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    test_client::TestClient::new(channel)
+        .unary_call(Input {})
+        .await
+        .unwrap();
+}
@@ -0,0 +1,43 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tonic::{
+    transport::{Channel, Endpoint, Server, Uri},
+    Request, Response, Status,
+};
+use tower::service_fn;
+
+#[tokio::test]
+async fn channel_over_custom_connector() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let (client, server) = tokio::io::duplex(1024);
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(futures::stream::iter(vec![Ok::<_, std::io::Error>(server)]))
+            .await
+            .unwrap();
+    });
+
+    // the pipe can only be handed out once
+    let mut client = Some(client);
+    let channel = Channel::with_connector(
+        Endpoint::from_static("http://[::]:50051"),
+        service_fn(move |_: Uri| {
+            let client = client.take();
+            async move { client.ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotConnected)) }
+        }),
+    );
+
+    let mut client = test_client::TestClient::new(channel);
+
+    client.unary_call(Input {}).await.unwrap();
+    client.unary_call(Input {}).await.unwrap();
+}
@@ -0,0 +1,55 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+
+#[tokio::test]
+async fn grpc_proto_content_type() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let check_request = tower::ServiceBuilder::new()
+        .map_request(|request: http::Request<hyper::Body>| {
+            assert_eq!(request.headers()["content-type"], "application/grpc+proto");
+            request
+        })
+        .into_inner();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .grpc_proto_content_type(true)
+            .layer(check_request)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .grpc_proto_content_type(true)
+        .connect()
+        .await
+        .unwrap();
+
+    let response = test_client::TestClient::new(channel)
+        .unary_call(Input {})
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.metadata().get("content-type").unwrap(),
+        "application/grpc+proto"
+    );
+}
@@ -0,0 +1,70 @@
+use integration_tests::pb::{test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::{transport::Server, Code, Request, Response, Status};
+
+// A message frame whose payload is a truncated length-delimited field.
+const CORRUPT_FRAME: &[u8] = &[0, 0, 0, 0, 2, 0x0a, 0x05];
+// A message frame whose header announces more bytes than the request has.
+const TRUNCATED_FRAME: &[u8] = &[0, 0, 0, 0, 8, 0x0a];
+
+async fn grpc_status(mut server: Server, frame: &'static [u8]) -> (Code, String) {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        server
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let client = hyper::Client::builder().http2_only(true).build_http();
+    let request = http::Request::post(format!("http://{}/test.Test/UnaryCall", addr))
+        .header("content-type", "application/grpc")
+        .header("te", "trailers")
+        .body(hyper::Body::from(frame))
+        .unwrap();
+
+    let response = client.request(request).await.unwrap();
+    let status = Status::from_header_map(response.headers()).expect("a Trailers-Only response");
+
+    (status.code(), status.message().to_string())
+}
+
+#[tokio::test]
+async fn decode_errors_are_internal_by_default() {
+    let (code, _) = grpc_status(Server::builder(), CORRUPT_FRAME).await;
+    assert_eq!(code, Code::Internal);
+}
+
+#[tokio::test]
+async fn on_decode_error_maps_status() {
+    let server = Server::builder().on_decode_error(|status| {
+        Status::invalid_argument(format!("bad request: {}", status.message()))
+    });
+
+    let (code, message) = grpc_status(server, CORRUPT_FRAME).await;
+    assert_eq!(code, Code::InvalidArgument);
+    assert!(message.starts_with("bad request: "), "{}", message);
+}
+
+#[tokio::test]
+async fn on_decode_error_maps_truncated_frames() {
+    let server = Server::builder().on_decode_error(|status| {
+        Status::invalid_argument(format!("bad request: {}", status.message()))
+    });
+
+    let (code, message) = grpc_status(server, TRUNCATED_FRAME).await;
+    assert_eq!(code, Code::InvalidArgument);
+    assert_eq!(message, "bad request: Unexpected EOF decoding stream.");
+}
@@ -0,0 +1,72 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn client(echo_deadlines: bool) -> test_client::TestClient<tonic::transport::Channel> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .echo_deadlines(echo_deadlines)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    test_client::TestClient::new(channel)
+}
+
+#[tokio::test]
+async fn echoes_received_deadline() {
+    let mut client = client(true).await;
+
+    let mut request = Request::new(Input {});
+    request.set_timeout(Duration::from_secs(5));
+    let response = client.unary_call(request).await.unwrap();
+
+    let metadata = response.metadata();
+    assert_eq!(metadata.get("tonic-received-timeout").unwrap(), "5000000u");
+
+    let remaining = metadata
+        .get("tonic-deadline-remaining")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    let micros: u64 = remaining.strip_suffix('u').unwrap().parse().unwrap();
+    assert!(micros > 0 && micros <= 5_000_000, "remaining {}", remaining);
+}
+
+#[tokio::test]
+async fn echoes_nothing_by_default() {
+    let mut client = client(false).await;
+
+    let mut request = Request::new(Input {});
+    request.set_timeout(Duration::from_secs(5));
+    let response = client.unary_call(request).await.unwrap();
+
+    assert!(response.metadata().get("tonic-received-timeout").is_none());
+    assert!(response
+        .metadata()
+        .get("tonic-deadline-remaining")
+        .is_none());
+}
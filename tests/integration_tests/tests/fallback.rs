@@ -0,0 +1,53 @@
+use integration_tests::pb::{
+    test_client, test_server, test_stream_client, Input, InputStream, Output,
+};
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{Body, Endpoint, Server},
+    Code, Request, Response, Status,
+};
+
+#[tokio::test]
+async fn unmatched_paths_go_to_fallback() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let fallback = tower::service_fn(|request: http::Request<Body>| async move {
+        let status = Status::not_found(format!("forwarded {}", request.uri().path()));
+        Ok::<_, std::convert::Infallible>(status.to_http())
+    });
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .fallback(fallback)
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    // the added service takes precedence over the fallback
+    let mut client = test_client::TestClient::new(channel.clone());
+    client.unary_call(Input {}).await.unwrap();
+
+    let mut client = test_stream_client::TestStreamClient::new(channel);
+    let status = client.stream_call(InputStream {}).await.unwrap_err();
+
+    assert_eq!(status.code(), Code::NotFound);
+    assert_eq!(status.message(), "forwarded /stream.TestStream/StreamCall");
+}
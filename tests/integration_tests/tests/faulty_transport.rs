@@ -0,0 +1,134 @@
+use futures::StreamExt;
+use integration_tests::{
+    faulty::{Builder, FaultyTransport},
+    pb::{test_client::TestClient, test_server, Input, Output},
+};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+use tonic::{
+    transport::{Channel, Endpoint, Server, Uri},
+    Request, Response, Status,
+};
+use tower::service_fn;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+// Serves `Svc` in memory, each new connection plays the next script.
+fn faulty_channel(scripts: Vec<Builder>) -> (Channel, Arc<AtomicUsize>) {
+    let (tx, rx) = mpsc::channel(16);
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(
+                tokio_stream::wrappers::ReceiverStream::new(rx).map(Ok::<_, std::io::Error>),
+            )
+            .await
+            .unwrap();
+    });
+
+    let scripts = Arc::new(Mutex::new(scripts.into_iter().collect::<VecDeque<_>>()));
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    let channel = Endpoint::from_static("http://[::]:50051")
+        .connect_with_connector_lazy({
+            let connections = connections.clone();
+            service_fn(move |_: Uri| {
+                let (client, server) = tokio::io::duplex(1024 * 64);
+                let script = scripts.lock().unwrap().pop_front().unwrap_or_default();
+                let tx = tx.clone();
+                connections.fetch_add(1, Ordering::SeqCst);
+
+                async move {
+                    tx.send(server).await.unwrap();
+                    Ok::<_, std::io::Error>(script.build(client))
+                }
+            })
+        })
+        .unwrap();
+
+    (channel, connections)
+}
+
+#[tokio::test]
+async fn reconnects_after_reset() {
+    let (channel, connections) = faulty_channel(vec![FaultyTransport::builder().reset()]);
+    let mut client = TestClient::new(channel);
+
+    // The connection fails before the request is sent, so it is sent on a
+    // new connection instead.
+    client.unary_call(Input {}).await.unwrap();
+    client.unary_call(Input {}).await.unwrap();
+
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn reset_after_frames() {
+    let (channel, connections) = faulty_channel(vec![
+        FaultyTransport::builder().forward(1).reset(),
+        Builder::default(),
+    ]);
+    let mut client = TestClient::new(channel);
+
+    client.unary_call(Input {}).await.unwrap_err();
+    client.unary_call(Input {}).await.unwrap();
+    client.unary_call(Input {}).await.unwrap();
+
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn adds_latency() {
+    let latency = Duration::from_millis(50);
+    let (channel, _) = faulty_channel(vec![FaultyTransport::builder().latency(latency)]);
+    let mut client = TestClient::new(channel);
+
+    client.unary_call(Input {}).await.unwrap();
+
+    let start = Instant::now();
+    client.unary_call(Input {}).await.unwrap();
+    assert!(start.elapsed() >= latency);
+}
+
+#[tokio::test]
+async fn reconnects_after_go_away() {
+    let (channel, connections) =
+        faulty_channel(vec![FaultyTransport::builder().forward(1).go_away()]);
+    let mut client = TestClient::new(channel);
+
+    // The GOAWAY follows the server's SETTINGS, before the first call has
+    // opened its stream, so that call fails and the next ones reconnect.
+    client.unary_call(Input {}).await.unwrap_err();
+    client.unary_call(Input {}).await.unwrap();
+    client.unary_call(Input {}).await.unwrap();
+
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn lost_frames_stall_call() {
+    let (channel, _) = faulty_channel(vec![FaultyTransport::builder().forward(1).drop(100)]);
+    let mut client = TestClient::new(channel);
+
+    // everything the server sends after its SETTINGS is lost, including the
+    // response
+    let call = client.unary_call(Input {});
+    tokio::time::timeout(Duration::from_millis(200), call)
+        .await
+        .unwrap_err();
+}
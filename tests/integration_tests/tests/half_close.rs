@@ -0,0 +1,89 @@
+use futures_util::StreamExt;
+use integration_tests::pb::{test_bidi_client, test_bidi_server, InputBidi, OutputBidi};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    server::{response_channel, ResponseStream},
+    transport::{Endpoint, Server},
+    Request, Response, Status, Streaming,
+};
+
+#[tokio::test]
+async fn server_keeps_sending_after_half_close() {
+    struct Svc {
+        half_closed: std::sync::Mutex<Option<oneshot::Sender<u32>>>,
+    }
+
+    #[tonic::async_trait]
+    impl test_bidi_server::TestBidi for Svc {
+        type BidiCallStream = ResponseStream<OutputBidi>;
+
+        async fn bidi_call(
+            &self,
+            request: Request<Streaming<InputBidi>>,
+        ) -> Result<Response<Self::BidiCallStream>, Status> {
+            let mut inbound = request.into_inner();
+            let half_closed = self.half_closed.lock().unwrap().take().unwrap();
+            let (tx, rx) = response_channel(4);
+
+            tokio::spawn(async move {
+                let mut received = 0;
+                while let Some(message) = inbound.next().await {
+                    message?;
+                    received += 1;
+                    tx.send(OutputBidi { received }).await?;
+                }
+
+                half_closed.send(received).unwrap();
+
+                // the response stream is still open after the half-close
+                tx.send(OutputBidi { received }).await?;
+                tx.send(OutputBidi { received }).await?;
+
+                Ok::<_, Status>(())
+            });
+
+            Ok(Response::new(rx))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (half_closed_tx, half_closed_rx) = oneshot::channel();
+    let svc = test_bidi_server::TestBidiServer::new(Svc {
+        half_closed: std::sync::Mutex::new(Some(half_closed_tx)),
+    });
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_bidi_client::TestBidiClient::new(channel);
+
+    let (tx, rx) = tonic::client::request_channel(4);
+    let mut inbound = client.bidi_call(rx).await.unwrap().into_inner();
+
+    for i in 1..=2 {
+        tx.send(InputBidi {}).await.unwrap();
+        let echo = inbound.message().await.unwrap().unwrap();
+        assert_eq!(echo.received, i);
+    }
+
+    tx.finish().await;
+    assert_eq!(half_closed_rx.await.unwrap(), 2);
+
+    let mut remaining = Vec::new();
+    while let Some(message) = inbound.message().await.unwrap() {
+        remaining.push(message.received);
+    }
+    assert_eq!(remaining, [2, 2]);
+}
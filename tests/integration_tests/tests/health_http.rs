@@ -0,0 +1,91 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+use tonic_health::proto::{health_client::HealthClient, HealthCheckRequest};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn healthz_is_routed_next_to_grpc_services() {
+    let (mut reporter, health_service) = tonic_health::server::health_reporter();
+    reporter
+        .set_not_serving::<test_server::TestServer<Svc>>()
+        .await;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn({
+        let http_service = reporter.http_service();
+
+        async move {
+            Server::builder()
+                .accept_http1(true)
+                .add_service(test_server::TestServer::new(Svc))
+                .add_service(health_service)
+                .add_service(http_service)
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+                .await
+                .unwrap();
+        }
+    });
+
+    let http = hyper::Client::new();
+    let probe = |path: &str| {
+        let uri = format!("http://{}{}", addr, path).parse().unwrap();
+        let response = http.get(uri);
+        async move { response.await.unwrap().status() }
+    };
+
+    assert_eq!(
+        probe("/healthz").await,
+        http::StatusCode::SERVICE_UNAVAILABLE
+    );
+    assert_eq!(
+        probe("/healthz/test.Test").await,
+        http::StatusCode::SERVICE_UNAVAILABLE
+    );
+    assert_eq!(
+        probe("/healthz/other.Other").await,
+        http::StatusCode::NOT_FOUND
+    );
+
+    reporter.set_serving::<test_server::TestServer<Svc>>().await;
+
+    assert_eq!(probe("/healthz").await, http::StatusCode::OK);
+    assert_eq!(probe("/healthz/test.Test").await, http::StatusCode::OK);
+
+    // the gRPC services are still served on the same server
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    test_client::TestClient::new(channel.clone())
+        .unary_call(Input {})
+        .await
+        .unwrap();
+
+    let health = HealthClient::new(channel)
+        .check(HealthCheckRequest {
+            service: "test.Test".into(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(
+        health.status,
+        tonic_health::proto::health_check_response::ServingStatus::Serving as i32
+    );
+}
@@ -0,0 +1,116 @@
+use futures_util::StreamExt;
+use integration_tests::pb::{
+    test_client, test_server, test_stream_client, test_stream_server, Input, InputStream, Output,
+    OutputStream,
+};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+
+type Stream<T> = std::pin::Pin<
+    Box<dyn futures::Stream<Item = std::result::Result<T, Status>> + Send + Sync + 'static>,
+>;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tonic::async_trait]
+impl test_stream_server::TestStream for Svc {
+    type StreamCallStream = Stream<OutputStream>;
+
+    async fn stream_call(
+        &self,
+        _: Request<InputStream>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let s = futures::stream::once(async {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            Ok(OutputStream {})
+        });
+        Ok(Response::new(Box::pin(s) as Self::StreamCallStream))
+    }
+}
+
+/// Serve `Svc`, returning the endpoint and the number of accepted connections.
+async fn serve() -> (Endpoint, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let incoming = {
+        let accepted = accepted.clone();
+        tokio_stream::wrappers::TcpListenerStream::new(listener).inspect(move |_| {
+            accepted.fetch_add(1, Ordering::SeqCst);
+        })
+    };
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .add_service(test_stream_server::TestStreamServer::new(Svc))
+            .serve_with_incoming(incoming)
+            .await
+            .unwrap();
+    });
+
+    let endpoint = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .idle_connection_timeout(Duration::from_millis(100));
+
+    (endpoint, accepted)
+}
+
+#[tokio::test]
+async fn reopens_idle_connection() {
+    let (endpoint, accepted) = serve().await;
+    let mut client = test_client::TestClient::new(endpoint.connect().await.unwrap());
+
+    client.unary_call(Input {}).await.unwrap();
+    client.unary_call(Input {}).await.unwrap();
+    assert_eq!(accepted.load(Ordering::SeqCst), 1);
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    // the idle connection was closed and is reopened by the next call
+    client.unary_call(Input {}).await.unwrap();
+    assert_eq!(accepted.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn streaming_call_outlives_idle_timeout() {
+    let (endpoint, accepted) = serve().await;
+    let channel = endpoint.connect().await.unwrap();
+    let mut client = test_stream_client::TestStreamClient::new(channel.clone());
+
+    let mut stream = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+
+    // the response is still streaming past the idle timeout, so the
+    // connection is not idle and the next call is made on it
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    test_client::TestClient::new(channel)
+        .unary_call(Input {})
+        .await
+        .unwrap();
+    assert_eq!(accepted.load(Ordering::SeqCst), 1);
+
+    stream.message().await.unwrap().unwrap();
+    assert!(stream.message().await.unwrap().is_none());
+}
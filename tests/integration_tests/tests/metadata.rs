@@ -0,0 +1,83 @@
+use futures_util::FutureExt;
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tonic::{
+    metadata::{MetadataMap, MetadataValue},
+    transport::Server,
+    Request, Response, Status,
+};
+
+const VALUES: [&str; 3] = ["one", "two", "three"];
+
+fn assert_all_values(metadata: &MetadataMap) {
+    let ascii = metadata
+        .get_all("x-multi")
+        .iter()
+        .map(|v| v.to_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(ascii, VALUES);
+
+    let binary = metadata
+        .get_all_bin("x-multi-bin")
+        .iter()
+        .map(|v| v.to_bytes().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(binary, VALUES);
+}
+
+#[tokio::test]
+async fn duplicate_keys_round_trip() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            assert_all_values(req.metadata());
+
+            let mut res = Response::new(Output {});
+            for value in req.metadata().get_all("x-multi") {
+                res.metadata_mut().append("x-multi", value.clone());
+            }
+            for value in req.metadata().get_all_bin("x-multi-bin") {
+                res.metadata_mut().append_bin("x-multi-bin", value.clone());
+            }
+
+            Ok(res)
+        }
+    }
+
+    let svc = test_server::TestServer::new(Svc);
+
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_shutdown("127.0.0.1:1344".parse().unwrap(), rx.map(drop))
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = test_client::TestClient::connect("http://127.0.0.1:1344")
+        .await
+        .unwrap();
+
+    let mut req = Request::new(Input {});
+    for value in VALUES.iter() {
+        req.metadata_mut()
+            .append("x-multi", MetadataValue::from_static(value));
+        req.metadata_mut()
+            .append_bin("x-multi-bin", MetadataValue::from_bytes(value.as_bytes()));
+    }
+
+    let res = client.unary_call(req).await.unwrap();
+
+    assert_all_values(res.metadata());
+
+    tx.send(()).unwrap();
+
+    jh.await.unwrap();
+}
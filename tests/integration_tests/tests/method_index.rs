@@ -0,0 +1,104 @@
+use integration_tests::pb::{wide_client, wide_server, MethodInput, MethodOutput};
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{Endpoint, Server},
+    Code, Request, Response, Status,
+};
+
+macro_rules! methods {
+    ($($name:ident = $index:literal),*) => {
+        struct Svc;
+
+        #[tonic::async_trait]
+        impl wide_server::Wide for Svc {
+            $(
+                async fn $name(
+                    &self,
+                    _: Request<MethodInput>,
+                ) -> Result<Response<MethodOutput>, Status> {
+                    Ok(Response::new(MethodOutput { method: $index }))
+                }
+            )*
+        }
+    };
+}
+
+methods!(
+    method00 = 0,
+    method01 = 1,
+    method02 = 2,
+    method03 = 3,
+    method04 = 4,
+    method05 = 5,
+    method06 = 6,
+    method07 = 7,
+    method08 = 8,
+    method09 = 9,
+    method10 = 10,
+    method11 = 11,
+    method12 = 12,
+    method13 = 13,
+    method14 = 14,
+    method15 = 15,
+    method16 = 16,
+    method17 = 17,
+    method18 = 18,
+    method19 = 19,
+    method20 = 20,
+    method21 = 21,
+    method22 = 22,
+    method23 = 23,
+    method24 = 24,
+    method25 = 25,
+    method26 = 26,
+    method27 = 27,
+    method28 = 28,
+    method29 = 29,
+    method30 = 30,
+    method31 = 31,
+    method32 = 32
+);
+
+#[tokio::test]
+async fn indexed_methods_dispatch_by_path() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(wide_server::WideServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = wide_client::WideClient::new(channel.clone());
+
+    let res = client.method00(MethodInput {}).await.unwrap();
+    assert_eq!(res.into_inner().method, 0);
+
+    let res = client.method17(MethodInput {}).await.unwrap();
+    assert_eq!(res.into_inner().method, 17);
+
+    let res = client.method32(MethodInput {}).await.unwrap();
+    assert_eq!(res.into_inner().method, 32);
+
+    // a method the service does not have
+    let mut grpc = tonic::client::Grpc::new(channel);
+    grpc.ready().await.unwrap();
+    let status = grpc
+        .unary::<_, MethodOutput, _>(
+            Request::new(MethodInput {}),
+            "/wide.Wide/Method33".parse().unwrap(),
+            tonic::codec::ProstCodec::default(),
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(status.code(), Code::Unimplemented);
+}
@@ -0,0 +1,77 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{Endpoint, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn serve(mut server: Server) -> Endpoint {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        server
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    Endpoint::from_shared(format!("http://{}", addr)).unwrap()
+}
+
+#[tokio::test]
+async fn prefixed_calls_reach_prefixed_server() {
+    let endpoint = serve(Server::builder().path_prefix("/v1")).await;
+
+    let channel = endpoint
+        .clone()
+        .path_prefix("/v1")
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    test_client::TestClient::new(channel)
+        .unary_call(Input {})
+        .await
+        .unwrap();
+
+    // calls without the prefix are still routed
+    let channel = endpoint.connect().await.unwrap();
+    test_client::TestClient::new(channel)
+        .unary_call(Input {})
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn prefixed_calls_miss_default_server() {
+    let endpoint = serve(Server::builder()).await;
+
+    let channel = endpoint.clone().connect().await.unwrap();
+    test_client::TestClient::new(channel)
+        .unary_call(Input {})
+        .await
+        .unwrap();
+
+    let channel = endpoint
+        .path_prefix("/v1")
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let status = test_client::TestClient::new(channel)
+        .unary_call(Input {})
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+}
@@ -0,0 +1,59 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{Endpoint, Server},
+    Request, Response, Status,
+};
+
+#[tokio::test]
+async fn pauses_and_resumes_accepting_connections() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mut server = Server::builder();
+    let handle = server.handle();
+
+    tokio::spawn(async move {
+        server
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let endpoint = Endpoint::from_shared(format!("http://{}", addr)).unwrap();
+
+    let mut connected = test_client::TestClient::new(endpoint.connect().await.unwrap());
+    connected.unary_call(Input {}).await.unwrap();
+
+    handle.pause_accept();
+    assert!(handle.is_accept_paused());
+
+    // the connection accepted before keeps being served
+    connected.unary_call(Input {}).await.unwrap();
+
+    // while a new one waits in the backlog
+    let mut waiting = test_client::TestClient::new(endpoint.connect_lazy().unwrap());
+    let call = tokio::spawn(async move { waiting.unary_call(Input {}).await });
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!call.is_finished());
+
+    handle.resume_accept();
+
+    tokio::time::timeout(Duration::from_secs(1), call)
+        .await
+        .expect("call should be served once accepting resumed")
+        .unwrap()
+        .unwrap();
+}
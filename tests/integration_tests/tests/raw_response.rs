@@ -0,0 +1,94 @@
+use bytes::Bytes;
+use futures::StreamExt;
+use futures_util::FutureExt;
+use integration_tests::pb::{test_stream_client, InputStream, OutputStream};
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::sync::oneshot;
+use tonic::{
+    body::BoxBody,
+    codec::{ProstCodec, RawResponse, RawStream},
+    server::{Grpc, ServerStreamingService},
+    transport::{NamedService, Server},
+    Request, Response, Status,
+};
+use tower_service::Service;
+
+#[derive(Clone)]
+struct RawSvc;
+
+impl NamedService for RawSvc {
+    const NAME: &'static str = "stream.TestStream";
+}
+
+impl Service<http::Request<hyper::Body>> for RawSvc {
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<hyper::Body>) -> Self::Future {
+        Box::pin(async move {
+            let mut grpc = Grpc::new(ProstCodec::<OutputStream, InputStream>::default());
+            Ok(grpc.server_streaming_raw(StreamCall, req).await)
+        })
+    }
+}
+
+struct StreamCall;
+
+impl ServerStreamingService<InputStream> for StreamCall {
+    type Response = Bytes;
+    type ResponseStream = RawStream;
+    type Future = futures::future::Ready<Result<RawResponse, Status>>;
+
+    fn call(&mut self, _: Request<InputStream>) -> Self::Future {
+        // An empty `OutputStream` encodes to an empty uncompressed frame.
+        let frame = Bytes::from_static(&[0, 0, 0, 0, 0]);
+        let frames = vec![Ok(frame.clone()), Ok(frame)];
+
+        futures::future::ok(Response::new(RawStream::new(futures::stream::iter(frames))))
+    }
+}
+
+#[tokio::test]
+async fn raw_response_stream() {
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(RawSvc)
+            .serve_with_shutdown("127.0.0.1:1341".parse().unwrap(), rx.map(drop))
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = test_stream_client::TestStreamClient::connect("http://127.0.0.1:1341")
+        .await
+        .unwrap();
+
+    let mut stream = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+
+    assert_eq!(stream.next().await.unwrap().unwrap(), OutputStream {});
+    assert_eq!(stream.next().await.unwrap().unwrap(), OutputStream {});
+    // A non-ok status in the trailers would be yielded as an error here.
+    assert!(stream.next().await.is_none());
+
+    tx.send(()).unwrap();
+
+    jh.await.unwrap();
+}
@@ -0,0 +1,82 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{channel::Redirect, Endpoint, Server, Uri},
+    Code, Request, Response, Status,
+};
+
+struct Svc {
+    redirect_to: Option<Uri>,
+    calls: Arc<AtomicUsize>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+
+        match &self.redirect_to {
+            Some(uri) => Err(Status::redirect("moved", uri)),
+            None => Ok(Response::new(Output {})),
+        }
+    }
+}
+
+async fn serve(redirect_to: Option<Uri>) -> (Uri, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let uri = format!("http://{}", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let svc = test_server::TestServer::new(Svc {
+        redirect_to,
+        calls: calls.clone(),
+    });
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    (uri, calls)
+}
+
+#[tokio::test]
+async fn follows_redirects() {
+    let (alternate, alternate_calls) = serve(None).await;
+    let (primary, primary_calls) = serve(Some(alternate)).await;
+
+    let endpoint = Endpoint::from(primary);
+    let channel = Redirect::new(endpoint.connect_lazy().unwrap(), endpoint);
+    let mut client = test_client::TestClient::new(channel);
+
+    client.unary_call(Input {}).await.unwrap();
+    client.unary_call(Input {}).await.unwrap();
+
+    assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
+    assert_eq!(alternate_calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn stops_after_max_redirects() {
+    let (last, last_calls) = serve(None).await;
+    let (second, _) = serve(Some(last)).await;
+    let (first, _) = serve(Some(second)).await;
+
+    let endpoint = Endpoint::from(first);
+    let channel = Redirect::new(endpoint.connect_lazy().unwrap(), endpoint);
+    let mut client = test_client::TestClient::new(channel);
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    assert!(status.redirect_endpoint().is_some());
+    assert_eq!(last_calls.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,110 @@
+use futures_util::FutureExt;
+use integration_tests::pb::{
+    test_client, test_server, test_stream_client, test_stream_server, Input, InputStream, Output,
+    OutputStream,
+};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{transport::Server, Code, Request, Response, Status};
+
+type Stream<T> = std::pin::Pin<
+    Box<dyn futures::Stream<Item = std::result::Result<T, Status>> + Send + Sync + 'static>,
+>;
+
+#[tokio::test]
+async fn rejects_single_stream() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            unreachable!("rejected by the interceptor")
+        }
+    }
+
+    struct StreamSvc;
+
+    #[tonic::async_trait]
+    impl test_stream_server::TestStream for StreamSvc {
+        type StreamCallStream = Stream<OutputStream>;
+
+        async fn stream_call(
+            &self,
+            _: Request<InputStream>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            let s = messages(200);
+            Ok(Response::new(Box::pin(s) as Self::StreamCallStream))
+        }
+    }
+
+    fn messages(
+        delay_ms: u64,
+    ) -> impl futures::Stream<Item = Result<OutputStream, Status>> + Send + Sync {
+        futures::stream::unfold(0, move |i| async move {
+            if i == 2 {
+                return None;
+            }
+            if i > 0 {
+                tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            }
+            Some((Ok(OutputStream {}), i + 1))
+        })
+    }
+
+    let svc = test_server::TestServer::with_interceptor(Svc, |_: Request<()>| {
+        Err(Status::unavailable("try again later"))
+    });
+    let stream_svc = test_stream_server::TestStreamServer::new(StreamSvc);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .add_service(stream_svc)
+            .serve_with_incoming_shutdown(
+                tokio_stream::wrappers::TcpListenerStream::new(listener),
+                rx.map(drop),
+            )
+            .await
+            .unwrap();
+    });
+
+    let channel = tonic::transport::Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    let mut stream_client = test_stream_client::TestStreamClient::new(channel.clone());
+    let mut client = test_client::TestClient::new(channel);
+
+    let mut stream = stream_client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(stream.message().await.unwrap().is_some());
+
+    // rejected while the stream is still in flight on the same connection
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    assert_eq!(status.message(), "try again later");
+
+    assert!(stream.message().await.unwrap().is_some());
+    assert_eq!(stream.message().await.unwrap(), None);
+
+    // the connection is still usable for new calls
+    let mut stream = stream_client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(stream.message().await.unwrap().is_some());
+    drop(stream);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,85 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::time::Duration;
+use tonic::{transport::Server, Request, Response, Status};
+
+#[tokio::test]
+async fn shuts_down_after_n_calls() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_n("127.0.0.1:1348".parse().unwrap(), 2)
+            .await
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = test_client::TestClient::connect("http://127.0.0.1:1348")
+        .await
+        .unwrap();
+
+    client.unary_call(Input {}).await.unwrap();
+    assert!(!jh.is_finished());
+
+    // both calls are in flight when the first of them completes the count
+    let mut other = client.clone();
+    let (first, second) = tokio::join!(client.unary_call(Input {}), other.unary_call(Input {}));
+    first.unwrap();
+    second.unwrap();
+
+    tokio::time::timeout(Duration::from_secs(1), jh)
+        .await
+        .expect("server should shut down after two calls")
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn does_not_count_cancelled_calls() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_n("127.0.0.1:1349".parse().unwrap(), 1)
+            .await
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = test_client::TestClient::connect("http://127.0.0.1:1349")
+        .await
+        .unwrap();
+
+    // dropping the call resets its stream before the response was sent
+    let cancelled = tokio::time::timeout(Duration::from_millis(20), client.unary_call(Input {}));
+    assert!(cancelled.await.is_err());
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(!jh.is_finished());
+
+    client.unary_call(Input {}).await.unwrap();
+
+    tokio::time::timeout(Duration::from_secs(1), jh)
+        .await
+        .expect("server should shut down after the completed call")
+        .unwrap()
+        .unwrap();
+}
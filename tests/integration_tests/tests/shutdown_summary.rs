@@ -0,0 +1,146 @@
+use futures_util::FutureExt;
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::ShutdownSummary, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc(Duration);
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        tokio::time::sleep(self.0).await;
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn call_then_shut_down(mut server: Server, handler: Duration) -> ShutdownSummary {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let jh = tokio::spawn(async move {
+        server
+            .add_service(test_server::TestServer::new(Svc(handler)))
+            .serve_with_incoming_shutdown(incoming, rx.map(drop))
+            .await
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+
+    let call = tokio::spawn(async move { client.unary_call(Input {}).await });
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    tx.send(()).unwrap();
+
+    let summary = jh.await.unwrap().unwrap();
+    let _ = call.await.unwrap();
+    summary
+}
+
+#[tokio::test]
+async fn drains_calls_in_flight() {
+    let summary = call_then_shut_down(Server::builder(), Duration::from_millis(100)).await;
+
+    assert!(!summary.timed_out());
+    assert_eq!(summary.connections_drained(), 1);
+    assert_eq!(summary.connections_closed(), 0);
+    assert_eq!(summary.calls_completed(), 1);
+    assert_eq!(summary.calls_cancelled(), 0);
+}
+
+#[tokio::test]
+async fn cancels_calls_after_drain_timeout() {
+    let server = Server::builder().drain_timeout(Duration::from_millis(100));
+    let summary = call_then_shut_down(server, Duration::from_secs(5)).await;
+
+    assert!(summary.timed_out());
+    assert_eq!(summary.connections_drained(), 0);
+    assert_eq!(summary.connections_closed(), 1);
+    assert_eq!(summary.calls_completed(), 0);
+    assert_eq!(summary.calls_cancelled(), 1);
+}
+
+#[tokio::test]
+async fn waits_for_connections_once_incoming_ends() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    // accepts a single connection
+    let incoming =
+        futures_util::stream::once(async move { listener.accept().await.map(|(io, _)| io) });
+    let (_tx, rx) = oneshot::channel::<()>();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc(Duration::from_millis(0))))
+            .serve_with_incoming_shutdown(incoming, rx.map(drop))
+            .await
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+
+    // the connection is still served after the incoming stream ended
+    client.unary_call(Input {}).await.unwrap();
+    assert!(!jh.is_finished());
+
+    drop(client);
+
+    let summary = tokio::time::timeout(Duration::from_secs(2), jh)
+        .await
+        .expect("server should stop once its connection closed")
+        .unwrap()
+        .unwrap();
+
+    assert!(!summary.timed_out());
+    assert_eq!(summary.connections_drained(), 1);
+    assert_eq!(summary.connections_closed(), 0);
+    assert_eq!(summary.calls_completed(), 1);
+}
+
+#[tokio::test]
+async fn returns_once_incoming_ends_without_signal() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    // accepts a single connection
+    let incoming =
+        futures_util::stream::once(async move { listener.accept().await.map(|(io, _)| io) });
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc(Duration::from_millis(0))))
+            .serve_with_incoming(incoming)
+            .await
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+
+    // there is no shutdown signal to wait for, so the server returns while
+    // its connection is still open
+    tokio::time::timeout(Duration::from_secs(2), jh)
+        .await
+        .expect("server should return once the incoming stream ended")
+        .unwrap()
+        .unwrap();
+
+    // and the connection is still served
+    client.unary_call(Input {}).await.unwrap();
+}
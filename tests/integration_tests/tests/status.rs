@@ -5,7 +5,7 @@ use integration_tests::pb::{
     OutputStream,
 };
 use std::time::Duration;
-use tokio::sync::oneshot;
+use tokio::{net::TcpListener, sync::oneshot};
 use tonic::metadata::{MetadataMap, MetadataValue};
 use tonic::{transport::Server, Code, Request, Response, Status};
 
@@ -173,6 +173,62 @@ async fn status_from_server_stream() {
     assert_eq!(stream.message().await.unwrap(), None);
 }
 
+#[tokio::test]
+async fn status_with_details_from_server_stream() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_stream_server::TestStream for Svc {
+        type StreamCallStream = Stream<OutputStream>;
+
+        async fn stream_call(
+            &self,
+            _: Request<InputStream>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            let s = futures::stream::iter(vec![
+                Ok(OutputStream {}),
+                Err(Status::with_details(
+                    Code::ResourceExhausted,
+                    "Too many requests",
+                    Bytes::from_static(&[1, 2, 3]),
+                )),
+            ]);
+            Ok(Response::new(Box::pin(s) as Self::StreamCallStream))
+        }
+    }
+
+    let svc = test_stream_server::TestStreamServer::new(Svc);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_stream_client::TestStreamClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    let mut stream = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+
+    assert!(stream.message().await.unwrap().is_some());
+
+    // the status arrives in the trailers, after the message
+    let err = stream.message().await.unwrap_err();
+    assert_eq!(err.code(), Code::ResourceExhausted);
+    assert_eq!(err.message(), "Too many requests");
+    assert_eq!(err.details(), &[1, 2, 3]);
+}
+
 fn trace_init() {
     let _ = tracing_subscriber::FmtSubscriber::builder()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
@@ -0,0 +1,53 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{Endpoint, Server},
+    Code, Request, Response, Status,
+};
+
+#[tokio::test]
+async fn rejects_streams_opened_too_fast() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .stream_rate_limit_per_connection(10, Duration::from_secs(1), 5)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+
+    // each call completes before the next one is opened, so only the rate
+    // of new streams is limited
+    let mut rejected = 0;
+    for _ in 0..20 {
+        if let Err(status) = client.unary_call(Input {}).await {
+            assert_eq!(status.code(), Code::ResourceExhausted);
+            rejected += 1;
+        }
+    }
+    assert!(rejected >= 10, "only {} streams were rejected", rejected);
+
+    // the connection may open new streams again once the bucket refilled
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    client.unary_call(Input {}).await.unwrap();
+}
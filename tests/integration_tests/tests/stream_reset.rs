@@ -0,0 +1,54 @@
+use integration_tests::pb::{test_client, Input};
+use std::error::Error as _;
+use tokio::net::TcpListener;
+use tonic::{transport::Endpoint, Code};
+
+/// Serve h2 on a random port, resetting every stream with `reason`.
+async fn reset_streams(reason: h2::Reason) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (io, _) = listener.accept().await.unwrap();
+        let mut conn = h2::server::handshake(io).await.unwrap();
+
+        while let Some(Ok((_, mut respond))) = conn.accept().await {
+            respond.send_reset(reason);
+        }
+    });
+
+    format!("http://{}", addr)
+}
+
+async fn call(reason: h2::Reason) -> tonic::Status {
+    let channel = Endpoint::from_shared(reset_streams(reason).await)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+
+    client.unary_call(Input {}).await.unwrap_err()
+}
+
+#[tokio::test]
+async fn maps_reset_reasons() {
+    let cases = [
+        (h2::Reason::REFUSED_STREAM, Code::Unavailable),
+        (h2::Reason::ENHANCE_YOUR_CALM, Code::ResourceExhausted),
+        (h2::Reason::CANCEL, Code::Cancelled),
+        (h2::Reason::INTERNAL_ERROR, Code::Internal),
+    ];
+
+    for &(reason, code) in &cases {
+        let status = call(reason).await;
+        assert_eq!(status.code(), code, "{:?}", reason);
+
+        // the reason is kept as the source of the status
+        let source = status
+            .source()
+            .and_then(|err| err.downcast_ref::<h2::Error>())
+            .unwrap();
+        assert_eq!(source.reason(), Some(reason));
+    }
+}
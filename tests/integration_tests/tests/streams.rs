@@ -0,0 +1,195 @@
+use futures_util::FutureExt;
+use integration_tests::pb::{test_stream_client, test_stream_server, InputStream, OutputStream};
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    net::TcpListener,
+    sync::{mpsc, oneshot},
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{
+    body::BoxBody,
+    transport::{NamedService, Server},
+    Code, Request, Response, Status,
+};
+use tower_service::Service;
+
+type Stream<T> = std::pin::Pin<
+    Box<dyn futures::Stream<Item = std::result::Result<T, Status>> + Send + Sync + 'static>,
+>;
+
+#[tokio::test]
+async fn empty_stream() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_stream_server::TestStream for Svc {
+        type StreamCallStream = Stream<OutputStream>;
+
+        async fn stream_call(
+            &self,
+            _: Request<InputStream>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            let s = futures::stream::empty();
+            Ok(Response::new(Box::pin(s) as Self::StreamCallStream))
+        }
+    }
+
+    let svc = test_stream_server::TestStreamServer::new(Svc);
+
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_shutdown("127.0.0.1:1342".parse().unwrap(), rx.map(drop))
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = test_stream_client::TestStreamClient::connect("http://127.0.0.1:1342")
+        .await
+        .unwrap();
+
+    let mut stream = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+
+    let message = tokio::time::timeout(Duration::from_secs(1), stream.message())
+        .await
+        .expect("empty stream should end promptly");
+    assert_eq!(message.unwrap(), None);
+
+    let trailers = stream.trailers().await.unwrap().unwrap();
+    let status = Status::from_header_map(&trailers.into_headers()).unwrap();
+    assert_eq!(status.code(), Code::Ok);
+
+    tx.send(()).unwrap();
+
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn trailers_only_empty_stream() {
+    // Responds to every call with a trailers-only response, which is what
+    // other gRPC implementations send for a stream without messages.
+    #[derive(Clone)]
+    struct Svc;
+
+    impl NamedService for Svc {
+        const NAME: &'static str = "stream.TestStream";
+    }
+
+    impl Service<http::Request<hyper::Body>> for Svc {
+        type Response = http::Response<BoxBody>;
+        type Error = Infallible;
+        type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: http::Request<hyper::Body>) -> Self::Future {
+            Box::pin(async move {
+                Ok(http::Response::builder()
+                    .status(200)
+                    .header("content-type", "application/grpc")
+                    .header("grpc-status", "0")
+                    .body(tonic::body::empty_body())
+                    .unwrap())
+            })
+        }
+    }
+
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(Svc)
+            .serve_with_shutdown("127.0.0.1:1343".parse().unwrap(), rx.map(drop))
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = test_stream_client::TestStreamClient::connect("http://127.0.0.1:1343")
+        .await
+        .unwrap();
+
+    let mut stream = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+
+    let message = tokio::time::timeout(Duration::from_secs(1), stream.message())
+        .await
+        .expect("trailers-only response should end promptly");
+    assert_eq!(message.unwrap(), None);
+
+    tx.send(()).unwrap();
+
+    jh.await.unwrap();
+}
+
+#[tokio::test]
+async fn receiver_stream() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_stream_server::TestStream for Svc {
+        type StreamCallStream = ReceiverStream<Result<OutputStream, Status>>;
+
+        async fn stream_call(
+            &self,
+            _: Request<InputStream>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            let (tx, rx) = mpsc::channel(4);
+
+            tokio::spawn(async move {
+                for _ in 0..3 {
+                    tx.send(Ok(OutputStream {})).await.unwrap();
+                }
+            });
+
+            Ok(Response::new(ReceiverStream::new(rx)))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_stream_server::TestStreamServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_stream_client::TestStreamClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    let mut stream = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+
+    let mut received = 0;
+    while stream.message().await.unwrap().is_some() {
+        received += 1;
+    }
+    assert_eq!(received, 3);
+}
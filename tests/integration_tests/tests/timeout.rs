@@ -90,3 +90,77 @@ async fn run_service_in_background(latency: Duration, server_timeout: Duration)
 
     addr
 }
+
+#[tokio::test]
+async fn picks_method_timeout_if_none_is_set() {
+    let addr = run_service_in_background(Duration::from_secs(1), Duration::from_secs(100)).await;
+
+    let mut client = test_client::TestClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap()
+        .method_timeout("UnaryCall", Duration::from_millis(100));
+
+    let res = client.unary_call(Request::new(Input {})).await;
+    let err = res.unwrap_err();
+    assert!(err.message().contains("Timeout expired"));
+    assert_eq!(err.code(), Code::Cancelled);
+}
+
+#[tokio::test]
+async fn request_timeout_overrides_method_timeout() {
+    let addr =
+        run_service_in_background(Duration::from_millis(200), Duration::from_secs(100)).await;
+
+    let mut client = test_client::TestClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap()
+        .method_timeout("UnaryCall", Duration::from_millis(50));
+
+    let mut req = Request::new(Input {});
+    req.set_timeout(Duration::from_secs(10));
+
+    client.unary_call(req).await.unwrap();
+}
+
+#[tokio::test]
+async fn reads_deadline_remaining() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            let remaining = req.deadline_remaining();
+
+            if req.metadata().get("grpc-timeout").is_some() {
+                let remaining = remaining.expect("deadline is set");
+                assert!(remaining > Duration::from_millis(500));
+                assert!(remaining <= Duration::from_secs(1));
+            } else {
+                assert_eq!(remaining, None);
+            }
+
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_client::TestClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    client.unary_call(Input {}).await.unwrap();
+
+    let mut req = Request::new(Input {});
+    req.set_timeout(Duration::from_secs(1));
+    client.unary_call(req).await.unwrap();
+}
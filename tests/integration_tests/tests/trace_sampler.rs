@@ -0,0 +1,91 @@
+use integration_tests::pb::{test_stream_client, test_stream_server, InputStream, OutputStream};
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
+use tokio::net::TcpListener;
+use tokio_stream::StreamExt;
+use tonic::{
+    transport::{server::TraceSampler, Endpoint, Server},
+    Request, Response, Status,
+};
+
+type Stream<T> =
+    std::pin::Pin<Box<dyn futures::Stream<Item = Result<T, Status>> + Send + Sync + 'static>>;
+
+#[tokio::test]
+async fn traces_unsampled_calls_failing_in_trailers() {
+    struct Svc {
+        fail: Arc<AtomicBool>,
+    }
+
+    #[tonic::async_trait]
+    impl test_stream_server::TestStream for Svc {
+        type StreamCallStream = Stream<OutputStream>;
+
+        async fn stream_call(
+            &self,
+            _: Request<InputStream>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            let last = if self.fail.load(Ordering::SeqCst) {
+                Err(Status::unavailable("unavailable"))
+            } else {
+                Ok(OutputStream {})
+            };
+            let s = futures::stream::iter(vec![Ok(OutputStream {}), last]);
+            Ok(Response::new(Box::pin(s) as Self::StreamCallStream))
+        }
+    }
+
+    let fail = Arc::new(AtomicBool::new(false));
+    let spans = Arc::new(AtomicUsize::new(0));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let svc = test_stream_server::TestStreamServer::new(Svc { fail: fail.clone() });
+    let trace_spans = spans.clone();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .trace_fn(move |request| {
+                assert_eq!(request.uri().path(), "/stream.TestStream/StreamCall");
+                trace_spans.fetch_add(1, Ordering::SeqCst);
+                tracing::info_span!("grpc")
+            })
+            .trace_sampler(TraceSampler::new(0.0))
+            .add_service(svc)
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_stream_client::TestStreamClient::new(channel);
+
+    let mut stream = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+    while let Some(message) = stream.next().await {
+        message.unwrap();
+    }
+    assert_eq!(spans.load(Ordering::SeqCst), 0);
+
+    fail.store(true, Ordering::SeqCst);
+
+    // the status is only sent in the trailers, after the first message
+    let mut stream = client
+        .stream_call(InputStream {})
+        .await
+        .unwrap()
+        .into_inner();
+    stream.next().await.unwrap().unwrap();
+    assert!(stream.next().await.unwrap().is_err());
+    assert_eq!(spans.load(Ordering::SeqCst), 1);
+}
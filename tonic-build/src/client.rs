@@ -93,6 +93,15 @@ pub fn generate<T: Service>(
                     self
                 }
 
+                /// Set a default timeout for calls to `method`, the name of a method
+                /// of this service as written in the proto file.
+                ///
+                /// This is only applied to requests that do not already have a timeout.
+                pub fn method_timeout(mut self, method: &str, timeout: std::time::Duration) -> Self {
+                    self.inner = self.inner.method_timeout(format!("/{}/{}", #path, method), timeout);
+                    self
+                }
+
                 #methods
             }
         }
@@ -18,6 +18,7 @@ pub fn configure() -> Builder {
         extern_path: Vec::new(),
         field_attributes: Vec::new(),
         type_attributes: Vec::new(),
+        bytes: Vec::new(),
         server_attributes: Attributes::default(),
         client_attributes: Attributes::default(),
         proto_path: "super".to_string(),
@@ -219,6 +220,7 @@ pub struct Builder {
     pub(crate) extern_path: Vec<(String, String)>,
     pub(crate) field_attributes: Vec<(String, String)>,
     pub(crate) type_attributes: Vec<(String, String)>,
+    pub(crate) bytes: Vec<String>,
     pub(crate) server_attributes: Attributes,
     pub(crate) client_attributes: Attributes,
     pub(crate) proto_path: String,
@@ -297,6 +299,29 @@ impl Builder {
         self
     }
 
+    /// Generate `bytes::Bytes` instead of `Vec<u8>` for the matched `bytes`
+    /// fields.
+    ///
+    /// Passed directly to `prost_build::Config.bytes`, see there for how
+    /// paths are matched. Such fields are decoded without copying them out of
+    /// the buffer the message was received into, which pays off for large
+    /// binary payloads. The decoded field keeps that buffer alive, so this is
+    /// best left off for small fields of long lived messages.
+    ///
+    /// ```
+    /// # let builder = tonic_build::configure();
+    /// builder.bytes(&[".routeguide.Feature.thumbnail"]);
+    /// ```
+    pub fn bytes<I, S>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.bytes
+            .extend(paths.into_iter().map(|path| path.as_ref().to_string()));
+        self
+    }
+
     /// Add additional attribute to matched server `mod`s. Matches on the package name.
     pub fn server_mod_attribute<P: AsRef<str>, A: AsRef<str>>(
         mut self,
@@ -408,6 +433,9 @@ impl Builder {
         for (prost_path, attr) in self.type_attributes.iter() {
             config.type_attribute(prost_path, attr);
         }
+        if !self.bytes.is_empty() {
+            config.bytes(&self.bytes);
+        }
         if self.compile_well_known_types {
             config.compile_well_known_types();
         }
@@ -4,6 +4,10 @@ use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use syn::{Ident, Lit, LitStr};
 
+/// The most methods a generated server dispatches to by matching on the path
+/// of the request, instead of looking it up in a `MethodIndex`.
+const MATCH_METHODS: usize = 32;
+
 /// Generate service for Server.
 ///
 /// This takes some `Service` and will generate a `TokenStream` that contains
@@ -16,6 +20,41 @@ pub fn generate<T: Service>(
     attributes: &Attributes,
 ) -> TokenStream {
     let methods = generate_methods(service, proto_path, compile_well_known_types);
+    let index_methods = methods.len() > MATCH_METHODS;
+
+    // matching on the path is faster than looking it up in a `MethodIndex`
+    // unless the service has many methods
+    let (methods_field, methods_new, methods_clone, dispatch) = if index_methods {
+        let paths = methods.iter().map(|(path, _)| path);
+
+        (
+            quote! { methods: Arc<MethodIndex>, },
+            quote! { methods: Arc::new(MethodIndex::new(&[#(#paths),*])), },
+            quote! { methods: self.methods.clone(), },
+            quote! { self.methods.get(req.uri().path()) },
+        )
+    } else {
+        (quote! {}, quote! {}, quote! {}, quote! { req.uri().path() })
+    };
+
+    let methods = methods
+        .iter()
+        .enumerate()
+        .map(|(index, (path, method))| {
+            let pattern = if index_methods {
+                let index = proc_macro2::Literal::usize_unsuffixed(index);
+                quote! { Some(#index) }
+            } else {
+                quote! { #path }
+            };
+
+            quote! {
+                #pattern => {
+                    #method
+                }
+            }
+        })
+        .collect::<TokenStream>();
 
     let server_service = quote::format_ident!("{}Server", service.name());
     let server_trait = quote::format_ident!("{}", service.name());
@@ -85,6 +124,7 @@ pub fn generate<T: Service>(
             #[derive(Debug)]
             pub struct #server_service<T: #server_trait> {
                 inner: _Inner<T>,
+                #methods_field
                 accept_compression_encodings: #compression_config_ty,
                 send_compression_encodings: #compression_config_ty,
             }
@@ -97,6 +137,7 @@ pub fn generate<T: Service>(
                     let inner = _Inner(inner);
                     Self {
                         inner,
+                        #methods_new
                         accept_compression_encodings: Default::default(),
                         send_compression_encodings: Default::default(),
                     }
@@ -129,7 +170,7 @@ pub fn generate<T: Service>(
                 fn call(&mut self, req: http::Request<B>) -> Self::Future {
                     let inner = self.inner.clone();
 
-                    match req.uri().path() {
+                    match #dispatch {
                         #methods
 
                         _ => Box::pin(async move {
@@ -149,6 +190,7 @@ pub fn generate<T: Service>(
                     let inner = self.inner.clone();
                     Self {
                         inner,
+                        #methods_clone
                         accept_compression_encodings: self.accept_compression_encodings,
                         send_compression_encodings: self.send_compression_encodings,
                     }
@@ -291,8 +333,8 @@ fn generate_methods<T: Service>(
     service: &T,
     proto_path: &str,
     compile_well_known_types: bool,
-) -> TokenStream {
-    let mut stream = TokenStream::new();
+) -> Vec<(Lit, TokenStream)> {
+    let mut methods = Vec::new();
 
     for method in service.methods() {
         let path = format!(
@@ -343,15 +385,10 @@ fn generate_methods<T: Service>(
             ),
         };
 
-        let method = quote! {
-            #method_path => {
-                #method_stream
-            }
-        };
-        stream.extend(method);
+        methods.push((method_path, method_stream));
     }
 
-    stream
+    methods
 }
 
 fn generate_unary<T: Method>(
@@ -377,10 +414,9 @@ fn generate_unary<T: Method>(
 
             fn call(&mut self, request: tonic::Request<#request>) -> Self::Future {
                 let inner = self.0.clone();
-                let fut = async move {
+                tonic::codegen::call_handler(request, |request| async move {
                     (*inner).#method_ident(request).await
-                };
-                Box::pin(fut)
+                })
             }
         }
 
@@ -429,10 +465,9 @@ fn generate_server_streaming<T: Method>(
 
             fn call(&mut self, request: tonic::Request<#request>) -> Self::Future {
                 let inner = self.0.clone();
-                let fut = async move {
+                tonic::codegen::call_handler(request, |request| async move {
                     (*inner).#method_ident(request).await
-                };
-                Box::pin(fut)
+                })
             }
         }
 
@@ -478,11 +513,9 @@ fn generate_client_streaming<T: Method>(
 
             fn call(&mut self, request: tonic::Request<tonic::Streaming<#request>>) -> Self::Future {
                 let inner = self.0.clone();
-                let fut = async move {
+                tonic::codegen::call_handler(request, |request| async move {
                     (*inner).#method_ident(request).await
-
-                };
-                Box::pin(fut)
+                })
             }
         }
 
@@ -532,10 +565,9 @@ fn generate_streaming<T: Method>(
 
             fn call(&mut self, request: tonic::Request<tonic::Streaming<#request>>) -> Self::Future {
                 let inner = self.0.clone();
-                let fut = async move {
+                tonic::codegen::call_handler(request, |request| async move {
                     (*inner).#method_ident(request).await
-                };
-                Box::pin(fut)
+                })
             }
         }
 
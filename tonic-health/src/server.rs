@@ -3,11 +3,15 @@
 use crate::proto::health_server::{Health, HealthServer};
 use crate::proto::{HealthCheckRequest, HealthCheckResponse};
 use crate::ServingStatus;
+use http_body::Body as _;
 use std::collections::HashMap;
+use std::convert::Infallible;
 use std::pin::Pin;
 use std::sync::Arc;
 use tokio::sync::{watch, RwLock};
 use tokio_stream::Stream;
+use tonic::body::BoxBody;
+use tonic::codegen::{BoxFuture, Context, Poll, Service};
 #[cfg(feature = "transport")]
 use tonic::transport::NamedService;
 use tonic::{Request, Response, Status};
@@ -98,6 +102,113 @@ impl HealthReporter {
         let mut writer = self.statuses.write().await;
         let _ = writer.remove(service_name);
     }
+
+    /// Creates an `HttpHealthService` that serves the statuses of this
+    /// reporter as plain HTTP responses.
+    pub fn http_service(&self) -> HttpHealthService {
+        HttpHealthService {
+            statuses: self.statuses.clone(),
+        }
+    }
+}
+
+/// A plain HTTP health endpoint, for probes that do not speak gRPC.
+///
+/// It is routed under the `/healthz` path alongside the gRPC services:
+///
+/// - `GET /healthz` returns `200 OK` if all services are `Serving` and
+///   `503 Service Unavailable` otherwise.
+/// - `GET /healthz/{service}` returns `200 OK` if that service is `Serving`,
+///   `503 Service Unavailable` if it is not and `404 Not Found` if its status
+///   was never set.
+///
+/// HTTP/1 probes require the server to be built with `accept_http1(true)`.
+///
+/// ```no_run
+/// # use tonic::transport::Server;
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let (reporter, health_service) = tonic_health::server::health_reporter();
+///
+/// Server::builder()
+///     .accept_http1(true)
+///     .add_service(health_service)
+///     .add_service(reporter.http_service())
+///     .serve("[::1]:50051".parse()?)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct HttpHealthService {
+    statuses: Arc<RwLock<HashMap<String, StatusPair>>>,
+}
+
+impl HttpHealthService {
+    async fn respond(
+        statuses: Arc<RwLock<HashMap<String, StatusPair>>>,
+        method: http::Method,
+        path: String,
+    ) -> http::Response<BoxBody> {
+        if method != http::Method::GET && method != http::Method::HEAD {
+            return http_response(http::StatusCode::METHOD_NOT_ALLOWED, "method not allowed");
+        }
+
+        let reader = statuses.read().await;
+
+        let serving = match path.trim_end_matches('/') {
+            "/healthz" => reader
+                .values()
+                .all(|(_, rx)| *rx.borrow() == ServingStatus::Serving),
+            path => match path
+                .strip_prefix("/healthz/")
+                .and_then(|service| reader.get(service))
+            {
+                Some((_, rx)) => *rx.borrow() == ServingStatus::Serving,
+                None => return http_response(http::StatusCode::NOT_FOUND, "not found"),
+            },
+        };
+
+        if serving {
+            http_response(http::StatusCode::OK, "ok")
+        } else {
+            http_response(http::StatusCode::SERVICE_UNAVAILABLE, "not serving")
+        }
+    }
+}
+
+fn http_response(status: http::StatusCode, body: &'static str) -> http::Response<BoxBody> {
+    let body = http_body::Full::new(bytes::Bytes::from_static(body.as_bytes()))
+        .map_err(|err| match err {})
+        .boxed();
+
+    http::Response::builder()
+        .status(status)
+        .header(http::header::CONTENT_TYPE, "text/plain")
+        .body(body)
+        .expect("valid response")
+}
+
+impl<B> Service<http::Request<B>> for HttpHealthService {
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let statuses = self.statuses.clone();
+        let method = req.method().clone();
+        let path = req.uri().path().to_string();
+
+        Box::pin(async move { Ok(Self::respond(statuses, method, path).await) })
+    }
+}
+
+#[cfg(feature = "transport")]
+impl NamedService for HttpHealthService {
+    const NAME: &'static str = "healthz";
 }
 
 struct HealthService {
@@ -164,7 +275,7 @@ impl Health for HealthService {
 mod tests {
     use crate::proto::health_server::Health;
     use crate::proto::HealthCheckRequest;
-    use crate::server::{HealthReporter, HealthService};
+    use crate::server::{HealthReporter, HealthService, HttpHealthService};
     use crate::ServingStatus;
     use std::collections::HashMap;
     use std::sync::Arc;
@@ -291,4 +402,47 @@ mod tests {
         let item = resp.next().await;
         assert!(item.is_none());
     }
+
+    async fn http_status(service: &mut HttpHealthService, path: &str) -> http::StatusCode {
+        let req = http::Request::get(path).body(()).unwrap();
+        tonic::codegen::Service::call(service, req)
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_http_service() {
+        let (mut reporter, _) = make_test_service().await;
+        let mut service = reporter.http_service();
+
+        // Registered service - initial state
+        assert_eq!(
+            http_status(&mut service, "/healthz").await,
+            http::StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            http_status(&mut service, "/healthz/TestService").await,
+            http::StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        // Unregistered service
+        assert_eq!(
+            http_status(&mut service, "/healthz/Unregistered").await,
+            http::StatusCode::NOT_FOUND
+        );
+
+        // Registered service - updated state
+        reporter
+            .set_service_status("TestService", ServingStatus::Serving)
+            .await;
+        assert_eq!(
+            http_status(&mut service, "/healthz").await,
+            http::StatusCode::OK
+        );
+        assert_eq!(
+            http_status(&mut service, "/healthz/TestService").await,
+            http::StatusCode::OK
+        );
+    }
 }
@@ -0,0 +1,90 @@
+use bencher::{benchmark_group, benchmark_main, Bencher};
+use http::uri::PathAndQuery;
+use http_body::Body;
+use tonic::{
+    body::BoxBody,
+    client::Grpc,
+    codec::{FlushMode, ProstCodec},
+    Request, Status,
+};
+
+macro_rules! bench {
+    ($name:ident, $message_size:expr, $message_count:expr) => {
+        bench!($name, $message_size, $message_count, FlushMode::FlushEach);
+    };
+    ($name:ident, $message_size:expr, $message_count:expr, $flush_mode:expr) => {
+        fn $name(b: &mut Bencher) {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .expect("runtime");
+
+            let message = vec![97u8; $message_size];
+            b.bytes = ($message_size * $message_count) as u64;
+
+            b.iter(|| {
+                rt.block_on(async {
+                    let mut grpc =
+                        Grpc::new(tower::service_fn(|request: http::Request<BoxBody>| {
+                            drain(request)
+                        }));
+
+                    let messages = std::iter::repeat(message.clone()).take($message_count);
+                    let mut request = Request::new(futures_util::stream::iter(messages));
+                    request.set_flush_mode($flush_mode);
+
+                    grpc.streaming(
+                        request,
+                        PathAndQuery::from_static("/bench.Bench/Encode"),
+                        ProstCodec::<Vec<u8>, ()>::default(),
+                    )
+                    .await
+                    .unwrap();
+                })
+            })
+        }
+    };
+}
+
+// Polls the encoded request body to completion and responds with an empty
+// trailers-only response.
+async fn drain<B>(request: http::Request<B>) -> Result<http::Response<BoxBody>, Status>
+where
+    B: Body + Unpin,
+    B::Error: std::fmt::Debug,
+{
+    let mut body = request.into_body();
+    while let Some(data) = body.data().await {
+        data.unwrap();
+    }
+
+    Ok(http::Response::builder()
+        .header("grpc-status", "0")
+        .body(tonic::body::empty_body())
+        .unwrap())
+}
+
+// change message size only
+bench!(message_size_1k, 1_000, 10);
+bench!(message_size_100k, 100_000, 10);
+bench!(message_size_1m, 1_000_000, 10);
+
+// change message count only
+bench!(message_count_1, 500, 1);
+bench!(message_count_10, 500, 10);
+bench!(message_count_100, 500, 100);
+
+benchmark_group!(
+    message_size,
+    message_size_1k,
+    message_size_100k,
+    message_size_1m
+);
+
+benchmark_group!(
+    message_count,
+    message_count_1,
+    message_count_10,
+    message_count_100
+);
+
+benchmark_main!(message_size, message_count);
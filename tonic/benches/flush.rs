@@ -0,0 +1,286 @@
+use criterion::{
+    criterion_group, criterion_main,
+    measurement::{Measurement, ValueFormatter},
+    Criterion, Throughput,
+};
+use http::uri::PathAndQuery;
+use http_body::Body;
+use hyper::server::conn::Http;
+use std::{
+    io,
+    pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
+    task::{Context, Poll},
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpListener,
+    runtime::Runtime,
+};
+use tonic::{
+    client::Grpc,
+    codec::{FlushMode, ProstCodec},
+    transport::{Channel, Endpoint},
+    Request, Status,
+};
+
+// Streams many small messages over a real HTTP/2 connection, where the flush
+// mode decides how many `DATA` frames and writes the messages take. Each
+// benchmark is measured twice: once in time per call, and once in `DATA`
+// frames per call, as counted by the server reading the connection.
+
+const MESSAGE_SIZE: usize = 50;
+const MESSAGE_COUNT: usize = 1000;
+
+// `DATA` frames read by the server, over all connections.
+static DATA_FRAMES: AtomicU64 = AtomicU64::new(0);
+
+fn flush_mode<M: Measurement>(c: &mut Criterion<M>, group: &str) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("runtime");
+    let channel = rt.block_on(serve_drain());
+
+    let mut group = c.benchmark_group(group);
+    group.throughput(Throughput::Bytes((MESSAGE_SIZE * MESSAGE_COUNT) as u64));
+
+    for (name, flush_mode) in &[
+        ("flush_each", FlushMode::FlushEach),
+        ("coalesce", FlushMode::Coalesce),
+    ] {
+        group.bench_function(*name, |b| {
+            b.iter(|| call(&rt, &channel, *flush_mode));
+        });
+    }
+
+    group.finish();
+}
+
+fn flush_mode_time(c: &mut Criterion) {
+    flush_mode(c, "flush_mode");
+}
+
+fn flush_mode_data_frames(c: &mut Criterion<DataFrames>) {
+    flush_mode(c, "flush_mode_data_frames");
+}
+
+fn call(rt: &Runtime, channel: &Channel, flush_mode: FlushMode) {
+    rt.block_on(async {
+        let mut grpc = Grpc::new(channel.clone());
+        grpc.ready().await.unwrap();
+
+        let messages = std::iter::repeat_with(|| vec![97u8; MESSAGE_SIZE]).take(MESSAGE_COUNT);
+        let mut request = Request::new(futures_util::stream::iter(messages));
+        request.set_flush_mode(flush_mode);
+
+        grpc.streaming(
+            request,
+            PathAndQuery::from_static("/bench.Bench/Encode"),
+            ProstCodec::<Vec<u8>, ()>::default(),
+        )
+        .await
+        .unwrap();
+    })
+}
+
+// Serves a local HTTP/2 connection that polls request bodies to completion
+// and responds with an empty trailers-only response.
+async fn serve_drain() -> Channel {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (io, _) = listener.accept().await.unwrap();
+        let svc = tower::service_fn(|request: http::Request<hyper::Body>| async move {
+            let mut body = request.into_body();
+            while let Some(data) = body.data().await {
+                data.unwrap();
+            }
+
+            Ok::<_, Status>(
+                http::Response::builder()
+                    .header("grpc-status", "0")
+                    .body(tonic::body::empty_body())
+                    .unwrap(),
+            )
+        });
+
+        Http::new()
+            .http2_only(true)
+            .serve_connection(DataFrameCounter::new(io), svc)
+            .await
+            .unwrap();
+    });
+
+    Endpoint::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+const PREFACE_LEN: usize = 24;
+const FRAME_HEADER_LEN: usize = 9;
+const DATA_FRAME: u8 = 0;
+
+// Counts the HTTP/2 `DATA` frames read through it in `DATA_FRAMES`.
+struct DataFrameCounter<T> {
+    inner: T,
+    // bytes of the client preface left to skip
+    preface: usize,
+    header: [u8; FRAME_HEADER_LEN],
+    header_len: usize,
+    // bytes of the current frame's payload left to skip
+    payload: usize,
+}
+
+impl<T> DataFrameCounter<T> {
+    fn new(inner: T) -> Self {
+        DataFrameCounter {
+            inner,
+            preface: PREFACE_LEN,
+            header: [0; FRAME_HEADER_LEN],
+            header_len: 0,
+            payload: 0,
+        }
+    }
+
+    fn scan(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let skip = if self.preface > 0 {
+                &mut self.preface
+            } else if self.payload > 0 {
+                &mut self.payload
+            } else {
+                let len = std::cmp::min(FRAME_HEADER_LEN - self.header_len, data.len());
+                self.header[self.header_len..self.header_len + len].copy_from_slice(&data[..len]);
+                self.header_len += len;
+                data = &data[len..];
+
+                if self.header_len == FRAME_HEADER_LEN {
+                    self.header_len = 0;
+                    self.payload = (self.header[0] as usize) << 16
+                        | (self.header[1] as usize) << 8
+                        | self.header[2] as usize;
+
+                    if self.header[3] == DATA_FRAME {
+                        DATA_FRAMES.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+
+                continue;
+            };
+
+            let len = std::cmp::min(*skip, data.len());
+            *skip -= len;
+            data = &data[len..];
+        }
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for DataFrameCounter<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        futures_util::ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+        self.scan(&buf.filled()[filled..]);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for DataFrameCounter<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+// Measures the number of `DATA` frames read by the server.
+struct DataFrames;
+
+impl Measurement for DataFrames {
+    type Intermediate = u64;
+    type Value = u64;
+
+    fn start(&self) -> Self::Intermediate {
+        DATA_FRAMES.load(Ordering::Relaxed)
+    }
+
+    fn end(&self, start: Self::Intermediate) -> Self::Value {
+        DATA_FRAMES.load(Ordering::Relaxed) - start
+    }
+
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        v1 + v2
+    }
+
+    fn zero(&self) -> Self::Value {
+        0
+    }
+
+    fn to_f64(&self, value: &Self::Value) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        self
+    }
+}
+
+impl ValueFormatter for DataFrames {
+    fn scale_values(&self, _typical_value: f64, _values: &mut [f64]) -> &'static str {
+        "frames"
+    }
+
+    fn scale_throughputs(
+        &self,
+        _typical_value: f64,
+        throughput: &Throughput,
+        values: &mut [f64],
+    ) -> &'static str {
+        // frames per KiB of messages
+        if let Throughput::Bytes(bytes) = throughput {
+            for value in values {
+                *value /= *bytes as f64 / 1024.0;
+            }
+        }
+
+        "frames/KiB"
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "frames"
+    }
+}
+
+criterion_group!(time, flush_mode_time);
+
+// The count does not vary, which the plots cannot show. `criterion_group!`
+// applies the arguments after the config, which enables them again, so this
+// group is set up by hand.
+fn data_frames() {
+    let mut criterion = Criterion::default()
+        .with_measurement(DataFrames)
+        .configure_from_args()
+        .without_plots();
+
+    flush_mode_data_frames(&mut criterion);
+}
+
+criterion_main!(time, data_frames);
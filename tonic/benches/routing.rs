@@ -0,0 +1,215 @@
+use bencher::{benchmark_group, benchmark_main, black_box, Bencher};
+use futures_util::{future, FutureExt};
+use std::task::{Context, Poll};
+use tonic::{
+    body::BoxBody,
+    codegen::MethodIndex,
+    transport::{Body, NamedService, Server},
+};
+use tower::Service;
+
+// Generated servers dispatch a request by matching its path against the path
+// of every method, or by looking it up in a `MethodIndex` when they have more
+// methods than a `match` is faster for. Both are benchmarked dispatching to
+// the last method, the worst case of the `match`.
+
+macro_rules! paths {
+    ($($tens:literal)*; $ones:tt) => {
+        [$(paths!(@row $tens; $ones)),*].concat()
+    };
+    (@row $tens:literal; [$($ones:literal)*]) => {
+        [$(concat!("/bench.Service/Method", $tens, $ones)),*]
+    };
+}
+
+macro_rules! match_dispatch {
+    ($path:expr, $($tens:literal)*; $ones:tt) => {
+        match_dispatch!(@arms $path; []; $($tens)*; $ones)
+    };
+    (@arms $path:expr; [$($arms:tt)*]; $tens:literal $($rest:literal)*; [$($ones:literal)*]) => {
+        match_dispatch!(
+            @arms $path;
+            [$($arms)* $(concat!("/bench.Service/Method", $tens, $ones) => Some($tens * 10 + $ones),)*];
+            $($rest)*;
+            [$($ones)*]
+        )
+    };
+    (@arms $path:expr; [$($arms:tt)*]; ; $ones:tt) => {
+        match $path {
+            $($arms)*
+            _ => None,
+        }
+    };
+}
+
+fn match_1_method(b: &mut Bencher) {
+    let path = String::from("/bench.Service/Method00");
+
+    b.iter(|| match black_box(path.as_str()) {
+        "/bench.Service/Method00" => Some(0),
+        _ => None,
+    })
+}
+
+fn match_8_methods(b: &mut Bencher) {
+    let path = String::from("/bench.Service/Method07");
+
+    b.iter(|| match_dispatch!(black_box(path.as_str()), 0; [0 1 2 3 4 5 6 7]))
+}
+
+fn match_32_methods(b: &mut Bencher) {
+    let path = String::from("/bench.Service/Method31");
+
+    b.iter(|| {
+        match_dispatch!(
+            black_box(path.as_str()),
+            0 1 2;
+            [0 1 2 3 4 5 6 7 8 9]
+        )
+        .or_else(|| match_dispatch!(black_box(path.as_str()), 3; [0 1]))
+    })
+}
+
+fn match_100_methods(b: &mut Bencher) {
+    let path = String::from("/bench.Service/Method99");
+
+    b.iter(|| {
+        match_dispatch!(
+            black_box(path.as_str()),
+            0 1 2 3 4 5 6 7 8 9;
+            [0 1 2 3 4 5 6 7 8 9]
+        )
+    })
+}
+
+fn index_1_method(b: &mut Bencher) {
+    let methods = MethodIndex::new(&["/bench.Service/Method00"]);
+    let path = String::from("/bench.Service/Method00");
+
+    b.iter(|| methods.get(black_box(path.as_str())))
+}
+
+fn index_8_methods(b: &mut Bencher) {
+    let methods = MethodIndex::new(&paths!(0; [0 1 2 3 4 5 6 7]));
+    let path = String::from("/bench.Service/Method07");
+
+    b.iter(|| methods.get(black_box(path.as_str())))
+}
+
+fn index_32_methods(b: &mut Bencher) {
+    let methods =
+        MethodIndex::new(&[paths!(0 1 2; [0 1 2 3 4 5 6 7 8 9]), paths!(3; [0 1])].concat());
+    let path = String::from("/bench.Service/Method31");
+
+    b.iter(|| methods.get(black_box(path.as_str())))
+}
+
+fn index_100_methods(b: &mut Bencher) {
+    let methods = MethodIndex::new(&paths!(0 1 2 3 4 5 6 7 8 9; [0 1 2 3 4 5 6 7 8 9]));
+    let path = String::from("/bench.Service/Method99");
+
+    b.iter(|| methods.get(black_box(path.as_str())))
+}
+
+// The router is benchmarked dispatching to the service added first, which is
+// the last one its chain of routes reaches. `direct` calls that service
+// without a router, so the difference is the cost of routing.
+
+macro_rules! names {
+    ($($tens:literal)*; $ones:tt) => {
+        [$(names!(@row $tens; $ones)),*]
+    };
+    (@row $tens:literal; [$($ones:literal)*]) => {
+        [$(concat!("bench.Service", $tens, $ones)),*]
+    };
+}
+
+const NAMES: [[&str; 10]; 10] = names!(0 1 2 3 4 5 6 7 8 9; [0 1 2 3 4 5 6 7 8 9]);
+
+#[derive(Clone)]
+struct Svc<const N: usize>;
+
+impl<const N: usize> NamedService for Svc<N> {
+    const NAME: &'static str = NAMES[N / 10][N % 10];
+}
+
+impl<const N: usize> Service<http::Request<Body>> for Svc<N> {
+    type Response = http::Response<BoxBody>;
+    type Error = std::convert::Infallible;
+    type Future = future::Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _req: http::Request<Body>) -> Self::Future {
+        future::ok(http::Response::new(tonic::body::empty_body()))
+    }
+}
+
+macro_rules! router {
+    ($($n:literal)*) => {
+        Server::builder()
+            .add_service(Svc::<0>)
+            $(.add_service(Svc::<$n>))*
+            .into_service()
+    };
+}
+
+macro_rules! bench_router {
+    ($name:ident, $router:expr) => {
+        fn $name(b: &mut Bencher) {
+            let mut router = $router;
+            let path = format!("/{}/Method", <Svc<0> as NamedService>::NAME);
+
+            b.iter(|| {
+                let request = http::Request::post(path.as_str())
+                    .body(Body::empty())
+                    .unwrap();
+
+                router.call(request).now_or_never().unwrap().unwrap()
+            })
+        }
+    };
+}
+
+bench_router!(direct, Svc::<0>);
+bench_router!(router_1_service, router!());
+bench_router!(router_8_services, router!(1 2 3 4 5 6 7));
+bench_router!(
+    router_100_services,
+    router!(
+        1 2 3 4 5 6 7 8 9
+        10 11 12 13 14 15 16 17 18 19
+        20 21 22 23 24 25 26 27 28 29
+        30 31 32 33 34 35 36 37 38 39
+        40 41 42 43 44 45 46 47 48 49
+        50 51 52 53 54 55 56 57 58 59
+        60 61 62 63 64 65 66 67 68 69
+        70 71 72 73 74 75 76 77 78 79
+        80 81 82 83 84 85 86 87 88 89
+        90 91 92 93 94 95 96 97 98 99
+    )
+);
+
+benchmark_group!(
+    methods,
+    match_1_method,
+    match_8_methods,
+    match_32_methods,
+    match_100_methods,
+    index_1_method,
+    index_8_methods,
+    index_32_methods,
+    index_100_methods
+);
+
+benchmark_group!(
+    router,
+    direct,
+    router_1_service,
+    router_8_services,
+    router_100_services
+);
+
+benchmark_main!(methods, router);
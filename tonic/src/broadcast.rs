@@ -0,0 +1,210 @@
+//! Fan out messages to the response streams of many calls.
+//!
+//! A [`Broadcast`] publishes every message to all of its [`Subscriber`]s,
+//! each of which is a stream that can be returned from a streaming handler
+//! as is. Every subscriber buffers up to the capacity of the broadcast, so
+//! a slow subscriber never blocks publishers; once it falls further behind,
+//! the [`LagPolicy`] decides what happens to it.
+//!
+//! ```
+//! use tonic::broadcast::{Broadcast, LagPolicy};
+//! # #[derive(Clone)]
+//! # struct Note;
+//!
+//! let notes = Broadcast::<Note>::new(128).lag_policy(LagPolicy::Error);
+//!
+//! // in a streaming handler, return a subscription as the response stream
+//! let stream = notes.subscribe();
+//!
+//! // and publish to all current subscribers from anywhere
+//! notes.publish(Note);
+//! # drop(stream);
+//! ```
+
+use crate::Status;
+use futures_core::Stream;
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+
+/// What happens to a subscriber that fell behind by more messages than the
+/// capacity of its [`Broadcast`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LagPolicy {
+    /// Drop the oldest messages the subscriber has not received yet and
+    /// continue with the oldest message still buffered.
+    DropOldest,
+    /// End the subscriber's stream with a `RESOURCE_EXHAUSTED` status.
+    Error,
+}
+
+/// Publishes messages to all of its subscribers.
+///
+/// Cloning a `Broadcast` gives another handle publishing to the same
+/// subscribers. See the [module documentation](self) for an example.
+pub struct Broadcast<T> {
+    tx: broadcast::Sender<T>,
+    lag_policy: LagPolicy,
+}
+
+impl<T: Clone + Send + 'static> Broadcast<T> {
+    /// Create a broadcast that buffers up to `capacity` messages for each
+    /// subscriber.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+
+        Broadcast {
+            tx,
+            lag_policy: LagPolicy::DropOldest,
+        }
+    }
+
+    /// Set what happens to subscribers that fall behind.
+    ///
+    /// Default is [`LagPolicy::DropOldest`].
+    pub fn lag_policy(self, lag_policy: LagPolicy) -> Self {
+        Broadcast { lag_policy, ..self }
+    }
+
+    /// Publish `message` to all current subscribers, returning how many
+    /// there were.
+    pub fn publish(&self, message: T) -> usize {
+        self.tx.send(message).unwrap_or(0)
+    }
+
+    /// Subscribe to all messages published from now on.
+    pub fn subscribe(&self) -> Subscriber<T> {
+        Subscriber {
+            rx: Some(BroadcastStream::new(self.tx.subscribe())),
+            lag_policy: self.lag_policy,
+        }
+    }
+
+    /// Returns the number of current subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+}
+
+impl<T> Clone for Broadcast<T> {
+    fn clone(&self) -> Self {
+        Broadcast {
+            tx: self.tx.clone(),
+            lag_policy: self.lag_policy,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Broadcast<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Broadcast")
+            .field("subscribers", &self.tx.receiver_count())
+            .field("lag_policy", &self.lag_policy)
+            .finish()
+    }
+}
+
+/// A stream of the messages published to a [`Broadcast`].
+///
+/// The stream ends once all handles of the broadcast have been dropped.
+pub struct Subscriber<T> {
+    // `None` once the stream ended with an error
+    rx: Option<BroadcastStream<T>>,
+    lag_policy: LagPolicy,
+}
+
+impl<T: Clone + Send + 'static> Stream for Subscriber<T> {
+    type Item = Result<T, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let lag_policy = self.lag_policy;
+
+        loop {
+            let rx = match self.rx.as_mut() {
+                Some(rx) => rx,
+                None => return Poll::Ready(None),
+            };
+
+            match futures_util::ready!(Pin::new(rx).poll_next(cx)) {
+                Some(Ok(message)) => return Poll::Ready(Some(Ok(message))),
+                Some(Err(BroadcastStreamRecvError::Lagged(_)))
+                    if lag_policy == LagPolicy::DropOldest =>
+                {
+                    // continue with the oldest message still buffered
+                }
+                Some(Err(BroadcastStreamRecvError::Lagged(skipped))) => {
+                    self.rx = None;
+                    return Poll::Ready(Some(Err(Status::resource_exhausted(format!(
+                        "subscriber fell behind by {} messages",
+                        skipped
+                    )))));
+                }
+                None => {
+                    self.rx = None;
+                    return Poll::Ready(None);
+                }
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Subscriber<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscriber")
+            .field("lag_policy", &self.lag_policy)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn drops_oldest_for_slow_subscribers() {
+        let broadcast = Broadcast::new(2);
+        let mut fast = broadcast.subscribe();
+        let mut slow = broadcast.subscribe();
+
+        for i in 0..4 {
+            assert_eq!(broadcast.publish(i), 2);
+            assert_eq!(fast.next().await.unwrap().unwrap(), i);
+        }
+
+        assert_eq!(slow.next().await.unwrap().unwrap(), 2);
+        assert_eq!(slow.next().await.unwrap().unwrap(), 3);
+
+        drop(broadcast);
+        assert!(fast.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn ends_lagging_subscribers_with_error() {
+        let broadcast = Broadcast::new(1).lag_policy(LagPolicy::Error);
+        let mut slow = broadcast.subscribe();
+
+        broadcast.publish(1);
+        broadcast.publish(2);
+
+        let status = slow.next().await.unwrap().unwrap_err();
+        assert_eq!(status.code(), crate::Code::ResourceExhausted);
+        assert!(slow.next().await.is_none());
+    }
+
+    #[test]
+    fn subscribers_of_send_messages_are_sync() {
+        fn assert_sync<T: Sync>(_: &T) {}
+
+        let broadcast = Broadcast::<std::cell::Cell<u8>>::new(1);
+        assert_sync(&broadcast.subscribe());
+    }
+}
@@ -0,0 +1,401 @@
+use crate::Status;
+use http::{HeaderMap, Request, Response};
+use http_body::Body;
+use pin_project::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+use tokio::{sync::Notify, task::futures::TaskLocalFuture};
+use tower_service::Service;
+
+tokio::task_local! {
+    static CURRENT: CancellationToken;
+}
+
+/// Signals that the inbound call a handler is serving has been cancelled.
+///
+/// The [`Server`] runs every handler with the token of its call as the
+/// current token, which is also available from the extensions of the
+/// request. The token is cancelled once the call ends before its response
+/// was sent completely, for example because the client cancelled it, hit
+/// its deadline or went away.
+///
+/// Calls made with a [`client::Grpc`], including generated clients, while a
+/// token is current fail with `CANCELLED` as soon as the token is
+/// cancelled, which abandons the outbound call. So do the response streams
+/// of streaming calls, even if the call returned before the token was
+/// cancelled. Outbound calls made
+/// directly in a handler are already dropped together with the handler, so
+/// this matters for work the handler spawned. Spawned tasks do not inherit
+/// the current token, use [`scope`](CancellationToken::scope) to carry it
+/// over:
+///
+/// ```no_run
+/// # use tonic::{CancellationToken, Request, Response, Status};
+/// # async fn downstream() {}
+/// # async fn handler(request: Request<()>) -> Result<Response<()>, Status> {
+/// if let Some(token) = CancellationToken::current() {
+///     tokio::spawn(token.scope(async move {
+///         // outbound calls made here are cancelled with the inbound call
+///         downstream().await
+///     }));
+/// }
+/// # Ok(Response::new(()))
+/// # }
+/// ```
+///
+/// [`Server`]: crate::transport::Server
+/// [`client::Grpc`]: crate::client::Grpc
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    /// Create a new token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the token of the call the current task is serving, if any.
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Clone::clone).ok()
+    }
+
+    /// Run `future` with this token as the current token.
+    pub fn scope<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        self.scope_future(future)
+    }
+
+    pub(crate) fn scope_future<F: Future>(
+        &self,
+        future: F,
+    ) -> TaskLocalFuture<CancellationToken, F> {
+        CURRENT.scope(self.clone(), future)
+    }
+
+    /// Cancel this token and all of its clones.
+    pub fn cancel(&self) {
+        if !self.inner.cancelled.swap(true, Ordering::AcqRel) {
+            self.inner.notify.notify_waiters();
+        }
+    }
+
+    /// Returns `true` once this token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Wait until this token is cancelled.
+    pub async fn cancelled(&self) {
+        let notified = self.inner.notify.notified();
+        futures_util::pin_mut!(notified);
+        notified.as_mut().enable();
+
+        if !self.is_cancelled() {
+            notified.await;
+        }
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Cancels a token when dropped, unless disarmed first.
+#[derive(Debug)]
+pub(crate) struct CancelGuard(Option<CancellationToken>);
+
+impl CancelGuard {
+    pub(crate) fn new(token: CancellationToken) -> Self {
+        CancelGuard(Some(token))
+    }
+
+    pub(crate) fn disarm(&mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        if let Some(token) = self.0.take() {
+            token.cancel();
+        }
+    }
+}
+
+/// A response body that cancels its call when dropped before it ended.
+#[pin_project]
+#[derive(Debug)]
+pub(crate) struct CancelOnDrop<B> {
+    #[pin]
+    inner: B,
+    guard: CancelGuard,
+}
+
+impl<B> CancelOnDrop<B> {
+    pub(crate) fn new(inner: B, mut guard: CancelGuard) -> Self
+    where
+        B: Body,
+    {
+        // Trailers-Only responses are sent without polling the body.
+        if inner.is_end_stream() {
+            guard.disarm();
+        }
+
+        Self { inner, guard }
+    }
+}
+
+impl<B: Body> Body for CancelOnDrop<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let mut this = self.project();
+        let data = futures_util::ready!(this.inner.as_mut().poll_data(cx));
+
+        if data.is_none() && this.inner.is_end_stream() {
+            this.guard.disarm();
+        }
+
+        Poll::Ready(data)
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let this = self.project();
+        let trailers = futures_util::ready!(this.inner.poll_trailers(cx));
+        this.guard.disarm();
+
+        Poll::Ready(trailers)
+    }
+}
+
+/// Runs every call with a new token that is cancelled once the call ends
+/// before its response was sent.
+///
+/// This wraps the routes directly, below the middleware that turns a
+/// deadline or other errors into a response, so that a call failed by the
+/// server is cancelled as well.
+#[derive(Debug, Clone)]
+pub(crate) struct CancelScope<S> {
+    inner: S,
+}
+
+impl<S> CancelScope<S> {
+    pub(crate) fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for CancelScope<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    ResBody: Body,
+{
+    type Response = Response<CancelOnDrop<ResBody>>;
+    type Error = S::Error;
+    type Future = CancelScopeFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        let token = CancellationToken::new();
+        req.extensions_mut().insert(token.clone());
+
+        CancelScopeFuture {
+            inner: token.scope_future(self.inner.call(req)),
+            guard: Some(CancelGuard::new(token)),
+        }
+    }
+}
+
+#[pin_project]
+#[derive(Debug)]
+pub(crate) struct CancelScopeFuture<F> {
+    #[pin]
+    inner: TaskLocalFuture<CancellationToken, F>,
+    guard: Option<CancelGuard>,
+}
+
+impl<F, B, E> Future for CancelScopeFuture<F>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+    B: Body,
+{
+    type Output = Result<Response<CancelOnDrop<B>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        // a failed call is cancelled by dropping the guard
+        let response = futures_util::ready!(this.inner.poll(cx))?;
+        let guard = this.guard.take().expect("polled after completion");
+
+        Poll::Ready(Ok(response.map(|body| CancelOnDrop::new(body, guard))))
+    }
+}
+
+/// The response body of an outbound call, which fails with `CANCELLED` once
+/// the token that was current when the call was made is cancelled.
+///
+/// This covers the messages of streaming responses, which are read after
+/// the call itself returned.
+#[pin_project]
+pub(crate) struct CancellableBody<B> {
+    #[pin]
+    inner: B,
+    token: Option<CancellationToken>,
+    cancelled: Option<Pin<Box<dyn Future<Output = ()> + Send + Sync>>>,
+}
+
+impl<B> CancellableBody<B> {
+    pub(crate) fn new(inner: B) -> Self {
+        let token = CancellationToken::current();
+        let cancelled = token.clone().map(|token| {
+            let cancelled = async move { token.cancelled().await };
+            Box::pin(cancelled) as Pin<Box<dyn Future<Output = ()> + Send + Sync>>
+        });
+
+        Self {
+            inner,
+            token,
+            cancelled,
+        }
+    }
+}
+
+fn poll_cancelled(
+    token: &Option<CancellationToken>,
+    cancelled: &mut Option<Pin<Box<dyn Future<Output = ()> + Send + Sync>>>,
+    cx: &mut Context<'_>,
+) -> Poll<crate::Error> {
+    let is_cancelled = match (token, cancelled) {
+        (Some(token), _) if token.is_cancelled() => true,
+        (_, Some(cancelled)) => cancelled.as_mut().poll(cx).is_ready(),
+        _ => false,
+    };
+
+    if is_cancelled {
+        Poll::Ready(Status::cancelled("the inbound call was cancelled").into())
+    } else {
+        Poll::Pending
+    }
+}
+
+impl<B> Body for CancellableBody<B>
+where
+    B: Body,
+    B::Error: Into<crate::Error>,
+{
+    type Data = B::Data;
+    type Error = crate::Error;
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let this = self.project();
+
+        if let Poll::Ready(err) = poll_cancelled(this.token, this.cancelled, cx) {
+            return Poll::Ready(Some(Err(err)));
+        }
+
+        this.inner.poll_data(cx).map_err(Into::into)
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let this = self.project();
+
+        if let Poll::Ready(err) = poll_cancelled(this.token, this.cancelled, cx) {
+            return Poll::Ready(Err(err));
+        }
+
+        this.inner.poll_trailers(cx).map_err(Into::into)
+    }
+}
+
+impl<B> fmt::Debug for CancellableBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellableBody")
+            .field("token", &self.token)
+            .finish()
+    }
+}
+
+/// Run an outbound call, failing it once the current token is cancelled.
+pub(crate) async fn or_cancelled<F, T>(call: F) -> Result<T, Status>
+where
+    F: Future<Output = Result<T, Status>>,
+{
+    let token = match CancellationToken::current() {
+        Some(token) => token,
+        None => return call.await,
+    };
+
+    tokio::select! {
+        biased;
+
+        result = call => result,
+        _ = token.cancelled() => Err(Status::cancelled("the inbound call was cancelled")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cancels_outbound_calls_in_scope() {
+        let token = CancellationToken::new();
+
+        let call = token.scope(or_cancelled(
+            futures_util::future::pending::<Result<(), _>>(),
+        ));
+        let call = tokio::spawn(call);
+
+        tokio::task::yield_now().await;
+        drop(CancelGuard::new(token.clone()));
+
+        let status = call.await.unwrap().unwrap_err();
+        assert_eq!(status.code(), crate::Code::Cancelled);
+
+        // outside of a scope calls are left alone
+        assert!(or_cancelled(async { Ok::<_, Status>(()) }).await.is_ok());
+    }
+}
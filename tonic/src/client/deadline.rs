@@ -0,0 +1,184 @@
+use crate::Status;
+use futures_core::Stream;
+use pin_project::pin_project;
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Instant,
+};
+
+/// Enforces the deadline of a call on its request stream.
+///
+/// Once the deadline has passed, the stream yields a `DEADLINE_EXCEEDED`
+/// status instead of its next message and ends, so the call is reset
+/// instead of sending messages the server can no longer act on. With the
+/// `transport` feature a timer fires at the deadline, so this happens even
+/// while the stream is waiting for its next message. Without it the
+/// deadline is only checked when the stream yields a message.
+#[pin_project]
+#[derive(Debug)]
+pub(crate) struct SendDeadline<S> {
+    #[pin]
+    inner: S,
+    deadline: Option<Instant>,
+    #[cfg(feature = "transport")]
+    sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+    expired: Arc<AtomicBool>,
+}
+
+impl<S> SendDeadline<S> {
+    pub(crate) fn new(inner: S, deadline: Option<Instant>) -> Self {
+        Self {
+            inner,
+            deadline,
+            #[cfg(feature = "transport")]
+            sleep: None,
+            expired: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns a flag that is set once a message was held back because the
+    /// deadline had passed.
+    pub(crate) fn expired(&self) -> Arc<AtomicBool> {
+        self.expired.clone()
+    }
+}
+
+impl<S: Stream> Stream for SendDeadline<S> {
+    type Item = Result<S::Item, Status>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        if this.expired.load(Ordering::Acquire) {
+            return Poll::Ready(None);
+        }
+
+        #[cfg(feature = "transport")]
+        {
+            if let Some(deadline) = *this.deadline {
+                let sleep = this
+                    .sleep
+                    .get_or_insert_with(|| Box::pin(tokio::time::sleep_until(deadline.into())));
+
+                if std::future::Future::poll(sleep.as_mut(), cx).is_ready() {
+                    this.expired.store(true, Ordering::Release);
+                    return Poll::Ready(Some(Err(deadline_exceeded())));
+                }
+            }
+        }
+
+        let item = match this.inner.poll_next(cx) {
+            Poll::Ready(Some(item)) => item,
+            Poll::Ready(None) => return Poll::Ready(None),
+            Poll::Pending => return Poll::Pending,
+        };
+
+        match this.deadline {
+            Some(deadline) if Instant::now() >= *deadline => {
+                this.expired.store(true, Ordering::Release);
+                Poll::Ready(Some(Err(deadline_exceeded())))
+            }
+            _ => Poll::Ready(Some(Ok(item))),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.expired.load(Ordering::Acquire) {
+            return (0, Some(0));
+        }
+
+        self.inner.size_hint()
+    }
+}
+
+pub(crate) fn deadline_exceeded() -> Status {
+    Status::deadline_exceeded("deadline exceeded while sending the request stream")
+}
+
+#[cfg(all(test, feature = "prost", feature = "transport"))]
+mod tests {
+    use crate::{body::BoxBody, client::Grpc, codec::ProstCodec, Code, Request};
+    use http_body::Body;
+    use std::time::Duration;
+
+    // Counts the messages the request stream sends until it ends.
+    async fn count_sent(request: http::Request<BoxBody>) -> Result<usize, crate::Status> {
+        let mut body = request.into_body();
+        let mut sent = 0;
+
+        while let Some(data) =
+            futures_util::future::poll_fn(|cx| std::pin::Pin::new(&mut body).poll_data(cx)).await
+        {
+            data?;
+            sent += 1;
+        }
+
+        Ok(sent)
+    }
+
+    #[tokio::test]
+    async fn fails_pending_sends_at_deadline() {
+        let svc = tower::service_fn(|request: http::Request<BoxBody>| async move {
+            let sent = count_sent(request).await?;
+            assert!(sent > 0, "the first message is sent before the deadline");
+            Ok::<_, crate::Status>(http::Response::new(hyper::Body::empty()))
+        });
+
+        let stalled = async_stream::stream! {
+            yield ();
+            futures_util::future::pending::<()>().await;
+        };
+
+        let mut request = Request::new(stalled);
+        request.set_timeout(Duration::from_millis(20));
+
+        let mut grpc = Grpc::new(svc);
+        let call = grpc.client_streaming(
+            request,
+            "/test.Test/Call".parse().unwrap(),
+            ProstCodec::<(), ()>::default(),
+        );
+        let status = tokio::time::timeout(Duration::from_secs(5), call)
+            .await
+            .expect("the call fails at the deadline")
+            .unwrap_err();
+
+        assert_eq!(status.code(), Code::DeadlineExceeded);
+    }
+
+    #[tokio::test]
+    async fn fails_sends_past_deadline() {
+        let svc = tower::service_fn(|request: http::Request<BoxBody>| async move {
+            let sent = count_sent(request).await?;
+            assert!(sent > 0, "the first message is sent before the deadline");
+            Ok::<_, crate::Status>(http::Response::new(hyper::Body::empty()))
+        });
+
+        let slow = async_stream::stream! {
+            yield ();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            yield ();
+            panic!("the stream ends at the deadline");
+        };
+
+        let mut request = Request::new(slow);
+        request.set_timeout(Duration::from_millis(20));
+
+        let mut grpc = Grpc::new(svc);
+        let status = grpc
+            .client_streaming(
+                request,
+                "/test.Test/Call".parse().unwrap(),
+                ProstCodec::<(), ()>::default(),
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(status.code(), Code::DeadlineExceeded);
+    }
+}
@@ -2,9 +2,13 @@
 use crate::codec::compression::{CompressionEncoding, EnabledCompressionEncodings};
 use crate::{
     body::BoxBody,
-    client::GrpcService,
-    codec::{encode_client, Codec, Streaming},
-    request::SanitizeHeaders,
+    client::{
+        deadline::{deadline_exceeded, SendDeadline},
+        observe::ObservedBody,
+        CallTimings, GrpcService, SendObserver,
+    },
+    codec::{encode_client, Codec, FlushMode, Streaming},
+    request::{try_parse_grpc_timeout, SanitizeHeaders},
     Code, Request, Response, Status,
 };
 use futures_core::Stream;
@@ -14,7 +18,12 @@ use http::{
     uri::{Parts, PathAndQuery, Uri},
 };
 use http_body::Body;
-use std::fmt;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, Instant},
+};
 
 /// A gRPC client dispatcher.
 ///
@@ -37,6 +46,8 @@ pub struct Grpc<T> {
     #[cfg(feature = "compression")]
     /// The compression encoding that will be applied to requests.
     send_compression_encodings: Option<CompressionEncoding>,
+    /// Default timeouts keyed by method path.
+    method_timeouts: Arc<HashMap<String, Duration>>,
 }
 
 impl<T> Grpc<T> {
@@ -48,24 +59,53 @@ impl<T> Grpc<T> {
             send_compression_encodings: None,
             #[cfg(feature = "compression")]
             accept_compression_encodings: EnabledCompressionEncodings::default(),
+            method_timeouts: Arc::new(HashMap::new()),
         }
     }
 
+    /// Set a default timeout for calls to the method at `path`.
+    ///
+    /// The timeout is sent as the `grpc-timeout` header of requests to that
+    /// method which do not already carry one, so a timeout set with
+    /// [`Request::set_timeout`] takes precedence.
+    ///
+    /// # Example
+    ///
+    /// The most common way of using this is through a client generated by
+    /// tonic-build, whose `method_timeout` takes the name of the method
+    /// instead of its full path. On a [`Grpc`] itself:
+    ///
+    /// ```no_run
+    /// use tonic::{client::Grpc, transport::Channel};
+    /// # use std::time::Duration;
+    ///
+    /// # async {
+    /// let channel = Channel::builder("127.0.0.1:3000".parse().unwrap())
+    ///     .connect()
+    ///     .await
+    ///     .unwrap();
+    ///
+    /// let client = Grpc::new(channel)
+    ///     .method_timeout("/routeguide.RouteGuide/GetFeature", Duration::from_millis(100))
+    ///     .method_timeout("/routeguide.RouteGuide/RecordRoute", Duration::from_secs(30));
+    /// # };
+    /// ```
+    pub fn method_timeout(mut self, path: impl Into<String>, timeout: Duration) -> Self {
+        Arc::make_mut(&mut self.method_timeouts).insert(path.into(), timeout);
+        self
+    }
+
     /// Compress requests with `gzip`.
     ///
     /// Requires the server to accept `gzip` otherwise it might return an error.
     ///
     /// # Example
     ///
-    /// The most common way of using this is through a client generated by tonic-build:
+    /// The most common way of using this is through a client generated by
+    /// tonic-build, which has the same method. On a [`Grpc`] itself:
     ///
-    /// ```rust
-    /// use tonic::transport::Channel;
-    /// # struct TestClient<T>(T);
-    /// # impl<T> TestClient<T> {
-    /// #     fn new(channel: T) -> Self { Self(channel) }
-    /// #     fn send_gzip(self) -> Self { self }
-    /// # }
+    /// ```no_run
+    /// use tonic::{client::Grpc, transport::Channel};
     ///
     /// # async {
     /// let channel = Channel::builder("127.0.0.1:3000".parse().unwrap())
@@ -73,7 +113,7 @@ impl<T> Grpc<T> {
     ///     .await
     ///     .unwrap();
     ///
-    /// let client = TestClient::new(channel).send_gzip();
+    /// let client = Grpc::new(channel).send_gzip();
     /// # };
     /// ```
     #[cfg(feature = "compression")]
@@ -97,15 +137,11 @@ impl<T> Grpc<T> {
     ///
     /// # Example
     ///
-    /// The most common way of using this is through a client generated by tonic-build:
+    /// The most common way of using this is through a client generated by
+    /// tonic-build, which has the same method. On a [`Grpc`] itself:
     ///
-    /// ```rust
-    /// use tonic::transport::Channel;
-    /// # struct TestClient<T>(T);
-    /// # impl<T> TestClient<T> {
-    /// #     fn new(channel: T) -> Self { Self(channel) }
-    /// #     fn accept_gzip(self) -> Self { self }
-    /// # }
+    /// ```no_run
+    /// use tonic::{client::Grpc, transport::Channel};
     ///
     /// # async {
     /// let channel = Channel::builder("127.0.0.1:3000".parse().unwrap())
@@ -113,7 +149,7 @@ impl<T> Grpc<T> {
     ///     .await
     ///     .unwrap();
     ///
-    /// let client = TestClient::new(channel).accept_gzip();
+    /// let client = Grpc::new(channel).accept_gzip();
     /// # };
     /// ```
     #[cfg(feature = "compression")]
@@ -176,25 +212,37 @@ impl<T> Grpc<T> {
         M1: Send + Sync + 'static,
         M2: Send + Sync + 'static,
     {
-        let (mut parts, body, extensions) =
-            self.streaming(request, path, codec).await?.into_parts();
-
-        futures_util::pin_mut!(body);
-
-        let message = body
-            .try_next()
-            .await
-            .map_err(|mut status| {
-                status.metadata_mut().merge(parts.clone());
-                status
-            })?
-            .ok_or_else(|| Status::new(Code::Internal, "Missing response message."))?;
-
-        if let Some(trailers) = body.trailers().await? {
-            parts.merge(trailers);
-        }
+        let call = async {
+            let start = Instant::now();
+            let (mut parts, body, mut extensions) =
+                self.streaming(request, path, codec).await?.into_parts();
+
+            futures_util::pin_mut!(body);
+
+            let message = body
+                .try_next()
+                .await
+                .map_err(|mut status| {
+                    status.metadata_mut().merge(parts.clone());
+                    status
+                })?
+                .ok_or_else(|| Status::new(Code::Internal, "Missing response message."))?;
+
+            if let Some(trailers) = body.trailers().await? {
+                parts.merge(trailers);
+            }
 
-        Ok(Response::from_parts(parts, message, extensions))
+            if let Some(timings) = extensions.get_mut::<CallTimings>() {
+                timings.set_total(start.elapsed());
+            }
+
+            Ok(Response::from_parts(parts, message, extensions))
+        };
+
+        #[cfg(feature = "transport")]
+        let call = crate::cancellation::or_cancelled(call);
+
+        call.await
     }
 
     /// Send a server side streaming gRPC request.
@@ -219,7 +267,7 @@ impl<T> Grpc<T> {
     /// Send a bi-directional streaming gRPC request.
     pub async fn streaming<S, M1, M2, C>(
         &mut self,
-        request: Request<S>,
+        mut request: Request<S>,
         path: PathAndQuery,
         mut codec: C,
     ) -> Result<Response<Streaming<M2>>, Status>
@@ -237,15 +285,38 @@ impl<T> Grpc<T> {
 
         let uri = Uri::from_parts(parts).expect("path_and_query only is valid Uri");
 
+        if request
+            .metadata()
+            .get(crate::metadata::GRPC_TIMEOUT_HEADER)
+            .is_none()
+        {
+            if let Some(timeout) = self.method_timeouts.get(uri.path()) {
+                request.set_timeout(*timeout);
+            }
+        }
+
+        let observer = request.extensions().get::<SendObserver>().cloned();
+        let flush_mode = request.extensions().get::<FlushMode>().copied();
+        let deadline = try_parse_grpc_timeout(request.metadata().as_headers())
+            .ok()
+            .flatten()
+            .map(|timeout| Instant::now() + timeout);
+        let mut expired = None;
+
         let request = request
             .map(|s| {
+                let s = SendDeadline::new(s, deadline);
+                expired = Some(s.expired());
+
                 encode_client(
                     codec.encoder(),
                     s,
                     #[cfg(feature = "compression")]
                     self.send_compression_encodings,
                 )
+                .flush_mode(flush_mode.unwrap_or_default())
             })
+            .map(|body| ObservedBody::new(body, observer))
             .map(BoxBody::new);
 
         let mut request = request.into_http(uri, SanitizeHeaders::Yes);
@@ -280,11 +351,28 @@ impl<T> Grpc<T> {
             }
         }
 
-        let response = self
-            .inner
-            .call(request)
-            .await
-            .map_err(|err| Status::from_error(err.into()))?;
+        let start = Instant::now();
+        let response = self.inner.call(request);
+        let response = async {
+            response.await.map_err(|err| match expired {
+                // the request stream was cut off by the deadline, which is
+                // what the transport error is reporting
+                Some(expired) if expired.load(Ordering::Acquire) => deadline_exceeded(),
+                _ => Status::from_error(err.into()),
+            })
+        };
+
+        #[cfg(feature = "transport")]
+        let response = crate::cancellation::or_cancelled(response);
+
+        let mut response = response.await?;
+
+        let mut timings = response
+            .extensions_mut()
+            .remove::<CallTimings>()
+            .unwrap_or_default();
+        timings.set_first_byte(start.elapsed());
+        response.extensions_mut().insert(timings);
 
         #[cfg(feature = "compression")]
         let encoding = CompressionEncoding::from_encoding_header(
@@ -307,6 +395,10 @@ impl<T> Grpc<T> {
             true
         };
 
+        #[cfg(feature = "transport")]
+        let response = crate::transport::InFlightBody::wrap(response)
+            .map(crate::cancellation::CancellableBody::new);
+
         let response = response.map(|body| {
             if expect_additional_trailers {
                 Streaming::new_response(
@@ -333,6 +425,7 @@ impl<T: Clone> Clone for Grpc<T> {
             send_compression_encodings: self.send_compression_encodings,
             #[cfg(feature = "compression")]
             accept_compression_encodings: self.accept_compression_encodings,
+            method_timeouts: self.method_timeouts.clone(),
         }
     }
 }
@@ -352,6 +445,8 @@ impl<T: fmt::Debug> fmt::Debug for Grpc<T> {
             &self.accept_compression_encodings,
         );
 
+        f.field("method_timeouts", &self.method_timeouts);
+
         f.finish()
     }
 }
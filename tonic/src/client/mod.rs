@@ -16,8 +16,23 @@
 //! communication. For more details, see
 //! [transport::Channel](../transport/struct.Channel.html#multiplexing-requests).
 
+mod deadline;
 mod grpc;
+mod observe;
+mod retry;
+#[cfg(feature = "transport")]
+mod sender;
 mod service;
+mod timings;
 
 pub use self::grpc::Grpc;
+pub use self::observe::{SendObserver, SentMessage};
+pub use self::retry::RetryBudget;
+#[cfg(feature = "transport")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport")))]
+pub use self::retry::{BudgetedFuture, BudgetedPolicy};
+#[cfg(feature = "transport")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport")))]
+pub use self::sender::{request_channel, RequestSender, RequestStream};
 pub use self::service::GrpcService;
+pub use self::timings::CallTimings;
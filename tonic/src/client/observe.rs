@@ -0,0 +1,254 @@
+use crate::codec::HEADER_SIZE;
+use bytes::Bytes;
+use http::HeaderMap;
+use http_body::Body;
+use pin_project::pin_project;
+use std::{
+    fmt,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tracing::Span;
+
+/// Observes every message sent on an outbound request stream.
+///
+/// Insert a `SendObserver` into the request extensions to have it called
+/// once for each message handed to the transport. This works for all
+/// kinds of calls but is mostly useful for client and bi-directional
+/// streaming calls, where it shows which messages were sent and when.
+///
+/// ```
+/// use tonic::{client::SendObserver, Request};
+///
+/// let mut request = Request::new(());
+/// request
+///     .extensions_mut()
+///     .insert(SendObserver::new(|sent| {
+///         println!("sent message #{} after {:?}", sent.index(), sent.since_previous());
+///     }));
+/// ```
+///
+/// Independently of any observer, each message is encoded within the tracing
+/// span that was current when the call was made, and a `TRACE` level event
+/// is emitted for it.
+///
+/// With [`FlushMode::Coalesce`] several messages may be handed to the
+/// transport together. The observer is still called once for each of them,
+/// one after the other, and all but the first see no time elapsed since the
+/// previous message.
+///
+/// [`FlushMode::Coalesce`]: crate::codec::FlushMode::Coalesce
+#[derive(Clone)]
+pub struct SendObserver {
+    f: Arc<dyn Fn(&SentMessage) + Send + Sync + 'static>,
+}
+
+impl SendObserver {
+    /// Create a new `SendObserver` from a closure.
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(&SentMessage) + Send + Sync + 'static,
+    {
+        Self { f: Arc::new(f) }
+    }
+}
+
+impl fmt::Debug for SendObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendObserver").finish()
+    }
+}
+
+/// A message that was sent on an outbound request stream.
+#[derive(Debug, Clone)]
+pub struct SentMessage {
+    index: usize,
+    encoded_len: usize,
+    since_start: Duration,
+    since_previous: Duration,
+}
+
+impl SentMessage {
+    /// The position of this message in the stream, starting at zero.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The length of the encoded message, including the gRPC frame header.
+    pub fn encoded_len(&self) -> usize {
+        self.encoded_len
+    }
+
+    /// The time elapsed between the start of the call and this message being sent.
+    pub fn since_start(&self) -> Duration {
+        self.since_start
+    }
+
+    /// The time elapsed since the previous message was sent, or since the
+    /// start of the call for the first message.
+    pub fn since_previous(&self) -> Duration {
+        self.since_previous
+    }
+}
+
+/// Wraps an encoded request body and reports each message it yields.
+#[pin_project]
+pub(crate) struct ObservedBody<B> {
+    #[pin]
+    inner: B,
+    observer: Option<SendObserver>,
+    span: Span,
+    index: usize,
+    start: Instant,
+    previous: Instant,
+}
+
+impl<B> ObservedBody<B> {
+    pub(crate) fn new(inner: B, observer: Option<SendObserver>) -> Self {
+        let now = Instant::now();
+
+        Self {
+            inner,
+            observer,
+            span: Span::current(),
+            index: 0,
+            start: now,
+            previous: now,
+        }
+    }
+}
+
+impl<B> Body for ObservedBody<B>
+where
+    B: Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let this = self.project();
+        let _enter = this.span.enter();
+
+        let poll = this.inner.poll_data(cx);
+
+        if let Poll::Ready(Some(Ok(data))) = &poll {
+            let now = Instant::now();
+
+            // the encoder only yields whole messages, but coalesced messages
+            // share a chunk
+            for encoded_len in message_lens(data) {
+                let sent = SentMessage {
+                    index: *this.index,
+                    encoded_len,
+                    since_start: now - *this.start,
+                    since_previous: now - *this.previous,
+                };
+
+                tracing::trace!(
+                    index = sent.index,
+                    encoded_len = sent.encoded_len,
+                    since_previous = ?sent.since_previous,
+                    "sent message"
+                );
+
+                if let Some(observer) = this.observer {
+                    (observer.f)(&sent);
+                }
+
+                *this.index += 1;
+                *this.previous = now;
+            }
+        }
+
+        poll
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        self.project().inner.poll_trailers(cx)
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// The encoded lengths of the messages in a chunk of whole messages.
+fn message_lens(data: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    let mut rest = data;
+
+    std::iter::from_fn(move || {
+        if rest.len() < HEADER_SIZE {
+            return None;
+        }
+
+        let len = u32::from_be_bytes([rest[1], rest[2], rest[3], rest[4]]) as usize;
+        let encoded_len = std::cmp::min(HEADER_SIZE + len, rest.len());
+        rest = &rest[encoded_len..];
+
+        Some(encoded_len)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn observes_each_message() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let observer = {
+            let count = count.clone();
+            SendObserver::new(move |sent| {
+                assert_eq!(sent.index(), count.fetch_add(1, Ordering::SeqCst));
+                assert_eq!(sent.encoded_len(), 5);
+            })
+        };
+
+        let body = http_body::Full::new(Bytes::from_static(&[0, 0, 0, 0, 0]));
+        let mut body = Box::pin(ObservedBody::new(body, Some(observer)));
+
+        while let Some(data) = futures_util::future::poll_fn(|cx| body.as_mut().poll_data(cx)).await
+        {
+            data.unwrap();
+        }
+
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn observes_each_coalesced_message() {
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let observer = {
+            let sent = sent.clone();
+            SendObserver::new(move |message| {
+                sent.lock()
+                    .unwrap()
+                    .push((message.index(), message.encoded_len()));
+            })
+        };
+
+        // two messages handed over in one chunk
+        let body = http_body::Full::new(Bytes::from_static(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 8, 1]));
+        let mut body = Box::pin(ObservedBody::new(body, Some(observer)));
+
+        while let Some(data) = futures_util::future::poll_fn(|cx| body.as_mut().poll_data(cx)).await
+        {
+            data.unwrap();
+        }
+
+        assert_eq!(*sent.lock().unwrap(), vec![(0, 5), (1, 7)]);
+    }
+}
@@ -0,0 +1,327 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+// Tokens are stored in thousandths, the precision gRPC uses for the ratio.
+const SCALE: u64 = 1000;
+
+/// A client side retry budget, following gRPC's retry throttling.
+///
+/// The budget starts with `max_tokens` tokens. Every failed call takes one
+/// token away and every successful call gives back `token_ratio` tokens, up
+/// to `max_tokens`. Retries are only allowed while more than `min_tokens`
+/// are left, which defaults to half of `max_tokens`. When a backend keeps
+/// failing the budget drains and calls fail without being retried, so that
+/// retries do not multiply the load on it.
+///
+/// A `RetryBudget` is cheap to clone and all clones share the same tokens,
+/// so one budget is usually shared by all clients of a backend.
+///
+/// The budget does not retry calls by itself. Calls retried by
+/// [`tower::retry`] can be limited by wrapping the retry policy in a
+/// [`BudgetedPolicy`]. Otherwise, the code retrying calls must record the
+/// outcome of every attempt with [`record_success`] and [`record_failure`],
+/// and check [`can_retry`] before each retry, like so:
+///
+/// ```
+/// use tonic::{client::RetryBudget, Status};
+///
+/// # async fn call() -> Result<(), Status> { Ok(()) }
+/// # async fn example() -> Result<(), Status> {
+/// let budget = RetryBudget::new(10, 0.1);
+///
+/// loop {
+///     match call().await {
+///         Ok(res) => {
+///             budget.record_success();
+///             return Ok(res);
+///         }
+///         Err(status) => {
+///             budget.record_failure();
+///             if !budget.can_retry() {
+///                 return Err(status);
+///             }
+///         }
+///     }
+/// }
+/// # }
+/// ```
+///
+/// [`record_success`]: RetryBudget::record_success
+/// [`record_failure`]: RetryBudget::record_failure
+/// [`can_retry`]: RetryBudget::can_retry
+/// [`tower::retry`]: https://docs.rs/tower/0.4/tower/retry/index.html
+#[derive(Debug, Clone)]
+pub struct RetryBudget {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    tokens: AtomicU64,
+    max_tokens: u64,
+    min_tokens: u64,
+    token_ratio: u64,
+}
+
+impl RetryBudget {
+    /// Create a new budget holding up to `max_tokens` tokens, refilled by
+    /// `token_ratio` tokens for each successful call.
+    ///
+    /// Retries are allowed while more than half of `max_tokens` are left.
+    /// `token_ratio` is clamped to `0.0..=1.0` and rounded to three decimal
+    /// places.
+    pub fn new(max_tokens: u32, token_ratio: f32) -> Self {
+        let max_tokens = u64::from(max_tokens) * SCALE;
+        Self::from_scaled(max_tokens, max_tokens / 2, token_ratio)
+    }
+
+    /// Create a new budget like [`new`](RetryBudget::new), which allows
+    /// retries while more than `min_tokens` tokens are left.
+    pub fn with_min_tokens(max_tokens: u32, min_tokens: u32, token_ratio: f32) -> Self {
+        Self::from_scaled(
+            u64::from(max_tokens) * SCALE,
+            u64::from(min_tokens) * SCALE,
+            token_ratio,
+        )
+    }
+
+    fn from_scaled(max_tokens: u64, min_tokens: u64, token_ratio: f32) -> Self {
+        let token_ratio = (token_ratio.clamp(0.0, 1.0) * SCALE as f32).round() as u64;
+
+        RetryBudget {
+            inner: Arc::new(Inner {
+                tokens: AtomicU64::new(max_tokens),
+                max_tokens,
+                min_tokens,
+                token_ratio,
+            }),
+        }
+    }
+
+    /// Record a successful call, refilling the budget.
+    pub fn record_success(&self) {
+        let inner = &self.inner;
+        let _ = inner
+            .tokens
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |tokens| {
+                Some(std::cmp::min(tokens + inner.token_ratio, inner.max_tokens))
+            });
+    }
+
+    /// Record a failed call, draining the budget.
+    pub fn record_failure(&self) {
+        let _ = self
+            .inner
+            .tokens
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |tokens| {
+                Some(tokens.saturating_sub(SCALE))
+            });
+    }
+
+    /// Returns `true` if a failed call may be retried.
+    pub fn can_retry(&self) -> bool {
+        self.inner.tokens.load(Ordering::Acquire) > self.inner.min_tokens
+    }
+}
+
+/// A [`tower::retry::Policy`] that only lets another policy retry a call
+/// while a [`RetryBudget`] allows it.
+///
+/// An attempt the inner policy wants to retry is recorded as a failure, and
+/// is only retried if the budget still [allows retries] afterwards. An
+/// attempt that succeeded is recorded as a success. Errors the inner policy
+/// does not retry do not change the budget.
+///
+/// ```
+/// # use tonic::client::{BudgetedPolicy, RetryBudget};
+/// # #[derive(Clone)]
+/// # struct Attempts;
+/// # impl<Req: Clone, Res, E> tower::retry::Policy<Req, Res, E> for Attempts {
+/// #     type Future = futures_util::future::Ready<Self>;
+/// #     fn retry(&self, _: &Req, _: Result<&Res, &E>) -> Option<Self::Future> { None }
+/// #     fn clone_request(&self, req: &Req) -> Option<Req> { Some(req.clone()) }
+/// # }
+/// let budget = RetryBudget::new(10, 0.1);
+/// let layer = tower::retry::RetryLayer::new(BudgetedPolicy::new(Attempts, budget));
+/// ```
+///
+/// [`tower::retry::Policy`]: https://docs.rs/tower/0.4/tower/retry/trait.Policy.html
+/// [allows retries]: RetryBudget::can_retry
+#[cfg(feature = "transport")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport")))]
+#[derive(Debug, Clone)]
+pub struct BudgetedPolicy<P> {
+    policy: P,
+    budget: RetryBudget,
+}
+
+#[cfg(feature = "transport")]
+impl<P> BudgetedPolicy<P> {
+    /// Limit the retries of `policy` with `budget`.
+    pub fn new(policy: P, budget: RetryBudget) -> Self {
+        Self { policy, budget }
+    }
+
+    /// Returns the budget limiting the retries.
+    pub fn budget(&self) -> &RetryBudget {
+        &self.budget
+    }
+}
+
+#[cfg(feature = "transport")]
+impl<P, Req, Res, E> tower::retry::Policy<Req, Res, E> for BudgetedPolicy<P>
+where
+    P: tower::retry::Policy<Req, Res, E>,
+{
+    type Future = BudgetedFuture<P::Future>;
+
+    fn retry(&self, req: &Req, result: Result<&Res, &E>) -> Option<Self::Future> {
+        match self.policy.retry(req, result) {
+            Some(policy) => {
+                self.budget.record_failure();
+
+                if self.budget.can_retry() {
+                    Some(BudgetedFuture {
+                        policy,
+                        budget: Some(self.budget.clone()),
+                    })
+                } else {
+                    None
+                }
+            }
+            None => {
+                if result.is_ok() {
+                    self.budget.record_success();
+                }
+
+                None
+            }
+        }
+    }
+
+    fn clone_request(&self, req: &Req) -> Option<Req> {
+        self.policy.clone_request(req)
+    }
+}
+
+/// The future returned by [`BudgetedPolicy`] when it retries a call.
+#[cfg(feature = "transport")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport")))]
+#[pin_project::pin_project]
+#[derive(Debug)]
+pub struct BudgetedFuture<F> {
+    #[pin]
+    policy: F,
+    budget: Option<RetryBudget>,
+}
+
+#[cfg(feature = "transport")]
+impl<F> std::future::Future for BudgetedFuture<F>
+where
+    F: std::future::Future,
+{
+    type Output = BudgetedPolicy<F::Output>;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        let this = self.project();
+        let policy = futures_util::ready!(this.policy.poll(cx));
+        let budget = this
+            .budget
+            .take()
+            .expect("BudgetedFuture polled after completion");
+
+        std::task::Poll::Ready(BudgetedPolicy::new(policy, budget))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttles_and_recovers() {
+        let budget = RetryBudget::new(4, 0.5);
+        assert!(budget.can_retry());
+
+        budget.record_failure();
+        assert!(budget.can_retry());
+
+        // 2 tokens left, which is not more than half
+        budget.record_failure();
+        assert!(!budget.can_retry());
+
+        budget.record_success();
+        assert!(budget.can_retry());
+
+        // refills up to `max_tokens` only
+        for _ in 0..10 {
+            budget.record_success();
+        }
+        budget.record_failure();
+        assert!(budget.clone().can_retry());
+        budget.record_failure();
+        assert!(!budget.can_retry());
+    }
+
+    #[test]
+    fn never_below_zero() {
+        let budget = RetryBudget::with_min_tokens(1, 0, 1.0);
+
+        budget.record_failure();
+        budget.record_failure();
+        assert!(!budget.can_retry());
+
+        budget.record_success();
+        assert!(budget.can_retry());
+    }
+
+    #[cfg(feature = "transport")]
+    #[tokio::test]
+    async fn policy_stops_retrying_when_budget_is_spent() {
+        use std::sync::atomic::AtomicUsize;
+        use tower::{retry::Retry, service_fn, ServiceExt};
+
+        #[derive(Clone)]
+        struct Always;
+
+        impl tower::retry::Policy<(), (), ()> for Always {
+            type Future = futures_util::future::Ready<Self>;
+
+            fn retry(&self, _: &(), _: Result<&(), &()>) -> Option<Self::Future> {
+                Some(futures_util::future::ready(Always))
+            }
+
+            fn clone_request(&self, _: &()) -> Option<()> {
+                Some(())
+            }
+        }
+
+        let budget = RetryBudget::new(4, 0.5);
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let failing = || {
+            let attempts = attempts.clone();
+            service_fn(move |()| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                futures_util::future::err::<(), ()>(())
+            })
+        };
+
+        // 4 tokens: the first failure leaves 3 and is retried, the second
+        // leaves 2, which is not more than half, so the call gives up.
+        let policy = BudgetedPolicy::new(Always, budget.clone());
+        let retry = Retry::new(policy.clone(), failing());
+        assert!(retry.oneshot(()).await.is_err());
+        assert_eq!(attempts.swap(0, Ordering::SeqCst), 2);
+
+        // the budget is spent, so the next call is not retried at all
+        let retry = Retry::new(policy, failing());
+        assert!(retry.oneshot(()).await.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert!(!budget.can_retry());
+    }
+}
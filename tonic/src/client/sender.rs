@@ -0,0 +1,200 @@
+use crate::Status;
+use futures_core::Stream;
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc;
+
+/// Create a bounded channel to feed the request of a client or
+/// bi-directional streaming call.
+///
+/// The [`RequestStream`] is passed to the call while the [`RequestSender`]
+/// keeps producing messages. Calling [`RequestSender::finish`] half-closes
+/// the call: the request stream ends, the server sees the end of its inbound
+/// stream, and the response stream stays open for the messages the server
+/// still sends.
+///
+/// ```no_run
+/// # use tonic::Status;
+/// # async fn example() -> Result<(), Status> {
+/// let (tx, rx) = tonic::client::request_channel(4);
+///
+/// // let mut inbound = client.route_chat(Request::new(rx)).await?.into_inner();
+/// # let _rx = rx;
+///
+/// tx.send("first note").await?;
+/// tx.send("last note").await?;
+///
+/// // done sending, but keep receiving
+/// tx.finish().await;
+///
+/// // while let Some(note) = inbound.message().await? { ... }
+/// # Ok(())
+/// # }
+/// ```
+pub fn request_channel<T>(buffer: usize) -> (RequestSender<T>, RequestStream<T>) {
+    let (tx, rx) = mpsc::channel(buffer);
+
+    let tx = RequestSender {
+        tx,
+        finished: Arc::new(Mutex::new(false)),
+    };
+    let rx = RequestStream {
+        rx,
+        finished: false,
+    };
+
+    (tx, rx)
+}
+
+/// The sending half of a [`request_channel`].
+pub struct RequestSender<T> {
+    tx: mpsc::Sender<Message<T>>,
+    // set by `finish`, and locked while a message is queued so that no
+    // message is queued behind the end of the stream
+    finished: Arc<Mutex<bool>>,
+}
+
+/// The receiving half of a [`request_channel`], used as the request stream.
+pub struct RequestStream<T> {
+    rx: mpsc::Receiver<Message<T>>,
+    finished: bool,
+}
+
+enum Message<T> {
+    Item(T),
+    Finish,
+}
+
+impl<T> RequestSender<T> {
+    /// Send a message, waiting for capacity if the buffer is full.
+    ///
+    /// Returns `CANCELLED` if the request stream has been closed, because
+    /// the call ended or the stream was finished.
+    pub async fn send(&self, message: T) -> Result<(), Status> {
+        let closed = || Status::cancelled("request stream closed");
+
+        let permit = self.tx.reserve().await.map_err(|_| closed())?;
+
+        let finished = self.finished.lock().unwrap();
+        if *finished {
+            return Err(closed());
+        }
+        permit.send(Message::Item(message));
+
+        Ok(())
+    }
+
+    /// Half-close the call.
+    ///
+    /// The request stream ends after the messages sent before, which sends
+    /// the end of the stream to the server while the response stream stays
+    /// open. Sending on any clone of this sender fails afterwards. Dropping
+    /// every sender ends the request stream the same way.
+    pub async fn finish(self) {
+        // nothing to do if the stream was closed already
+        if let Ok(permit) = self.tx.reserve().await {
+            let mut finished = self.finished.lock().unwrap();
+            if !*finished {
+                *finished = true;
+                permit.send(Message::Finish);
+            }
+        }
+    }
+
+    /// Returns `true` once the request stream has been closed or finished.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed() || *self.finished.lock().unwrap()
+    }
+
+    /// Wait until the request stream has been closed.
+    pub async fn closed(&self) {
+        self.tx.closed().await
+    }
+}
+
+impl<T> Clone for RequestSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            finished: self.finished.clone(),
+        }
+    }
+}
+
+impl<T> Stream for RequestStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.finished {
+            return Poll::Ready(None);
+        }
+
+        match futures_util::ready!(self.rx.poll_recv(cx)) {
+            Some(Message::Item(item)) => Poll::Ready(Some(item)),
+            Some(Message::Finish) | None => {
+                self.finished = true;
+                self.rx.close();
+                Poll::Ready(None)
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for RequestSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestSender")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+impl<T> fmt::Debug for RequestStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestStream")
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Code;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn finish_ends_stream() {
+        let (tx, mut rx) = request_channel::<u32>(4);
+        let other = tx.clone();
+
+        tx.send(1).await.unwrap();
+        tx.finish().await;
+
+        assert_eq!(rx.next().await, Some(1));
+        assert_eq!(rx.next().await, None);
+
+        assert!(other.is_closed());
+        let err = other.send(2).await.unwrap_err();
+        assert_eq!(err.code(), Code::Cancelled);
+        assert_eq!(rx.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn send_fails_once_finished() {
+        let (tx, mut rx) = request_channel::<u32>(4);
+        let other = tx.clone();
+
+        tx.finish().await;
+
+        // the request stream has not reached the end yet
+        assert!(other.is_closed());
+        let err = other.send(1).await.unwrap_err();
+        assert_eq!(err.code(), Code::Cancelled);
+
+        assert_eq!(rx.next().await, None);
+    }
+}
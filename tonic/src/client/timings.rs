@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+/// Where the time of a call went, as seen by the client.
+///
+/// Clients made with [`Grpc`](super::Grpc) insert this into the extensions
+/// of every response:
+///
+/// ```no_run
+/// # use tonic::{client::CallTimings, Response};
+/// # fn example(response: Response<()>) {
+/// if let Some(timings) = response.extensions().get::<CallTimings>() {
+///     println!("first byte after {:?}", timings.first_byte());
+/// }
+/// # }
+/// ```
+///
+/// The durations of name resolution and of establishing the connection are
+/// only available with a [`Channel`] and only for the call that was the
+/// first to be answered on a newly opened connection, which is usually the
+/// call that caused it to be opened.
+///
+/// [`Channel`]: crate::transport::Channel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CallTimings {
+    resolve: Option<Duration>,
+    connect: Option<Duration>,
+    first_byte: Duration,
+    total: Option<Duration>,
+}
+
+impl CallTimings {
+    #[cfg_attr(not(feature = "transport"), allow(dead_code))]
+    pub(crate) fn connected(resolve: Option<Duration>, connect: Duration) -> Self {
+        CallTimings {
+            resolve,
+            connect: Some(connect),
+            ..CallTimings::default()
+        }
+    }
+
+    pub(crate) fn set_first_byte(&mut self, first_byte: Duration) {
+        self.first_byte = first_byte;
+    }
+
+    pub(crate) fn set_total(&mut self, total: Duration) {
+        self.total = Some(total);
+    }
+
+    /// Time spent resolving the name of the server, if the call opened a
+    /// new connection to a host that was not an IP address.
+    pub fn resolve(&self) -> Option<Duration> {
+        self.resolve
+    }
+
+    /// Time spent establishing a new connection, including the TCP and TLS
+    /// handshakes but not name resolution, if the call opened one.
+    pub fn connect(&self) -> Option<Duration> {
+        self.connect
+    }
+
+    /// Time from sending the call until the response headers arrived.
+    ///
+    /// This includes the time spent resolving and connecting, and the time
+    /// the call waited in the channel's queue.
+    pub fn first_byte(&self) -> Duration {
+        self.first_byte
+    }
+
+    /// Time from sending the call until the whole response arrived.
+    ///
+    /// This is only known for unary and client streaming calls, the
+    /// response of streaming calls is returned before it has been received.
+    pub fn total(&self) -> Option<Duration> {
+        self.total
+    }
+}
@@ -1,11 +1,13 @@
+use super::UnknownFields;
 use bytes::buf::UninitSlice;
-use bytes::{Buf, BufMut, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 
 /// A specialized buffer to decode gRPC messages from.
 #[derive(Debug)]
 pub struct DecodeBuf<'a> {
     buf: &'a mut BytesMut,
     len: usize,
+    unknown_fields: Option<&'a UnknownFields>,
 }
 
 /// A specialized buffer to encode gRPC messages into.
@@ -16,7 +18,24 @@ pub struct EncodeBuf<'a> {
 
 impl<'a> DecodeBuf<'a> {
     pub(crate) fn new(buf: &'a mut BytesMut, len: usize) -> Self {
-        DecodeBuf { buf, len }
+        DecodeBuf {
+            buf,
+            len,
+            unknown_fields: None,
+        }
+    }
+
+    pub(crate) fn with_unknown_fields(self, unknown_fields: Option<&'a UnknownFields>) -> Self {
+        DecodeBuf {
+            unknown_fields,
+            ..self
+        }
+    }
+
+    /// The counter for unknown fields of the message, if they are tracked.
+    #[cfg_attr(not(feature = "prost"), allow(dead_code))]
+    pub(crate) fn unknown_fields(&self) -> Option<&UnknownFields> {
+        self.unknown_fields
     }
 }
 
@@ -43,6 +62,15 @@ impl Buf for DecodeBuf<'_> {
         self.buf.advance(cnt);
         self.len -= cnt;
     }
+
+    // Splits off the receive buffer instead of copying, so that `bytes`
+    // fields generated as `Bytes` are decoded without a copy.
+    #[inline]
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        assert!(len <= self.len);
+        self.len -= len;
+        self.buf.copy_to_bytes(len)
+    }
 }
 
 impl<'a> EncodeBuf<'a> {
@@ -106,6 +134,18 @@ mod tests {
         assert!(!buf.has_remaining());
     }
 
+    #[test]
+    fn decode_buf_copy_to_bytes_does_not_copy() {
+        let mut payload = BytesMut::from(&[1u8; 50][..]);
+        let start = payload.as_ptr();
+        let mut buf = DecodeBuf::new(&mut payload, 20);
+
+        let bytes = buf.copy_to_bytes(10);
+        assert_eq!(bytes.as_ptr(), start);
+        assert_eq!(buf.remaining(), 10);
+        assert_eq!(payload.len(), 40);
+    }
+
     #[test]
     fn encode_buf() {
         let mut bytes = BytesMut::with_capacity(100);
@@ -1,6 +1,6 @@
 #[cfg(feature = "compression")]
 use super::compression::{decompress, CompressionEncoding};
-use super::{DecodeBuf, Decoder, HEADER_SIZE};
+use super::{DecodeBuf, Decoder, UnknownFields, HEADER_SIZE};
 use crate::{body::BoxBody, metadata::MetadataMap, Code, Status};
 use bytes::{Buf, BufMut, BytesMut};
 use futures_core::Stream;
@@ -10,6 +10,7 @@ use http_body::Body;
 use std::{
     fmt,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 use tracing::{debug, trace};
@@ -27,6 +28,9 @@ pub struct Streaming<T> {
     direction: Direction,
     buf: BytesMut,
     trailers: Option<MetadataMap>,
+    end: Option<StreamEnd>,
+    unknown_fields: Option<UnknownFields>,
+    decode_error_handler: Option<DecodeErrorHandler>,
     #[cfg(feature = "compression")]
     decompress_buf: BytesMut,
     #[cfg(feature = "compression")]
@@ -35,6 +39,52 @@ pub struct Streaming<T> {
 
 impl<T> Unpin for Streaming<T> {}
 
+/// Maps the status of a request message that failed to decode.
+#[derive(Clone)]
+pub(crate) struct DecodeErrorHandler(Arc<dyn Fn(Status) -> Status + Send + Sync>);
+
+impl DecodeErrorHandler {
+    #[cfg_attr(not(feature = "transport"), allow(dead_code))]
+    pub(crate) fn new(f: Arc<dyn Fn(Status) -> Status + Send + Sync>) -> Self {
+        DecodeErrorHandler(f)
+    }
+}
+
+/// How a [`Streaming`] ended.
+///
+/// A handler of a client streaming call sees the end of the request stream
+/// whether the client finished it or went away, and this tells the two
+/// apart, for example to only commit the side effects of a call the client
+/// finished:
+///
+/// ```rust
+/// # use tonic::{codec::StreamEnd, Status, Streaming};
+/// # async fn record(mut stream: Streaming<u32>) -> Result<(), Status> {
+/// let mut points = Vec::new();
+///
+/// while let Ok(Some(point)) = stream.message().await {
+///     points.push(point);
+/// }
+///
+/// if stream.end() != Some(StreamEnd::Finished) {
+///     // discard the points
+///     return Err(Status::aborted("the route was not finished"));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamEnd {
+    /// The peer ended the stream, by half-closing a request stream or with
+    /// the trailers of a response stream.
+    Finished,
+    /// The peer ended the stream in the middle of a message.
+    Truncated,
+    /// The stream was reset, or its connection lost, before the peer ended
+    /// it.
+    Disconnected,
+}
+
 #[derive(Debug)]
 enum State {
     ReadHeader,
@@ -104,6 +154,20 @@ impl<T> Streaming<T> {
         )
     }
 
+    pub(crate) fn track_unknown_fields(self, unknown_fields: Option<UnknownFields>) -> Self {
+        Self {
+            unknown_fields,
+            ..self
+        }
+    }
+
+    pub(crate) fn on_decode_error(self, decode_error_handler: Option<DecodeErrorHandler>) -> Self {
+        Self {
+            decode_error_handler,
+            ..self
+        }
+    }
+
     fn new<B, D>(
         decoder: D,
         body: B,
@@ -125,6 +189,9 @@ impl<T> Streaming<T> {
             direction,
             buf: BytesMut::with_capacity(BUFFER_SIZE),
             trailers: None,
+            end: None,
+            unknown_fields: None,
+            decode_error_handler: None,
             #[cfg(feature = "compression")]
             decompress_buf: BytesMut::new(),
             #[cfg(feature = "compression")]
@@ -156,6 +223,13 @@ impl<T> Streaming<T> {
         }
     }
 
+    /// Returns how this stream ended, or `None` if it has not ended yet.
+    ///
+    /// See [`StreamEnd`] for more details.
+    pub fn end(&self) -> Option<StreamEnd> {
+        self.end
+    }
+
     /// Fetch the trailing metadata.
     ///
     /// This will drain the stream of all its messages to receive the trailing
@@ -208,6 +282,8 @@ impl<T> Streaming<T> {
                     if cfg!(feature = "compression") {
                         true
                     } else {
+                        // not a malformed message, so not for the decode
+                        // error handler to map
                         return Err(Status::new(
                             Code::Unimplemented,
                             "Message compressed, compression support not enabled.".to_string(),
@@ -224,7 +300,7 @@ impl<T> Streaming<T> {
                     } else {
                         format!("protocol error: received message with invalid compression flag: {} (valid flags are 0 and 1), while sending request", f)
                     };
-                    return Err(Status::new(Code::Internal, message));
+                    return Err(self.decode_error(Status::new(Code::Internal, message)));
                 }
             };
             let len = self.buf.get_u32() as usize;
@@ -264,20 +340,22 @@ impl<T> Streaming<T> {
                         } else {
                             format!("Error decompressing: {}, while sending request", err)
                         };
-                        return Err(Status::new(Code::Internal, message));
+                        return Err(self.decode_error(Status::new(Code::Internal, message)));
                     }
                     let decompressed_len = self.decompress_buf.len();
-                    self.decoder.decode(&mut DecodeBuf::new(
-                        &mut self.decompress_buf,
-                        decompressed_len,
-                    ))
+                    self.decoder.decode(
+                        &mut DecodeBuf::new(&mut self.decompress_buf, decompressed_len)
+                            .with_unknown_fields(self.unknown_fields.as_ref()),
+                    )
                 }
 
                 #[cfg(not(feature = "compression"))]
                 unreachable!("should not take this branch if compression is disabled")
             } else {
-                self.decoder
-                    .decode(&mut DecodeBuf::new(&mut self.buf, *len))
+                self.decoder.decode(
+                    &mut DecodeBuf::new(&mut self.buf, *len)
+                        .with_unknown_fields(self.unknown_fields.as_ref()),
+                )
             };
 
             return match decoding_result {
@@ -286,12 +364,21 @@ impl<T> Streaming<T> {
                     Ok(Some(msg))
                 }
                 Ok(None) => Ok(None),
-                Err(e) => Err(e),
+                Err(e) => Err(self.decode_error(e)),
             };
         }
 
         Ok(None)
     }
+
+    /// Maps the status of a message that could not be read off the stream,
+    /// because of its framing or its encoding, with the decode error handler.
+    fn decode_error(&self, status: Status) -> Status {
+        match &self.decode_error_handler {
+            Some(DecodeErrorHandler(f)) => f(status),
+            None => status,
+        }
+    }
 }
 
 impl<T> Stream for Streaming<T> {
@@ -312,6 +399,7 @@ impl<T> Stream for Streaming<T> {
                     let err: crate::Error = e.into();
                     debug!("decoder inner stream error: {:?}", err);
                     let status = Status::from_error(err);
+                    self.end = Some(StreamEnd::Disconnected);
                     return Poll::Ready(Some(Err(status)));
                 }
                 None => None,
@@ -323,10 +411,12 @@ impl<T> Stream for Streaming<T> {
                 // FIXME: improve buf usage.
                 if self.buf.has_remaining() {
                     trace!("unexpected EOF decoding stream");
-                    return Poll::Ready(Some(Err(Status::new(
+                    self.end = Some(StreamEnd::Truncated);
+                    let status = Status::new(
                         Code::Internal,
                         "Unexpected EOF decoding stream.".to_string(),
-                    ))));
+                    );
+                    return Poll::Ready(Some(Err(self.decode_error(status))));
                 } else {
                     break;
                 }
@@ -336,6 +426,8 @@ impl<T> Stream for Streaming<T> {
         if let Direction::Response(status) = self.direction {
             match ready!(Pin::new(&mut self.body).poll_trailers(cx)) {
                 Ok(trailer) => {
+                    self.end = Some(StreamEnd::Finished);
+
                     if let Err(e) = crate::status::infer_grpc_status(trailer.as_ref(), status) {
                         if let Some(e) = e {
                             return Some(Err(e)).into();
@@ -350,11 +442,13 @@ impl<T> Stream for Streaming<T> {
                     let err: crate::Error = e.into();
                     debug!("decoder inner trailers error: {:?}", err);
                     let status = Status::from_error(err);
+                    self.end = Some(StreamEnd::Disconnected);
                     return Some(Err(status)).into();
                 }
             }
         }
 
+        self.end = Some(StreamEnd::Finished);
         Poll::Ready(None)
     }
 }
@@ -9,12 +9,73 @@ use http::HeaderMap;
 use http_body::Body;
 use pin_project::pin_project;
 use std::{
+    cell::Cell,
     pin::Pin,
     task::{Context, Poll},
 };
 
 pub(super) const BUFFER_SIZE: usize = 8 * 1024;
 
+/// Controls how the messages of a stream are handed to the transport.
+///
+/// By default each message is handed over as soon as it is encoded, which
+/// keeps the latency of every message as low as possible, at the cost of
+/// one HTTP/2 `DATA` frame, and usually one write, per message.
+///
+/// With [`FlushMode::Coalesce`] messages that are ready back to back are
+/// buffered, up to 8 KiB, and handed over together once the stream has no
+/// message ready. This saves frames and writes for streams of many small
+/// messages, but a message may wait for the ones produced right after it.
+/// A [`SendObserver`] still observes each message on its own.
+///
+/// The mode is set per call, with [`Request::set_flush_mode`] for messages
+/// sent by a client and [`Response::set_flush_mode`] for messages sent by a
+/// server.
+///
+/// [`Request::set_flush_mode`]: crate::Request::set_flush_mode
+/// [`Response::set_flush_mode`]: crate::Response::set_flush_mode
+/// [`SendObserver`]: crate::client::SendObserver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushMode {
+    /// Hand over every message on its own. This is the default.
+    FlushEach,
+    /// Hand over messages that are ready back to back together.
+    Coalesce,
+}
+
+impl Default for FlushMode {
+    fn default() -> Self {
+        FlushMode::FlushEach
+    }
+}
+
+thread_local! {
+    // `Some` while an `EncodeBody` coalescing messages polls its stream, and
+    // `Some(true)` once the stream asked for the current batch to end.
+    static FLUSHED: Cell<Option<bool>> = Cell::new(None);
+}
+
+/// End the batch of messages coalesced by the body polling the current
+/// stream, before the message the stream returns next.
+///
+/// Does nothing if the stream is not polled by a body coalescing messages.
+pub(crate) fn flush_batch() {
+    FLUSHED.with(|flushed| {
+        if flushed.get().is_some() {
+            flushed.set(Some(true));
+        }
+    });
+}
+
+// Run `poll`, returning whether the stream it polls called `flush_batch`.
+fn poll_flushed<T>(poll: impl FnOnce() -> T) -> (T, bool) {
+    let outer = FLUSHED.with(|flushed| flushed.replace(Some(false)));
+    let polled = poll();
+    let flushed = FLUSHED.with(|flushed| flushed.replace(outer));
+
+    (polled, flushed == Some(true))
+}
+
 pub(crate) fn encode_server<T, U>(
     encoder: T,
     source: U,
@@ -39,6 +100,13 @@ where
     EncodeBody::new_server(stream)
 }
 
+pub(crate) fn encode_server_raw<U>(source: U) -> EncodeBody<U>
+where
+    U: Stream<Item = Result<Bytes, Status>> + Send + Sync + 'static,
+{
+    EncodeBody::new_server(source)
+}
+
 pub(crate) fn encode_client<T, U>(
     encoder: T,
     source: U,
@@ -47,11 +115,11 @@ pub(crate) fn encode_client<T, U>(
 where
     T: Encoder<Error = Status> + Send + Sync + 'static,
     T::Item: Send + Sync,
-    U: Stream<Item = T::Item> + Send + Sync + 'static,
+    U: Stream<Item = Result<T::Item, Status>> + Send + Sync + 'static,
 {
     let stream = encode(
         encoder,
-        source.map(Ok),
+        source,
         #[cfg(feature = "compression")]
         compression_encoding,
         #[cfg(feature = "compression")]
@@ -153,6 +221,11 @@ pub(crate) struct EncodeBody<S> {
     error: Option<Status>,
     role: Role,
     is_end_stream: bool,
+    flush_mode: FlushMode,
+    // messages buffered with `FlushMode::Coalesce`
+    buf: BytesMut,
+    // what `inner` returned after the buffered messages
+    deferred: Option<Option<Result<Bytes, Status>>>,
 }
 
 impl<S> EncodeBody<S>
@@ -165,6 +238,9 @@ where
             error: None,
             role: Role::Client,
             is_end_stream: false,
+            flush_mode: FlushMode::default(),
+            buf: BytesMut::new(),
+            deferred: None,
         }
     }
 
@@ -174,6 +250,62 @@ where
             error: None,
             role: Role::Server,
             is_end_stream: false,
+            flush_mode: FlushMode::default(),
+            buf: BytesMut::new(),
+            deferred: None,
+        }
+    }
+
+    pub(crate) fn flush_mode(self, flush_mode: FlushMode) -> Self {
+        Self { flush_mode, ..self }
+    }
+}
+
+impl<S> EncodeBody<S>
+where
+    S: Stream<Item = Result<Bytes, Status>>,
+{
+    fn poll_coalesced(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Status>>> {
+        let mut this = self.project();
+
+        match this.deferred.take() {
+            // a message returned after a flush starts the next batch
+            Some(Some(Ok(data))) => this.buf.extend_from_slice(&data),
+            Some(next) => return Poll::Ready(next),
+            None => {}
+        }
+
+        loop {
+            if this.buf.len() >= BUFFER_SIZE {
+                return Poll::Ready(Some(Ok(this.buf.split().freeze())));
+            }
+
+            let (next, flushed) = poll_flushed(|| this.inner.as_mut().try_poll_next(cx));
+            let next = match next {
+                Poll::Ready(Some(Ok(data))) if !flushed || this.buf.is_empty() => {
+                    if this.buf.is_empty() && data.len() >= BUFFER_SIZE {
+                        return Poll::Ready(Some(Ok(data)));
+                    }
+
+                    this.buf.extend_from_slice(&data);
+                    continue;
+                }
+                Poll::Ready(next) => Some(next),
+                Poll::Pending => None,
+            };
+
+            if this.buf.is_empty() {
+                return match next {
+                    Some(next) => Poll::Ready(next),
+                    None => Poll::Pending,
+                };
+            }
+
+            *this.deferred = next;
+            return Poll::Ready(Some(Ok(this.buf.split().freeze())));
         }
     }
 }
@@ -190,11 +322,16 @@ where
     }
 
     fn poll_data(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
-        let mut self_proj = self.project();
-        match ready!(self_proj.inner.try_poll_next_unpin(cx)) {
+        let next = match self.flush_mode {
+            FlushMode::FlushEach => ready!(self.as_mut().project().inner.try_poll_next(cx)),
+            FlushMode::Coalesce => ready!(self.as_mut().poll_coalesced(cx)),
+        };
+
+        let self_proj = self.project();
+        match next {
             Some(Ok(d)) => Some(Ok(d)).into(),
             Some(Err(status)) => match self_proj.role {
                 Role::Client => Some(Err(status)).into(),
@@ -10,23 +10,29 @@ mod decode;
 mod encode;
 #[cfg(feature = "prost")]
 mod prost;
+mod raw;
+mod unknown_fields;
 
 use crate::Status;
 use std::io;
 
-pub(crate) use self::encode::{encode_client, encode_server};
+pub(crate) use self::decode::DecodeErrorHandler;
+pub(crate) use self::encode::{encode_client, encode_server, encode_server_raw, flush_batch};
 
 pub use self::buffer::{DecodeBuf, EncodeBuf};
 #[cfg(feature = "compression")]
 #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
 pub use self::compression::{CompressionEncoding, EnabledCompressionEncodings};
-pub use self::decode::Streaming;
+pub use self::decode::{StreamEnd, Streaming};
+pub use self::encode::FlushMode;
 #[cfg(feature = "prost")]
 #[cfg_attr(docsrs, doc(cfg(feature = "prost")))]
-pub use self::prost::ProstCodec;
+pub use self::prost::{decode_message, encode_message, ProstCodec};
+pub use self::raw::{RawResponse, RawStream};
+pub use self::unknown_fields::UnknownFields;
 
 // 5 bytes
-const HEADER_SIZE: usize =
+pub(crate) const HEADER_SIZE: usize =
     // compression flag
     std::mem::size_of::<u8>() +
     // data length
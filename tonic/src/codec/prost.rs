@@ -1,6 +1,8 @@
-use super::{Codec, DecodeBuf, Decoder, Encoder};
+use super::{Codec, DecodeBuf, Decoder, Encoder, HEADER_SIZE};
 use crate::codec::EncodeBuf;
 use crate::{Code, Status};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use prost1::encoding::{decode_key, encode_key, skip_field, DecodeContext, WireType};
 use prost1::Message;
 use std::marker::PhantomData;
 
@@ -45,8 +47,11 @@ impl<T: Message> Encoder for ProstEncoder<T> {
     type Error = Status;
 
     fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
-        item.encode(buf)
-            .expect("Message only errors if not enough space");
+        // Reserve the exact size upfront so large messages do not grow the
+        // buffer multiple times while being encoded. `encode_raw` skips the
+        // length check of `encode`, which would compute the length again.
+        buf.reserve(item.encoded_len());
+        item.encode_raw(buf);
 
         Ok(())
     }
@@ -61,6 +66,14 @@ impl<U: Message + Default> Decoder for ProstDecoder<U> {
     type Error = Status;
 
     fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        if let Some(unknown_fields) = buf.unknown_fields().cloned() {
+            let raw = buf.copy_to_bytes(buf.remaining());
+            let item: U = Message::decode(&raw[..]).map_err(from_decode_error)?;
+            unknown_fields.add(count_unknown_fields::<U>(&raw));
+
+            return Ok(Some(item));
+        }
+
         let item = Message::decode(buf)
             .map(Option::Some)
             .map_err(from_decode_error)?;
@@ -69,6 +82,142 @@ impl<U: Message + Default> Decoder for ProstDecoder<U> {
     }
 }
 
+// Counts the top-level fields of `raw` whose numbers `U` does not know.
+//
+// prost messages carry no descriptor to look their field numbers up in, so
+// every field number is probed instead: it is known if a message decoded from
+// only that field, holding a value that is not the default, keeps the field
+// when it is encoded again. Unlike comparing against `raw` encoded again,
+// this does not depend on the value that was sent, which may be the default.
+fn count_unknown_fields<U: Message + Default>(mut raw: &[u8]) -> u64 {
+    use std::collections::HashMap;
+
+    let mut known = HashMap::new();
+    let mut unknown = 0;
+
+    while let Some((tag, wire_type)) = next_field(&mut raw) {
+        let is_known = *known
+            .entry((tag, wire_type as u8))
+            .or_insert_with(|| is_known_field::<U>(tag, wire_type));
+
+        if !is_known {
+            unknown += 1;
+        }
+    }
+
+    unknown
+}
+
+fn is_known_field<U: Message + Default>(tag: u32, wire_type: WireType) -> bool {
+    // Values that are not the default for any field of the wire type. A
+    // length delimited field may be a message, which is kept even if empty,
+    // a string or bytes, or a packed repeated field of any scalar.
+    let probes: &[&[u8]] = match wire_type {
+        WireType::Varint => &[&[1]],
+        WireType::ThirtyTwoBit => &[&[1, 0, 0, 0]],
+        WireType::SixtyFourBit => &[&[1, 0, 0, 0, 0, 0, 0, 0]],
+        WireType::LengthDelimited => &[
+            &[0],
+            &[1, b'a'],
+            &[4, 1, 0, 0, 0],
+            &[8, 1, 0, 0, 0, 0, 0, 0, 0],
+        ],
+        // groups are not supported by prost
+        WireType::StartGroup | WireType::EndGroup => return false,
+    };
+
+    probes.iter().any(|value| {
+        let mut probe = Vec::with_capacity(5 + value.len());
+        encode_key(tag, wire_type, &mut probe);
+        probe.extend_from_slice(value);
+
+        let message = match U::decode(&probe[..]) {
+            Ok(message) => message,
+            Err(_) => return false,
+        };
+
+        let encoded = message.encode_to_vec();
+        let mut encoded = &encoded[..];
+        std::iter::from_fn(|| next_field(&mut encoded)).any(|(encoded_tag, _)| encoded_tag == tag)
+    })
+}
+
+// Reads the key of the next field of `buf` and skips its value.
+fn next_field(buf: &mut &[u8]) -> Option<(u32, WireType)> {
+    let (tag, wire_type) = decode_key(buf).ok()?;
+    skip_field(wire_type, tag, buf, DecodeContext::default()).ok()?;
+    Some((tag, wire_type))
+}
+
+/// Encode `message` into a single, uncompressed, length-prefixed gRPC frame.
+///
+/// The frame is exactly what tonic sends on the wire for this message, which
+/// makes it useful for snapshot tests of the serialized form.
+///
+/// ```
+/// let frame = tonic::codec::encode_message(&String::from("hello"));
+/// assert_eq!(&frame[..], b"\x00\x00\x00\x00\x07\x0a\x05hello");
+/// ```
+pub fn encode_message<T: Message>(message: &T) -> Bytes {
+    let len = message.encoded_len();
+    let mut buf = BytesMut::with_capacity(HEADER_SIZE + len);
+
+    buf.put_u8(0);
+    buf.put_u32(len as u32);
+    message.encode_raw(&mut buf);
+
+    buf.freeze()
+}
+
+/// Decode a single length-prefixed gRPC frame, as produced by
+/// [`encode_message`].
+///
+/// The frame must be uncompressed and contain exactly one message.
+pub fn decode_message<T: Message + Default>(mut frame: Bytes) -> Result<T, Status> {
+    if frame.len() < HEADER_SIZE {
+        return Err(Status::new(
+            Code::Internal,
+            format!(
+                "protocol error: frame of {} bytes is shorter than the header",
+                frame.len()
+            ),
+        ));
+    }
+
+    match frame.get_u8() {
+        0 => {}
+        1 => {
+            return Err(Status::new(
+                Code::Unimplemented,
+                "Message compressed, decoding compressed frames is not supported.".to_string(),
+            ))
+        }
+        f => {
+            return Err(Status::new(
+                Code::Internal,
+                format!(
+                    "protocol error: received message with invalid compression flag: {} (valid flags are 0 and 1)",
+                    f
+                ),
+            ))
+        }
+    }
+
+    let len = frame.get_u32() as usize;
+    if frame.len() != len {
+        return Err(Status::new(
+            Code::Internal,
+            format!(
+                "protocol error: frame declares {} bytes but contains {}",
+                len,
+                frame.len()
+            ),
+        ));
+    }
+
+    T::decode(frame).map_err(from_decode_error)
+}
+
 fn from_decode_error(error: prost1::DecodeError) -> crate::Status {
     // Map Protobuf parse errors to an INTERNAL status code, as per
     // https://github.com/grpc/grpc/blob/master/doc/statuscodes.md
@@ -77,9 +226,10 @@ fn from_decode_error(error: prost1::DecodeError) -> crate::Status {
 
 #[cfg(test)]
 mod tests {
+    use super::ProstEncoder;
     use crate::codec::compression::SingleMessageCompressionOverride;
     use crate::codec::{
-        encode_server, DecodeBuf, Decoder, EncodeBuf, Encoder, Streaming, HEADER_SIZE,
+        encode_server, DecodeBuf, Decoder, EncodeBuf, Encoder, FlushMode, Streaming, HEADER_SIZE,
     };
     use crate::Status;
     use bytes::{Buf, BufMut, BytesMut};
@@ -136,6 +286,179 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn encode_coalesced() {
+        let msg = vec![0u8; 100];
+
+        let messages = std::iter::repeat_with(move || Ok::<_, Status>(msg.clone())).take(100);
+        let source = futures_util::stream::iter(messages);
+
+        let body = encode_server(
+            MockEncoder,
+            source,
+            None,
+            SingleMessageCompressionOverride::default(),
+        )
+        .flush_mode(FlushMode::Coalesce);
+
+        futures_util::pin_mut!(body);
+
+        let mut chunks = Vec::new();
+        while let Some(r) = body.data().await {
+            chunks.push(r.unwrap().len());
+        }
+
+        // flushed once the buffer is full and once the stream ends
+        assert_eq!(chunks, [79 * (100 + HEADER_SIZE), 21 * (100 + HEADER_SIZE)]);
+    }
+
+    #[test]
+    fn encode_reserves_once() {
+        let msg = vec![0u8; LEN];
+        let mut buf = BytesMut::new();
+
+        ProstEncoder::<Vec<u8>>::default()
+            .encode(msg, &mut EncodeBuf::new(&mut buf))
+            .unwrap();
+
+        // Growing the buffer while encoding would have left spare capacity.
+        assert_eq!(buf.capacity(), buf.len());
+    }
+
+    #[test]
+    fn encode_computes_len_once() {
+        use prost1::encoding::{bytes, DecodeContext, WireType};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static LEN_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Debug, Default)]
+        struct Counted(Vec<u8>);
+
+        impl prost1::Message for Counted {
+            fn encode_raw<B: BufMut>(&self, buf: &mut B) {
+                bytes::encode(1, &self.0, buf);
+            }
+
+            fn merge_field<B: Buf>(
+                &mut self,
+                tag: u32,
+                wire_type: WireType,
+                buf: &mut B,
+                ctx: DecodeContext,
+            ) -> Result<(), prost1::DecodeError> {
+                prost1::encoding::skip_field(wire_type, tag, buf, ctx)
+            }
+
+            fn encoded_len(&self) -> usize {
+                LEN_CALLS.fetch_add(1, Ordering::SeqCst);
+                bytes::encoded_len(1, &self.0)
+            }
+
+            fn clear(&mut self) {
+                self.0.clear();
+            }
+        }
+
+        let mut buf = BytesMut::new();
+        ProstEncoder::<Counted>::default()
+            .encode(Counted(vec![0u8; LEN]), &mut EncodeBuf::new(&mut buf))
+            .unwrap();
+        assert_eq!(LEN_CALLS.swap(0, Ordering::SeqCst), 1);
+        assert_eq!(buf.capacity(), buf.len());
+
+        super::encode_message(&Counted(vec![0u8; LEN]));
+        assert_eq!(LEN_CALLS.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "transport")]
+    #[tokio::test]
+    async fn reports_stream_end() {
+        use crate::codec::StreamEnd;
+        use bytes::Bytes;
+
+        async fn end(chunks: Vec<Result<Bytes, Status>>) -> Option<StreamEnd> {
+            let body = hyper::Body::wrap_stream(futures_util::stream::iter(chunks));
+            let mut stream =
+                Streaming::new_request(super::ProstDecoder::<String>::default(), body, None);
+
+            assert_eq!(stream.end(), None);
+            while let Ok(Some(_)) = stream.message().await {}
+            stream.end()
+        }
+
+        let frame = super::encode_message(&String::from("hello"));
+
+        assert_eq!(
+            end(vec![Ok(frame.clone())]).await,
+            Some(StreamEnd::Finished)
+        );
+        assert_eq!(
+            end(vec![Ok(frame.slice(..frame.len() - 1))]).await,
+            Some(StreamEnd::Truncated)
+        );
+        assert_eq!(
+            end(vec![Ok(frame), Err(Status::cancelled("reset"))]).await,
+            Some(StreamEnd::Disconnected)
+        );
+    }
+
+    #[test]
+    fn message_round_trip() {
+        let frame = super::encode_message(&String::from("hello"));
+        assert_eq!(&frame[..HEADER_SIZE], &[0, 0, 0, 0, 7]);
+
+        let message: String = super::decode_message(frame.clone()).unwrap();
+        assert_eq!(message, "hello");
+
+        let truncated = frame.slice(..frame.len() - 1);
+        let err = super::decode_message::<String>(truncated).unwrap_err();
+        assert_eq!(err.code(), crate::Code::Internal);
+
+        let mut compressed = BytesMut::from(&frame[..]);
+        compressed[0] = 1;
+        let err = super::decode_message::<String>(compressed.freeze()).unwrap_err();
+        assert_eq!(err.code(), crate::Code::Unimplemented);
+    }
+
+    #[test]
+    fn counts_unknown_fields() {
+        use crate::codec::UnknownFields;
+
+        // field 1 is the string, fields 2 and 3 are unknown varints
+        let mut buf = BytesMut::from(&b"\x0a\x05hello\x10\x01\x18\x02"[..]);
+        let len = buf.len();
+
+        let unknown_fields = UnknownFields::new();
+        let mut decoder = super::ProstDecoder::<String>::default();
+        let message = decoder
+            .decode(&mut DecodeBuf::new(&mut buf, len).with_unknown_fields(Some(&unknown_fields)))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(message, "hello");
+        assert_eq!(unknown_fields.count(), 2);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn known_fields_with_default_values_are_not_unknown() {
+        // an empty string, zero seconds, and the unknown varint field 3
+        assert_eq!(
+            super::count_unknown_fields::<String>(b"\x0a\x00\x18\x02"),
+            1
+        );
+        assert_eq!(
+            super::count_unknown_fields::<prost_types::Duration>(b"\x08\x00\x10\x00\x18\x00"),
+            1
+        );
+        // an empty name and an empty message
+        assert_eq!(
+            super::count_unknown_fields::<prost_types::Option>(b"\x0a\x00\x12\x00"),
+            0
+        );
+    }
+
     #[derive(Debug, Clone, Default)]
     struct MockEncoder;
 
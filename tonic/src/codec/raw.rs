@@ -0,0 +1,57 @@
+use crate::{Response, Status};
+use bytes::Bytes;
+use futures_core::Stream;
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A response whose body is made of pre-encoded gRPC messages.
+pub type RawResponse = Response<RawStream>;
+
+/// A stream of messages that are already in gRPC wire format.
+///
+/// Each item must be a complete length-prefixed message, that is the
+/// compression flag, the big-endian `u32` message length and the encoded
+/// message itself. Items are written to the response body as is, without
+/// going through the codec.
+///
+/// If any of the messages have the compression flag set, the handler is
+/// responsible for setting the matching `grpc-encoding` metadata on the
+/// response.
+///
+/// This is served with [`Grpc::server_streaming_raw`](crate::server::Grpc::server_streaming_raw).
+pub struct RawStream {
+    inner: Pin<Box<dyn Stream<Item = Result<Bytes, Status>> + Send + Sync + 'static>>,
+}
+
+impl RawStream {
+    /// Create a new `RawStream` from a stream of encoded messages.
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, Status>> + Send + Sync + 'static,
+    {
+        Self {
+            inner: Box::pin(stream),
+        }
+    }
+}
+
+impl Stream for RawStream {
+    type Item = Result<Bytes, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl fmt::Debug for RawStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawStream").finish()
+    }
+}
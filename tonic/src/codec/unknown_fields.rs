@@ -0,0 +1,50 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// Counts the fields of decoded messages that the receiving schema does not
+/// know about.
+///
+/// Protobuf decoders skip unknown fields, which keeps old servers
+/// compatible with newer clients but also hides that clients send fields the
+/// server was not built for. With
+/// [`Server::track_unknown_fields`](crate::transport::Server::track_unknown_fields)
+/// every request carries this counter in its extensions, counting the unknown
+/// top-level fields of all request messages of the call decoded so far:
+///
+/// ```
+/// # use tonic::{codec::UnknownFields, Request};
+/// # fn example(request: Request<()>) {
+/// if let Some(unknown) = request.extensions().get::<UnknownFields>() {
+///     if unknown.count() > 0 {
+///         tracing::warn!(unknown = unknown.count(), "request has unknown fields");
+///     }
+/// }
+/// # }
+/// ```
+///
+/// Detecting unknown fields requires decoding and encoding probe messages
+/// for the field numbers of every decoded message, which is why it is off
+/// by default, and is only supported by the prost codec.
+#[derive(Debug, Clone, Default)]
+pub struct UnknownFields {
+    count: Arc<AtomicU64>,
+}
+
+impl UnknownFields {
+    /// Create a new counter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of unknown fields seen so far.
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    #[cfg_attr(not(feature = "prost"), allow(dead_code))]
+    pub(crate) fn add(&self, unknown: u64) {
+        self.count.fetch_add(unknown, Ordering::Relaxed);
+    }
+}
@@ -34,6 +34,53 @@ impl std::fmt::Display for Never {
 
 impl std::error::Error for Never {}
 
+/// The paths of the methods of a generated server, mapped to their index.
+///
+/// Built once when the server is created. Servers with a few methods compare
+/// the path of a request to the path of each method, like a `match` on the
+/// path would, and larger servers look it up in a hash map, so dispatching a
+/// request does not get slower with the number of methods.
+#[derive(Debug)]
+pub struct MethodIndex {
+    paths: crate::util::PathMap,
+}
+
+impl MethodIndex {
+    /// Index `paths` in order.
+    pub fn new(paths: &[&'static str]) -> Self {
+        Self {
+            paths: paths.iter().copied().zip(0..).collect(),
+        }
+    }
+
+    /// Returns the index of the method at `path`.
+    pub fn get(&self, path: &str) -> Option<usize> {
+        self.paths.get(path)
+    }
+}
+
+/// Call the `handler` of a generated server with `request`.
+///
+/// The handlers of methods passed to `Server::blocking_method` are run on the
+/// blocking thread pool, all others on the runtime.
+pub fn call_handler<T, R, F>(
+    request: crate::Request<T>,
+    handler: impl FnOnce(crate::Request<T>) -> F,
+) -> BoxFuture<R, crate::Status>
+where
+    F: Future<Output = Result<R, crate::Status>> + Send + 'static,
+    R: Send + 'static,
+{
+    #[cfg(feature = "transport")]
+    {
+        if crate::transport::is_blocking(&request) {
+            return crate::transport::spawn_handler(handler(request));
+        }
+    }
+
+    Box::pin(handler(request))
+}
+
 pub fn empty_body() -> crate::body::BoxBody {
     http_body::Empty::new().map_err(|err| match err {}).boxed()
 }
@@ -64,6 +64,35 @@ impl Extensions {
     }
 }
 
+/// The id of the HTTP/2 stream a call was carried on.
+///
+/// This is meant for diagnostics, for example to match a call to the frames
+/// of a packet capture. It is read by [`Request::stream_id`] on the server and
+/// [`Response::stream_id`] on the client.
+///
+/// Stream ids are best effort. `hyper` does not expose the ids of the streams
+/// it manages, so calls made or served with tonic's `transport` module carry
+/// no `StreamId` and both methods return `None`. A custom transport, or a
+/// middleware that learns the id some other way, can insert a `StreamId` into
+/// the `http` extensions of the request or response to fill it in.
+///
+/// [`Request::stream_id`]: crate::Request::stream_id
+/// [`Response::stream_id`]: crate::Response::stream_id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StreamId(u32);
+
+impl StreamId {
+    /// Create a new `StreamId`.
+    pub fn new(id: u32) -> Self {
+        StreamId(id)
+    }
+
+    /// Get the raw stream id.
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+}
+
 impl fmt::Debug for Extensions {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Extensions").finish()
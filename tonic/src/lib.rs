@@ -31,6 +31,8 @@
 //! - `compression`: Enables compressing requests, responses, and streams. Note
 //! that you must enable the `compression` feature on both `tonic` and
 //! `tonic-build` to use it. Depends on [flate2]. Not enabled by default.
+//! - `serde`: Implements `serde::Serialize` for [`Status`], to log errors in a
+//! structured form. Not enabled by default.
 //!
 //! # Structure
 //!
@@ -84,22 +86,31 @@
 #![doc(test(no_crate_inject, attr(deny(rust_2018_idioms))))]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+// the messages derived with `prost-derive` refer to `::prost`
+#[cfg(feature = "prost")]
+extern crate prost1 as prost;
+
 pub mod body;
+#[cfg(feature = "transport")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport")))]
+pub mod broadcast;
 pub mod client;
 pub mod codec;
 pub mod metadata;
 pub mod server;
 pub mod service;
+pub mod status;
 
 #[cfg(feature = "transport")]
 #[cfg_attr(docsrs, doc(cfg(feature = "transport")))]
 pub mod transport;
 
+#[cfg(feature = "transport")]
+mod cancellation;
 mod extensions;
 mod macros;
 mod request;
 mod response;
-mod status;
 mod util;
 
 /// A re-export of [`async-trait`](https://docs.rs/async-trait) for use with codegen.
@@ -107,9 +118,12 @@ mod util;
 #[cfg_attr(docsrs, doc(cfg(feature = "codegen")))]
 pub use async_trait::async_trait;
 
+#[cfg(feature = "transport")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport")))]
+pub use cancellation::CancellationToken;
 #[doc(inline)]
 pub use codec::Streaming;
-pub use extensions::Extensions;
+pub use extensions::{Extensions, StreamId};
 pub use request::{IntoRequest, IntoStreamingRequest, Request};
 pub use response::Response;
 pub use status::{Code, Status};
@@ -31,6 +31,25 @@ use std::marker::PhantomData;
 ///
 /// assert!(!map.contains_key("x-host"));
 /// ```
+///
+/// A key may be associated with multiple values, just like repeated HTTP
+/// headers. [`insert`](MetadataMap::insert) replaces all existing values for
+/// a key while [`append`](MetadataMap::append) adds another value to it.
+/// Every value is sent as its own header, so all of them are preserved
+/// across the wire, and for binary keys each value is base64 encoded and
+/// decoded independently.
+///
+/// ```
+/// # use tonic::metadata::*;
+/// let mut map = MetadataMap::new();
+///
+/// map.append("x-host", "a.example.com".parse().unwrap());
+/// map.append("x-host", "b.example.com".parse().unwrap());
+/// assert_eq!(map.get_all("x-host").iter().count(), 2);
+///
+/// map.insert("x-host", "c.example.com".parse().unwrap());
+/// assert_eq!(map.get_all("x-host").iter().count(), 1);
+/// ```
 #[derive(Clone, Debug, Default)]
 pub struct MetadataMap {
     headers: http::HeaderMap,
@@ -249,6 +268,10 @@ impl MetadataMap {
         self.headers
     }
 
+    pub(crate) fn as_headers(&self) -> &http::HeaderMap {
+        &self.headers
+    }
+
     pub(crate) fn into_sanitized_headers(mut self) -> http::HeaderMap {
         for r in &Self::GRPC_RESERVED_HEADERS {
             self.headers.remove(*r);
@@ -3,11 +3,17 @@ use crate::metadata::{MetadataMap, MetadataValue};
 use crate::transport::server::TlsConnectInfo;
 #[cfg(feature = "transport")]
 use crate::transport::{server::TcpConnectInfo, Certificate};
-use crate::Extensions;
+use crate::{Code, Extensions, Status, StreamId};
 use futures_core::Stream;
+use http::{HeaderMap, HeaderValue};
 #[cfg(feature = "transport")]
 use std::sync::Arc;
-use std::{net::SocketAddr, time::Duration};
+use std::{
+    fmt,
+    net::SocketAddr,
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
 /// A gRPC request and metadata from an RPC call.
 #[derive(Debug)]
@@ -135,6 +141,82 @@ impl<T> Request<T> {
         &mut self.metadata
     }
 
+    /// Get the value of the ASCII metadata `key`, which the request must
+    /// have.
+    ///
+    /// Fails with `INVALID_ARGUMENT` if the metadata is missing or if its
+    /// value is not visible ASCII, so the handler can return the error as is:
+    ///
+    /// ```
+    /// use tonic::{Request, Status};
+    ///
+    /// fn tenant(request: &Request<()>) -> Result<&str, Status> {
+    ///     request.require_metadata("x-tenant")
+    /// }
+    ///
+    /// let mut request = Request::new(());
+    /// assert_eq!(tenant(&request).unwrap_err().code(), tonic::Code::InvalidArgument);
+    ///
+    /// request.metadata_mut().insert("x-tenant", "acme".parse().unwrap());
+    /// assert_eq!(tenant(&request).unwrap(), "acme");
+    /// ```
+    pub fn require_metadata(&self, key: &str) -> Result<&str, Status> {
+        self.require_metadata_with(key, Code::InvalidArgument)
+    }
+
+    /// Get the value of the ASCII metadata `key` like [`require_metadata`],
+    /// but fail with `code` if the metadata is missing.
+    ///
+    /// ```
+    /// use tonic::{Code, Request, Status};
+    ///
+    /// fn api_key(request: &Request<()>) -> Result<&str, Status> {
+    ///     request.require_metadata_with("x-api-key", Code::Unauthenticated)
+    /// }
+    ///
+    /// let request = Request::new(());
+    /// assert_eq!(api_key(&request).unwrap_err().code(), Code::Unauthenticated);
+    /// ```
+    ///
+    /// [`require_metadata`]: Request::require_metadata
+    pub fn require_metadata_with(&self, key: &str, code: Code) -> Result<&str, Status> {
+        let value = self
+            .metadata
+            .get(key)
+            .ok_or_else(|| Status::new(code, format!("missing metadata `{}`", key)))?;
+
+        value
+            .to_str()
+            .map_err(|_| Status::invalid_argument(format!("metadata `{}` is not ASCII", key)))
+    }
+
+    /// Get the value of the ASCII metadata `key`, which the request must
+    /// have, parsed as a `V`.
+    ///
+    /// Fails like [`require_metadata`], and with `INVALID_ARGUMENT` if the
+    /// value does not parse.
+    ///
+    /// ```
+    /// use tonic::Request;
+    ///
+    /// let mut request = Request::new(());
+    /// request.metadata_mut().insert("x-page-size", "50".parse().unwrap());
+    ///
+    /// let page_size: u32 = request.require_metadata_parsed("x-page-size").unwrap();
+    /// assert_eq!(page_size, 50);
+    /// ```
+    ///
+    /// [`require_metadata`]: Request::require_metadata
+    pub fn require_metadata_parsed<V>(&self, key: &str) -> Result<V, Status>
+    where
+        V: FromStr,
+        V::Err: fmt::Display,
+    {
+        self.require_metadata(key)?
+            .parse()
+            .map_err(|e| Status::invalid_argument(format!("invalid metadata `{}`: {}", key, e)))
+    }
+
     /// Consumes `self`, returning the message
     pub fn into_inner(self) -> T {
         self.message
@@ -185,6 +267,7 @@ impl<T> Request<T> {
         request
     }
 
+    /// Map the message of the request, keeping its metadata and extensions.
     #[doc(hidden)]
     pub fn map<F, U>(self, f: F) -> Request<U>
     where
@@ -195,7 +278,7 @@ impl<T> Request<T> {
         Request {
             metadata: self.metadata,
             message,
-            extensions: Extensions::new(),
+            extensions: self.extensions,
         }
     }
 
@@ -233,6 +316,18 @@ impl<T> Request<T> {
         }
     }
 
+    /// Get the id of the HTTP/2 stream this request was received on.
+    ///
+    /// This is best effort and returns `None` unless the transport inserted
+    /// a [`StreamId`] into the request extensions. Requests received by
+    /// tonic's own server always return `None`, since `hyper` does not expose
+    /// stream ids.
+    ///
+    /// [`StreamId`]: crate::StreamId
+    pub fn stream_id(&self) -> Option<u32> {
+        self.extensions().get::<StreamId>().map(StreamId::get)
+    }
+
     /// Get the peer certificates of the connected client.
     ///
     /// This is used to fetch the certificates from the TLS session
@@ -288,7 +383,36 @@ impl<T> Request<T> {
             .insert(crate::metadata::GRPC_TIMEOUT_HEADER, value);
     }
 
+    /// Set how the messages of this request are handed to the transport.
+    ///
+    /// See [`FlushMode`] for the tradeoff. This only matters for client and
+    /// bi-directional streaming calls.
+    ///
+    /// [`FlushMode`]: crate::codec::FlushMode
+    pub fn set_flush_mode(&mut self, flush_mode: crate::codec::FlushMode) {
+        self.extensions_mut().insert(flush_mode);
+    }
+
+    /// Get the time left until the deadline of this call.
+    ///
+    /// The deadline is the shorter of the client's `grpc-timeout` and the
+    /// server's [`timeout`], counted from when the request was received.
+    /// Returns `None` if neither is set, and `Duration::from_secs(0)` once
+    /// the deadline has passed. This can be used to bound the time spent on
+    /// downstream calls. This currently only works on the server side.
+    ///
+    /// [`timeout`]: crate::transport::Server::timeout
+    pub fn deadline_remaining(&self) -> Option<Duration> {
+        self.extensions()
+            .get::<Deadline>()
+            .map(|deadline| deadline.0.saturating_duration_since(Instant::now()))
+    }
+
     /// Returns a reference to the associated extensions.
+    ///
+    /// The extensions of a client request are passed on in the extensions
+    /// of the `http::Request` handed to the transport, so that middleware
+    /// of the channel can see them.
     pub fn extensions(&self) -> &Extensions {
         &self.extensions
     }
@@ -385,7 +509,7 @@ mod sealed {
     pub trait Sealed {}
 }
 
-fn duration_to_grpc_timeout(duration: Duration) -> String {
+pub(crate) fn duration_to_grpc_timeout(duration: Duration) -> String {
     fn try_format<T: Into<u128>>(
         duration: Duration,
         unit: char,
@@ -419,6 +543,64 @@ fn duration_to_grpc_timeout(duration: Duration) -> String {
         .expect("duration is unrealistically large")
 }
 
+const SECONDS_IN_HOUR: u64 = 60 * 60;
+const SECONDS_IN_MINUTE: u64 = 60;
+
+/// Tries to parse the `grpc-timeout` header if it is present. If we fail to parse, returns
+/// the value we attempted to parse.
+///
+/// Follows the [gRPC over HTTP2 spec](https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-HTTP2.md).
+pub(crate) fn try_parse_grpc_timeout(
+    headers: &HeaderMap<HeaderValue>,
+) -> Result<Option<Duration>, &HeaderValue> {
+    match headers.get(crate::metadata::GRPC_TIMEOUT_HEADER) {
+        Some(val) => {
+            let (timeout_value, timeout_unit) = val
+                .to_str()
+                .map_err(|_| val)
+                .and_then(|s| if s.is_empty() { Err(val) } else { Ok(s) })?
+                // `HeaderValue::to_str` only returns `Ok` if the header contains ASCII so this
+                // `split_at` will never panic from trying to split in the middle of a character.
+                // See https://docs.rs/http/0.2.4/http/header/struct.HeaderValue.html#method.to_str
+                //
+                // `len - 1` also wont panic since we just checked `s.is_empty`.
+                .split_at(val.len() - 1);
+
+            // gRPC spec specifies `TimeoutValue` will be at most 8 digits
+            // Caping this at 8 digits also prevents integer overflow from ever occurring
+            if timeout_value.len() > 8 {
+                return Err(val);
+            }
+
+            let timeout_value: u64 = timeout_value.parse().map_err(|_| val)?;
+
+            let duration = match timeout_unit {
+                // Hours
+                "H" => Duration::from_secs(timeout_value * SECONDS_IN_HOUR),
+                // Minutes
+                "M" => Duration::from_secs(timeout_value * SECONDS_IN_MINUTE),
+                // Seconds
+                "S" => Duration::from_secs(timeout_value),
+                // Milliseconds
+                "m" => Duration::from_millis(timeout_value),
+                // Microseconds
+                "u" => Duration::from_micros(timeout_value),
+                // Nanoseconds
+                "n" => Duration::from_nanos(timeout_value),
+                _ => return Err(val),
+            };
+
+            Ok(Some(duration))
+        }
+        None => Ok(None),
+    }
+}
+
+/// The point in time a call has to complete by, set by the server when the
+/// request is received.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Deadline(pub(crate) Instant);
+
 /// When converting a `tonic::Request` into a `http::Request` should reserved
 /// headers be removed?
 pub(crate) enum SanitizeHeaders {
@@ -445,6 +627,65 @@ mod tests {
         assert!(http_request.headers().is_empty());
     }
 
+    #[test]
+    fn stream_id_from_extensions() {
+        assert_eq!(Request::new(()).stream_id(), None);
+
+        let mut r = Request::new(());
+
+        r.extensions_mut().insert(StreamId::new(5));
+        assert_eq!(r.stream_id(), Some(5));
+    }
+
+    #[test]
+    fn map_keeps_extensions() {
+        #[derive(Debug, PartialEq)]
+        struct Marker;
+
+        let mut r = Request::new(1);
+        r.extensions_mut().insert(Marker);
+
+        let r = r.map(|message| message + 1);
+        assert_eq!(r.extensions().get::<Marker>(), Some(&Marker));
+
+        let http_request = r.into_http(Uri::default(), SanitizeHeaders::Yes);
+        assert_eq!(http_request.extensions().get::<Marker>(), Some(&Marker));
+    }
+
+    #[test]
+    fn require_metadata_maps_errors() {
+        let mut r = Request::new(());
+        r.metadata_mut()
+            .insert("x-opaque", MetadataValue::try_from_bytes(b"\xfa").unwrap());
+        r.metadata_mut()
+            .insert("x-page-size", MetadataValue::from_static("many"));
+
+        let status = r.require_metadata("x-api-key").unwrap_err();
+        assert_eq!(status.code(), crate::Code::InvalidArgument);
+        assert_eq!(status.message(), "missing metadata `x-api-key`");
+
+        let status = r
+            .require_metadata_with("x-api-key", crate::Code::Unauthenticated)
+            .unwrap_err();
+        assert_eq!(status.code(), crate::Code::Unauthenticated);
+
+        let status = r.require_metadata("x-opaque").unwrap_err();
+        assert_eq!(status.code(), crate::Code::InvalidArgument);
+
+        // the chosen code is only for missing metadata
+        let status = r
+            .require_metadata_with("x-opaque", crate::Code::Unauthenticated)
+            .unwrap_err();
+        assert_eq!(status.code(), crate::Code::InvalidArgument);
+
+        let status = r.require_metadata_parsed::<u32>("x-page-size").unwrap_err();
+        assert_eq!(status.code(), crate::Code::InvalidArgument);
+        assert_eq!(
+            status.message(),
+            "invalid metadata `x-page-size`: invalid digit found in string"
+        );
+    }
+
     #[test]
     fn duration_to_grpc_timeout_less_than_second() {
         let timeout = Duration::from_millis(500);
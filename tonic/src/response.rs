@@ -1,4 +1,4 @@
-use crate::{metadata::MetadataMap, Extensions};
+use crate::{metadata::MetadataMap, Extensions, StreamId};
 
 /// A gRPC response and metadata from an RPC call.
 #[derive(Debug)]
@@ -49,6 +49,28 @@ impl<T> Response<T> {
         &mut self.metadata
     }
 
+    /// Set how the messages of this response are handed to the transport.
+    ///
+    /// See [`FlushMode`] for the tradeoff. This only matters for server and
+    /// bi-directional streaming calls.
+    ///
+    /// [`FlushMode`]: crate::codec::FlushMode
+    pub fn set_flush_mode(&mut self, flush_mode: crate::codec::FlushMode) {
+        self.extensions_mut().insert(flush_mode);
+    }
+
+    /// Get the id of the HTTP/2 stream this response was received on.
+    ///
+    /// This is best effort and returns `None` unless the transport inserted
+    /// a [`StreamId`] into the response extensions. Responses received over
+    /// tonic's `Channel` always return `None`, since `hyper` does not expose
+    /// stream ids.
+    ///
+    /// [`StreamId`]: crate::StreamId
+    pub fn stream_id(&self) -> Option<u32> {
+        self.extensions.get::<StreamId>().map(StreamId::get)
+    }
+
     /// Consumes `self`, returning the message
     pub fn into_inner(self) -> T {
         self.message
@@ -141,4 +163,17 @@ mod tests {
         let http_response = r.into_http();
         assert!(http_response.headers().is_empty());
     }
+
+    #[test]
+    fn stream_id_from_extensions() {
+        assert_eq!(
+            Response::from_http(http::Response::new(())).stream_id(),
+            None
+        );
+
+        let mut res = http::Response::new(());
+
+        res.extensions_mut().insert(StreamId::new(3));
+        assert_eq!(Response::from_http(res).stream_id(), Some(3));
+    }
 }
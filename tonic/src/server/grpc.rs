@@ -4,10 +4,14 @@ use crate::codec::compression::{
 };
 use crate::{
     body::BoxBody,
-    codec::{encode_server, Codec, Streaming},
+    codec::{
+        encode_server, encode_server_raw, Codec, DecodeErrorHandler, FlushMode, Streaming,
+        UnknownFields,
+    },
     server::{ClientStreamingService, ServerStreamingService, StreamingService, UnaryService},
     Code, Request, Status,
 };
+use bytes::Bytes;
 use futures_core::TryStream;
 use futures_util::{future, stream, TryStreamExt};
 use http_body::Body;
@@ -257,6 +261,42 @@ where
         )
     }
 
+    /// Handle a server side streaming request whose response messages are
+    /// already encoded.
+    ///
+    /// The response stream yields complete gRPC frames, see
+    /// [`RawStream`](crate::codec::RawStream), which are written to the body
+    /// without being encoded or compressed again. Headers and trailers are
+    /// still handled as for [`server_streaming`](Grpc::server_streaming).
+    pub async fn server_streaming_raw<S, B>(
+        &mut self,
+        mut service: S,
+        req: http::Request<B>,
+    ) -> http::Response<BoxBody>
+    where
+        S: ServerStreamingService<T::Decode, Response = Bytes>,
+        S::ResponseStream: Send + Sync + 'static,
+        B: Body + Send + Sync + 'static,
+        B::Error: Into<crate::Error> + Send,
+    {
+        let request = t!(self.map_request_unary(req).await);
+
+        let response = t!(service.call(request).await);
+
+        let (mut parts, body) = response.into_http().into_parts();
+
+        // Set the content type
+        parts.headers.insert(
+            http::header::CONTENT_TYPE,
+            http::header::HeaderValue::from_static("application/grpc"),
+        );
+
+        let flush_mode = parts.extensions.get::<FlushMode>().copied();
+        let body = encode_server_raw(body).flush_mode(flush_mode.unwrap_or_default());
+
+        http::Response::from_parts(parts, BoxBody::new(body))
+    }
+
     /// Handle a client side streaming gRPC request.
     pub async fn client_streaming<S, B>(
         &mut self,
@@ -335,6 +375,8 @@ where
         #[cfg(feature = "compression")]
         let request_compression_encoding = self.request_encoding_if_supported(&request)?;
 
+        let unknown_fields = request.extensions().get::<UnknownFields>().cloned();
+        let decode_error_handler = request.extensions().get::<DecodeErrorHandler>().cloned();
         let (parts, body) = request.into_parts();
 
         #[cfg(feature = "compression")]
@@ -344,6 +386,10 @@ where
         #[cfg(not(feature = "compression"))]
         let stream = Streaming::new_request(self.codec.decoder(), body);
 
+        let stream = stream
+            .track_unknown_fields(unknown_fields)
+            .on_decode_error(decode_error_handler);
+
         futures_util::pin_mut!(stream);
 
         let message = stream
@@ -371,6 +417,9 @@ where
         #[cfg(feature = "compression")]
         let encoding = self.request_encoding_if_supported(&request)?;
 
+        let unknown_fields = request.extensions().get::<UnknownFields>().cloned();
+        let decode_error_handler = request.extensions().get::<DecodeErrorHandler>().cloned();
+
         #[cfg(feature = "compression")]
         let request =
             request.map(|body| Streaming::new_request(self.codec.decoder(), body, encoding));
@@ -378,6 +427,12 @@ where
         #[cfg(not(feature = "compression"))]
         let request = request.map(|body| Streaming::new_request(self.codec.decoder(), body));
 
+        let request = request.map(|stream| {
+            stream
+                .track_unknown_fields(unknown_fields)
+                .on_decode_error(decode_error_handler)
+        });
+
         Ok(Request::from_http(request))
     }
 
@@ -412,6 +467,7 @@ where
             );
         }
 
+        let flush_mode = parts.extensions.get::<FlushMode>().copied();
         let body = encode_server(
             self.codec.encoder(),
             body.into_stream(),
@@ -419,7 +475,8 @@ where
             accept_encoding,
             #[cfg(feature = "compression")]
             compression_override,
-        );
+        )
+        .flush_mode(flush_mode.unwrap_or_default());
 
         http::Response::from_parts(parts, BoxBody::new(body))
     }
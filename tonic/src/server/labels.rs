@@ -0,0 +1,77 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+/// Labels a handler attaches to its call for the layers observing it.
+///
+/// Metrics and access logs are recorded by layers that only see the HTTP
+/// request and response, while the context worth recording, like the tenant
+/// of a call or whether a lookup hit, is only known to the handler. The
+/// [`Server`] inserts a `CallLabels` into the extensions of every request,
+/// which the handler fills with labels of its own types:
+///
+/// ```
+/// use tonic::{server::CallLabels, Request};
+///
+/// #[derive(Clone)]
+/// struct Tenant(String);
+///
+/// # fn example(request: Request<()>) {
+/// if let Some(labels) = request.extensions().get::<CallLabels>() {
+///     labels.insert(Tenant("acme".to_string()));
+/// }
+/// # }
+/// ```
+///
+/// Clones share the labels, so a layer that keeps a clone of the
+/// `CallLabels` of the `http::Request` reads what the handler set once the
+/// call completes:
+///
+/// ```
+/// # use tonic::server::CallLabels;
+/// # #[derive(Clone)] struct Tenant(String);
+/// # fn example(request: &http::Request<()>) {
+/// let labels = request.extensions().get::<CallLabels>().cloned();
+///
+/// // ... after the response
+/// if let Some(Tenant(tenant)) = labels.and_then(|labels| labels.get::<Tenant>()) {
+///     tracing::info!(%tenant, "call completed");
+/// }
+/// # }
+/// ```
+///
+/// [`Server`]: crate::transport::Server
+#[derive(Clone, Default)]
+pub struct CallLabels {
+    labels: Arc<Mutex<http::Extensions>>,
+}
+
+impl CallLabels {
+    /// Create an empty set of labels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach a label, returning the label of the same type attached
+    /// before.
+    pub fn insert<T: Clone + Send + Sync + 'static>(&self, label: T) -> Option<T> {
+        self.labels.lock().unwrap().insert(label)
+    }
+
+    /// Returns the label of type `T`, if one was attached.
+    pub fn get<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        self.labels.lock().unwrap().get::<T>().cloned()
+    }
+
+    /// Remove the label of type `T`, returning it.
+    pub fn remove<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        self.labels.lock().unwrap().remove::<T>()
+    }
+}
+
+impl fmt::Debug for CallLabels {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallLabels").finish()
+    }
+}
@@ -7,11 +7,65 @@
 //! tool that will provide the user some custom way to implement the server that
 //! will implement the proper gRPC service. Thusly, they are a bit hard to use
 //! by hand.
+//!
+//! ## Streaming responses
+//!
+//! A server streaming handler can return any `Stream` of messages as its
+//! response, including the [`ReceiverStream`] wrapper of a tokio channel fed
+//! by a spawned task:
+//!
+//! ```
+//! use tokio::sync::mpsc;
+//! use tokio_stream::wrappers::ReceiverStream;
+//! use tonic::{Request, Response, Status};
+//! # use std::sync::Arc;
+//! # #[derive(Clone, Debug)] struct Feature;
+//! # struct Rectangle;
+//! # struct RouteGuideService { features: Arc<Vec<Feature>> }
+//! # fn in_range(_: &Feature, _: &Rectangle) -> bool { true }
+//!
+//! // type ListFeaturesStream = ReceiverStream<Result<Feature, Status>>;
+//!
+//! # impl RouteGuideService {
+//! async fn list_features(
+//!     &self,
+//!     request: Request<Rectangle>,
+//! ) -> Result<Response<ReceiverStream<Result<Feature, Status>>>, Status> {
+//!     let (tx, rx) = mpsc::channel(4);
+//!     let features = self.features.clone();
+//!
+//!     tokio::spawn(async move {
+//!         for feature in &features[..] {
+//!             if in_range(feature, request.get_ref()) {
+//!                 if tx.send(Ok(feature.clone())).await.is_err() {
+//!                     // the client is gone
+//!                     break;
+//!                 }
+//!             }
+//!         }
+//!     });
+//!
+//!     Ok(Response::new(ReceiverStream::new(rx)))
+//! }
+//! # }
+//! ```
+//!
+//! [`response_channel`] does the same and also fails sending with
+//! `CANCELLED` once the client is gone.
+//!
+//! [`ReceiverStream`]: https://docs.rs/tokio-stream/0.1/tokio_stream/wrappers/struct.ReceiverStream.html
 
 mod grpc;
+mod labels;
+#[cfg(feature = "transport")]
+mod sender;
 mod service;
 
 pub use self::grpc::Grpc;
+pub use self::labels::CallLabels;
+#[cfg(feature = "transport")]
+#[cfg_attr(docsrs, doc(cfg(feature = "transport")))]
+pub use self::sender::{response_channel, ResponseSender, ResponseStream};
 pub use self::service::{
     ClientStreamingService, ServerStreamingService, StreamingService, UnaryService,
 };
@@ -0,0 +1,300 @@
+use crate::Status;
+use futures_core::Stream;
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+
+/// Create a bounded channel to feed the response of a streaming handler.
+///
+/// The [`ResponseStream`] is returned from the handler while the
+/// [`ResponseSender`] is usually moved into a spawned task that produces the
+/// messages. Once the client cancels the call, or the stream ends for any
+/// other reason, sending fails with `CANCELLED` so that the producer can stop
+/// with `?` instead of producing values nobody will read.
+///
+/// ```
+/// # use tonic::{Response, Status};
+/// # async fn example() -> Result<Response<tonic::server::ResponseStream<String>>, Status> {
+/// let (tx, rx) = tonic::server::response_channel(4);
+///
+/// tokio::spawn(async move {
+///     for i in 0.. {
+///         tx.send(format!("message {}", i)).await?;
+///     }
+///
+///     Ok::<_, Status>(())
+/// });
+///
+/// Ok(Response::new(rx))
+/// # }
+/// ```
+///
+/// For live data that is worthless once stale, see
+/// [`ResponseStream::max_queue_time`].
+pub fn response_channel<T>(buffer: usize) -> (ResponseSender<T>, ResponseStream<T>) {
+    let (tx, rx) = mpsc::channel(buffer);
+    let dropped = Arc::new(AtomicU64::new(0));
+
+    let tx = ResponseSender {
+        tx,
+        warned: Arc::new(AtomicBool::new(false)),
+        dropped: dropped.clone(),
+    };
+
+    let rx = ResponseStream {
+        rx,
+        max_queue_time: None,
+        dropped,
+    };
+
+    (tx, rx)
+}
+
+/// The sending half of a [`response_channel`].
+pub struct ResponseSender<T> {
+    tx: mpsc::Sender<Message<T>>,
+    // shared by all clones so a closed stream is only logged once
+    warned: Arc<AtomicBool>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// The receiving half of a [`response_channel`], used as the response stream.
+pub struct ResponseStream<T> {
+    rx: mpsc::Receiver<Message<T>>,
+    max_queue_time: Option<Duration>,
+    dropped: Arc<AtomicU64>,
+}
+
+enum Message<T> {
+    Item(Result<T, Status>, Instant),
+    Flush,
+}
+
+impl<T> ResponseSender<T> {
+    /// Send a message, waiting for capacity if the buffer is full.
+    ///
+    /// Returns `CANCELLED` if the response stream has been closed.
+    pub async fn send(&self, message: T) -> Result<(), Status> {
+        self.send_result(Ok(message)).await
+    }
+
+    /// End the response stream with `status`.
+    ///
+    /// Messages sent before are still delivered to the client.
+    pub async fn send_error(&self, status: Status) -> Result<(), Status> {
+        self.send_result(Err(status)).await
+    }
+
+    /// Hand the messages sent so far to the transport.
+    ///
+    /// With [`FlushMode::Coalesce`] messages sent back to back are buffered
+    /// and handed over together, this ends the current batch. With the
+    /// default [`FlushMode::FlushEach`] every message is handed over on its
+    /// own already.
+    ///
+    /// [`FlushMode::Coalesce`]: crate::codec::FlushMode::Coalesce
+    /// [`FlushMode::FlushEach`]: crate::codec::FlushMode::FlushEach
+    pub async fn flush(&self) -> Result<(), Status> {
+        self.send_message(Message::Flush).await
+    }
+
+    /// Returns the number of messages dropped for having been queued longer
+    /// than [`ResponseStream::max_queue_time`].
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` once the response stream has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    /// Wait until the response stream has been closed.
+    pub async fn closed(&self) {
+        self.tx.closed().await
+    }
+
+    async fn send_result(&self, result: Result<T, Status>) -> Result<(), Status> {
+        self.send_message(Message::Item(result, Instant::now()))
+            .await
+    }
+
+    async fn send_message(&self, message: Message<T>) -> Result<(), Status> {
+        self.tx.send(message).await.map_err(|_| {
+            if !self.warned.swap(true, Ordering::Relaxed) {
+                tracing::debug!("sending on a closed response stream");
+            }
+
+            Status::cancelled("response stream closed")
+        })
+    }
+}
+
+impl<T> Clone for ResponseSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            warned: self.warned.clone(),
+            dropped: self.dropped.clone(),
+        }
+    }
+}
+
+impl<T> ResponseStream<T> {
+    /// Drop messages that were queued for longer than `max`.
+    ///
+    /// When the client reads slower than messages are produced, messages
+    /// wait in the channel. With a maximum queue time, messages that waited
+    /// longer than `max` since they were sent are dropped instead of being
+    /// delivered late. Errors are never dropped. The number of dropped
+    /// messages is available from [`ResponseSender::dropped`].
+    pub fn max_queue_time(self, max: Duration) -> Self {
+        Self {
+            max_queue_time: Some(max),
+            ..self
+        }
+    }
+
+    fn is_stale(&self, sent: Instant) -> bool {
+        match self.max_queue_time {
+            Some(max) => sent.elapsed() > max,
+            None => false,
+        }
+    }
+}
+
+impl<T> Stream for ResponseStream<T> {
+    type Item = Result<T, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match futures_util::ready!(self.rx.poll_recv(cx)) {
+                Some(Message::Item(Ok(_), sent)) if self.is_stale(sent) => {
+                    let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                    tracing::trace!(dropped, "dropping stale response message");
+                }
+                Some(Message::Item(item, _)) => return Poll::Ready(Some(item)),
+                Some(Message::Flush) => crate::codec::flush_batch(),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for ResponseSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseSender")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+impl<T> fmt::Debug for ResponseStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseStream")
+            .field("max_queue_time", &self.max_queue_time)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Code;
+
+    #[tokio::test]
+    async fn send_after_close_fails() {
+        let (tx, rx) = response_channel::<u32>(1);
+
+        tx.send(1).await.unwrap();
+        assert!(!tx.is_closed());
+
+        drop(rx);
+        assert!(tx.is_closed());
+
+        let err = tx.send(2).await.unwrap_err();
+        assert_eq!(err.code(), Code::Cancelled);
+        tx.clone().send(3).await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn drops_stale_messages() {
+        use futures_util::StreamExt;
+
+        let (tx, rx) = response_channel::<u32>(4);
+        let mut rx = rx.max_queue_time(Duration::from_millis(50));
+
+        tx.send(1).await.unwrap();
+        tx.send_error(Status::internal("stale errors are kept"))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        tx.send(2).await.unwrap();
+
+        assert_eq!(rx.next().await.unwrap().unwrap_err().code(), Code::Internal);
+        assert_eq!(rx.next().await.unwrap().unwrap(), 2);
+        assert_eq!(tx.dropped(), 1);
+    }
+
+    #[cfg(all(feature = "compression", feature = "prost"))]
+    #[tokio::test]
+    async fn flush_ends_coalesced_batch() {
+        use crate::codec::{
+            compression::SingleMessageCompressionOverride, encode_server, Codec, FlushMode,
+            ProstCodec,
+        };
+        use http_body::Body;
+
+        let (tx, rx) = response_channel(8);
+
+        tx.send(String::from("a")).await.unwrap();
+        tx.send(String::from("b")).await.unwrap();
+        tx.flush().await.unwrap();
+        tx.send(String::from("c")).await.unwrap();
+        drop(tx);
+
+        let body = encode_server(
+            ProstCodec::<String, String>::default().encoder(),
+            rx,
+            None,
+            SingleMessageCompressionOverride::default(),
+        )
+        .flush_mode(FlushMode::Coalesce);
+
+        futures_util::pin_mut!(body);
+
+        let mut chunks = Vec::new();
+        while let Some(data) = body.data().await {
+            chunks.push(data.unwrap().len());
+        }
+
+        // each message is a 5 byte header and 3 bytes of protobuf
+        assert_eq!(chunks, [16, 8]);
+    }
+
+    #[tokio::test]
+    async fn flush_is_not_a_message() {
+        use futures_util::StreamExt;
+
+        let (tx, mut rx) = response_channel::<u32>(8);
+
+        tx.send(1).await.unwrap();
+        tx.flush().await.unwrap();
+        tx.flush().await.unwrap();
+        tx.send(2).await.unwrap();
+        tx.flush().await.unwrap();
+        drop(tx);
+
+        assert_eq!(rx.next().await.unwrap().unwrap(), 1);
+        assert_eq!(rx.next().await.unwrap().unwrap(), 2);
+        assert!(rx.next().await.is_none());
+    }
+}
@@ -0,0 +1,285 @@
+//! Inject errors into calls to test how clients handle them.
+//!
+//! See [`fault_injection`] for more details.
+
+use crate::{service::interceptor::ResponseFuture, Code, Status};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Create a layer that fails calls with injected errors.
+///
+/// Each fault is added for a method path, for example
+/// `/routeguide.RouteGuide/GetFeature`, and fails the calls it picks with a
+/// status before they reach the handler. This tests how clients handle
+/// errors, for example their retries or circuit breakers, against a real
+/// server without changing the handlers:
+///
+/// ```
+/// use tonic::{service::fault_injection, transport::Server, Code};
+///
+/// let faults = fault_injection(42)
+///     // fail 10% of the calls
+///     .fail_ratio("/routeguide.RouteGuide/GetFeature", 0.1, Code::Unavailable, "injected")
+///     // and the third call
+///     .fail_nth("/routeguide.RouteGuide/GetFeature", 3, Code::Internal, "injected");
+///
+/// let server = Server::builder().layer(faults);
+/// ```
+///
+/// The calls failed by a ratio are picked by a pseudo random generator
+/// starting from `seed`, so the same calls fail whenever they arrive in the
+/// same order. The state of the faults is shared by all services this layer
+/// is applied to, which makes the counts add up across connections.
+pub fn fault_injection(seed: u64) -> FaultInjectionLayer {
+    FaultInjectionLayer {
+        faults: Arc::new(Vec::new()),
+        state: Arc::new(Mutex::new(State {
+            rng: seed,
+            calls: HashMap::new(),
+        })),
+    }
+}
+
+/// A [`Layer`] injecting errors into calls, created by calling
+/// [`fault_injection`].
+#[derive(Debug, Clone)]
+pub struct FaultInjectionLayer {
+    faults: Arc<Vec<Fault>>,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Clone)]
+struct Fault {
+    path: String,
+    trigger: Trigger,
+    code: Code,
+    message: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Trigger {
+    Ratio(f64),
+    Nth(u64),
+}
+
+#[derive(Debug)]
+struct State {
+    rng: u64,
+    // the number of calls seen by each fault
+    calls: HashMap<usize, u64>,
+}
+
+impl FaultInjectionLayer {
+    /// Fail the share `ratio` of the calls to `path`, between `0.0` for none
+    /// and `1.0` for all of them.
+    pub fn fail_ratio(
+        self,
+        path: impl Into<String>,
+        ratio: f64,
+        code: Code,
+        message: impl Into<String>,
+    ) -> Self {
+        self.fail(path.into(), Trigger::Ratio(ratio), code, message.into())
+    }
+
+    /// Fail the `n`th call to `path`, counting from 1.
+    pub fn fail_nth(
+        self,
+        path: impl Into<String>,
+        n: u64,
+        code: Code,
+        message: impl Into<String>,
+    ) -> Self {
+        self.fail(path.into(), Trigger::Nth(n), code, message.into())
+    }
+
+    fn fail(mut self, path: String, trigger: Trigger, code: Code, message: String) -> Self {
+        Arc::make_mut(&mut self.faults).push(Fault {
+            path,
+            trigger,
+            code,
+            message,
+        });
+        self
+    }
+}
+
+impl<S> Layer<S> for FaultInjectionLayer {
+    type Service = FaultInjection<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        FaultInjection {
+            inner: service,
+            faults: self.faults.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// A service that fails some calls with injected errors before passing the
+/// others on.
+///
+/// See [`fault_injection`] for more details.
+#[derive(Clone)]
+pub struct FaultInjection<S> {
+    inner: S,
+    faults: Arc<Vec<Fault>>,
+    state: Arc<Mutex<State>>,
+}
+
+impl<S> FaultInjection<S> {
+    /// Returns the status to fail a call to `path` with, if any.
+    fn inject(&self, path: &str) -> Option<Status> {
+        let mut state = self.state.lock().unwrap();
+        let mut injected = None;
+
+        // every matching fault sees the call, so that counts and the random
+        // sequence do not depend on which fault fired
+        for (index, fault) in self.faults.iter().enumerate() {
+            if fault.path != path {
+                continue;
+            }
+
+            let calls = state.calls.entry(index).or_insert(0);
+            *calls += 1;
+
+            let fire = match fault.trigger {
+                Trigger::Nth(n) => *calls == n,
+                Trigger::Ratio(ratio) => state.next_f64() < ratio,
+            };
+
+            if fire && injected.is_none() {
+                injected = Some(Status::new(fault.code, fault.message.clone()));
+            }
+        }
+
+        injected
+    }
+}
+
+impl State {
+    /// The next number in `[0, 1)` from a splitmix64 generator.
+    fn next_f64(&mut self) -> f64 {
+        self.rng = self.rng.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl<S> fmt::Debug for FaultInjection<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FaultInjection")
+            .field("inner", &self.inner)
+            .field("faults", &self.faults)
+            .finish()
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for FaultInjection<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Error: Into<crate::Error>,
+{
+    type Response = http::Response<ResBody>;
+    type Error = crate::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        match self.inject(req.uri().path()) {
+            Some(status) => ResponseFuture::error(status),
+            None => ResponseFuture::future(self.inner.call(req)),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "transport"))]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    const PATH: &str = "/test.Test/Call";
+
+    async fn call<S>(svc: &mut S, path: &str) -> Result<(), Code>
+    where
+        S: Service<
+            http::Request<hyper::Body>,
+            Response = http::Response<hyper::Body>,
+            Error = crate::Error,
+        >,
+    {
+        let request = http::Request::builder()
+            .uri(path)
+            .body(hyper::Body::empty())
+            .unwrap();
+
+        match svc.ready().await.unwrap().call(request).await {
+            Ok(_) => Ok(()),
+            Err(error) => Err(error.downcast::<Status>().unwrap().code()),
+        }
+    }
+
+    fn service() -> impl Service<
+        http::Request<hyper::Body>,
+        Response = http::Response<hyper::Body>,
+        Error = hyper::Error,
+    > + Clone {
+        tower::service_fn(|_: http::Request<hyper::Body>| async move {
+            Ok::<_, hyper::Error>(hyper::Response::new(hyper::Body::empty()))
+        })
+    }
+
+    #[tokio::test]
+    async fn fails_nth_call() {
+        let layer = fault_injection(0).fail_nth(PATH, 3, Code::Unavailable, "injected");
+
+        // the count is shared by services created from the same layer
+        let mut first = layer.layer(service());
+        let mut second = layer.layer(service());
+
+        assert_eq!(call(&mut first, PATH).await, Ok(()));
+        assert_eq!(call(&mut second, "/test.Test/Other").await, Ok(()));
+        assert_eq!(call(&mut second, PATH).await, Ok(()));
+        assert_eq!(call(&mut first, PATH).await, Err(Code::Unavailable));
+        assert_eq!(call(&mut first, PATH).await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn fails_ratio_deterministically() {
+        async fn outcomes(seed: u64) -> Vec<bool> {
+            let layer = fault_injection(seed).fail_ratio(PATH, 0.3, Code::Unavailable, "injected");
+            let mut svc = layer.layer(service());
+
+            let mut outcomes = Vec::new();
+            for _ in 0..1000 {
+                outcomes.push(call(&mut svc, PATH).await.is_err());
+            }
+            outcomes
+        }
+
+        let first = outcomes(7).await;
+        assert_eq!(first, outcomes(7).await);
+        assert_ne!(first, outcomes(8).await);
+
+        let failed = first.iter().filter(|failed| **failed).count();
+        assert!((250..350).contains(&failed), "failed {} calls", failed);
+    }
+}
@@ -5,9 +5,11 @@
 use crate::{request::SanitizeHeaders, Status};
 use pin_project::pin_project;
 use std::{
+    collections::HashSet,
     fmt,
     future::Future,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 use tower_layer::Layer;
@@ -27,6 +29,11 @@ use tower_service::Service;
 ///
 /// See the [interceptor example][example] for more details.
 ///
+/// On the server a request cancelled with a `Status` is answered with a Trailers-Only response
+/// for just that stream. The connection and the other calls in flight on it are not affected, so
+/// returning `Status::unavailable` is a way to shed a single call, for example a method that is
+/// temporarily overloaded, while the client keeps its connection.
+///
 /// If you need more powerful middleware, [tower] is the recommended approach. You can find
 /// examples of how to use tower with tonic [here][tower-example].
 ///
@@ -59,7 +66,10 @@ pub fn interceptor<F>(f: F) -> InterceptorLayer<F>
 where
     F: Interceptor,
 {
-    InterceptorLayer { f }
+    InterceptorLayer {
+        f,
+        skip: Arc::default(),
+    }
 }
 
 #[deprecated(
@@ -80,9 +90,27 @@ where
 /// created by calling [`interceptor`].
 ///
 /// See [`Interceptor`] for more details.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct InterceptorLayer<F> {
     f: F,
+    skip: Arc<HashSet<String>>,
+}
+
+impl<F> InterceptorLayer<F> {
+    /// Pass requests to the methods at `paths` to the inner service without
+    /// intercepting them.
+    ///
+    /// See [`InterceptedService::skip_methods`] for more details.
+    pub fn skip_methods<I>(self, paths: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Self {
+            skip: Arc::new(paths.into_iter().map(Into::into).collect()),
+            ..self
+        }
+    }
 }
 
 impl<S, F> Layer<S> for InterceptorLayer<F>
@@ -92,7 +120,11 @@ where
     type Service = InterceptedService<S, F>;
 
     fn layer(&self, service: S) -> Self::Service {
-        InterceptedService::new(service, self.f.clone())
+        InterceptedService {
+            inner: service,
+            f: self.f.clone(),
+            skip: self.skip.clone(),
+        }
     }
 }
 
@@ -109,10 +141,11 @@ pub type InterceptorFn<F> = InterceptorLayer<F>;
 /// A service wrapped in an interceptor middleware.
 ///
 /// See [`Interceptor`] for more details.
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct InterceptedService<S, F> {
     inner: S,
     f: F,
+    skip: Arc<HashSet<String>>,
 }
 
 impl<S, F> InterceptedService<S, F> {
@@ -122,7 +155,50 @@ impl<S, F> InterceptedService<S, F> {
     where
         F: Interceptor,
     {
-        Self { inner: service, f }
+        Self {
+            inner: service,
+            f,
+            skip: Arc::default(),
+        }
+    }
+
+    /// Pass requests to the methods at `paths` to the inner service without
+    /// intercepting them.
+    ///
+    /// This exempts some methods from an interceptor that every other method
+    /// of the service requires, for example to keep a method callable
+    /// without authentication:
+    ///
+    /// ```
+    /// use tonic::{service::interceptor::InterceptedService, Request, Status};
+    /// # fn example<S>(route_guide_server: S) {
+    ///
+    /// fn authenticate(request: Request<()>) -> Result<Request<()>, Status> {
+    ///     match request.metadata().get("authorization") {
+    ///         Some(token) if token == "Bearer secret" => Ok(request),
+    ///         _ => Err(Status::unauthenticated("missing or invalid token")),
+    ///     }
+    /// }
+    ///
+    /// let service = InterceptedService::new(route_guide_server, authenticate)
+    ///     .skip_methods(["/routeguide.RouteGuide/GetFeature"]);
+    /// # drop(service);
+    /// # }
+    /// ```
+    ///
+    /// With [`InterceptorLayer::skip_methods`] methods of any service can be
+    /// exempted from an interceptor applied to all services of a server,
+    /// like the `Check` method of the health service at
+    /// `/grpc.health.v1.Health/Check`.
+    pub fn skip_methods<I>(self, paths: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Self {
+            skip: Arc::new(paths.into_iter().map(Into::into).collect()),
+            ..self
+        }
     }
 }
 
@@ -134,6 +210,7 @@ where
         f.debug_struct("InterceptedService")
             .field("inner", &self.inner)
             .field("f", &format_args!("{}", std::any::type_name::<F>()))
+            .field("skip", &self.skip)
             .finish()
     }
 }
@@ -154,6 +231,10 @@ where
     }
 
     fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        if self.skip.contains(req.uri().path()) {
+            return ResponseFuture::future(self.inner.call(req));
+        }
+
         let uri = req.uri().clone();
         let req = crate::Request::from_http(req);
         let (metadata, extensions, msg) = req.into_parts();
@@ -182,7 +263,8 @@ where
     const NAME: &'static str = S::NAME;
 }
 
-/// Response future for [`InterceptedService`].
+/// Response future for [`InterceptedService`] and
+/// [`RewritePath`](super::rewrite_path::RewritePath).
 #[pin_project]
 #[derive(Debug)]
 pub struct ResponseFuture<F> {
@@ -191,13 +273,13 @@ pub struct ResponseFuture<F> {
 }
 
 impl<F> ResponseFuture<F> {
-    fn future(future: F) -> Self {
+    pub(crate) fn future(future: F) -> Self {
         Self {
             kind: Kind::Future(future),
         }
     }
 
-    fn error(status: Status) -> Self {
+    pub(crate) fn error(status: Status) -> Self {
         Self {
             kind: Kind::Error(Some(status)),
         }
@@ -229,7 +311,7 @@ where
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "transport"))]
 mod tests {
     #[allow(unused_imports)]
     use super::*;
@@ -267,4 +349,38 @@ mod tests {
 
         svc.oneshot(request).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn skips_methods() {
+        let svc = tower::service_fn(|_: http::Request<hyper::Body>| async move {
+            Ok::<_, hyper::Error>(hyper::Response::new(hyper::Body::empty()))
+        });
+
+        let mut svc = InterceptedService::new(svc, |_: crate::Request<()>| {
+            Err(Status::unauthenticated("no credentials"))
+        })
+        .skip_methods(vec![String::from("/test.Test/Public")]);
+
+        let request = |path| {
+            http::Request::builder()
+                .uri(path)
+                .body(hyper::Body::empty())
+                .unwrap()
+        };
+
+        let public = svc
+            .ready()
+            .await
+            .unwrap()
+            .call(request("/test.Test/Public"));
+        public.await.unwrap();
+
+        let private = svc
+            .ready()
+            .await
+            .unwrap()
+            .call(request("/test.Test/Private"));
+        let status = private.await.unwrap_err().downcast::<Status>().unwrap();
+        assert_eq!(status.code(), crate::Code::Unauthenticated);
+    }
 }
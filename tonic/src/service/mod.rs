@@ -1,7 +1,13 @@
 //! Utilities for using Tower services with Tonic.
 
+pub mod fault_injection;
 pub mod interceptor;
+pub mod rewrite_path;
 
+#[doc(inline)]
+pub use self::fault_injection::fault_injection;
 #[doc(inline)]
 #[allow(deprecated)]
 pub use self::interceptor::{interceptor, interceptor_fn, Interceptor};
+#[doc(inline)]
+pub use self::rewrite_path::rewrite_path;
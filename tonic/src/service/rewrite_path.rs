@@ -0,0 +1,203 @@
+//! Rewrite the method path of outgoing calls.
+//!
+//! See [`rewrite_path`] for more details.
+
+use crate::{service::interceptor::ResponseFuture, Status};
+use http::uri::{PathAndQuery, Uri};
+use std::{
+    fmt,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Create a layer that rewrites the method path of every call.
+///
+/// `f` receives the path a call would be sent to, for example
+/// `/routeguide.RouteGuide/GetFeature`, and returns the path it is sent to
+/// instead. This moves calls to a new version of a service without changing
+/// the call sites:
+///
+/// ```
+/// use tonic::{service::rewrite_path, transport::Channel};
+/// use tower::ServiceBuilder;
+/// # fn example(channel: Channel) {
+///
+/// let channel = ServiceBuilder::new()
+///     .layer(rewrite_path(|path: &str| match path.strip_prefix("/routeguide.RouteGuide/") {
+///         Some(method) => format!("/routeguide.v2.RouteGuide/{}", method),
+///         None => path.to_owned(),
+///     }))
+///     .service(channel);
+///
+/// // let client = RouteGuideClient::new(channel);
+/// # drop(channel);
+/// # }
+/// ```
+///
+/// The returned path must have the form `/{service}/{method}`, otherwise the
+/// call fails with an `INTERNAL` status without being sent.
+pub fn rewrite_path<F>(f: F) -> RewritePathLayer<F>
+where
+    F: Fn(&str) -> String,
+{
+    RewritePathLayer { f }
+}
+
+/// A [`Layer`] rewriting the method path of calls, created by calling
+/// [`rewrite_path`].
+#[derive(Debug, Clone, Copy)]
+pub struct RewritePathLayer<F> {
+    f: F,
+}
+
+impl<S, F> Layer<S> for RewritePathLayer<F>
+where
+    F: Fn(&str) -> String + Clone,
+{
+    type Service = RewritePath<S, F>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        RewritePath::new(service, self.f.clone())
+    }
+}
+
+/// A service that rewrites the method path of calls before passing them on.
+///
+/// See [`rewrite_path`] for more details.
+#[derive(Clone, Copy)]
+pub struct RewritePath<S, F> {
+    inner: S,
+    f: F,
+}
+
+impl<S, F> RewritePath<S, F> {
+    /// Create a new `RewritePath` that wraps `S` and rewrites the path of
+    /// each call with the function `F`.
+    pub fn new(service: S, f: F) -> Self
+    where
+        F: Fn(&str) -> String,
+    {
+        Self { inner: service, f }
+    }
+}
+
+impl<S, F> fmt::Debug for RewritePath<S, F>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RewritePath")
+            .field("inner", &self.inner)
+            .field("f", &format_args!("{}", std::any::type_name::<F>()))
+            .finish()
+    }
+}
+
+impl<S, F, ReqBody, ResBody> Service<http::Request<ReqBody>> for RewritePath<S, F>
+where
+    F: Fn(&str) -> String,
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Error: Into<crate::Error>,
+{
+    type Response = http::Response<ResBody>;
+    type Error = crate::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
+        let path = (self.f)(req.uri().path());
+
+        let path = match parse_method_path(&path) {
+            Some(path) => path,
+            None => {
+                return ResponseFuture::error(Status::internal(format!(
+                    "rewritten path `{}` is not a valid method path",
+                    path
+                )))
+            }
+        };
+
+        let mut parts = req.uri().clone().into_parts();
+        parts.path_and_query = Some(path);
+        *req.uri_mut() = Uri::from_parts(parts).expect("replacing the path keeps the uri valid");
+
+        ResponseFuture::future(self.inner.call(req))
+    }
+}
+
+/// Parses a path of the form `/{service}/{method}`.
+fn parse_method_path(path: &str) -> Option<PathAndQuery> {
+    let mut segments = path.strip_prefix('/')?.split('/');
+
+    match (segments.next(), segments.next(), segments.next()) {
+        (Some(service), Some(method), None) if !service.is_empty() && !method.is_empty() => {}
+        _ => return None,
+    }
+
+    let path = path.parse::<PathAndQuery>().ok()?;
+
+    if path.query().is_some() {
+        return None;
+    }
+
+    Some(path)
+}
+
+#[cfg(all(test, feature = "transport"))]
+mod tests {
+    use super::*;
+    use tower::ServiceExt;
+
+    fn versioned(path: &str) -> String {
+        path.replace("/test.Test/", "/test.v2.Test/")
+    }
+
+    #[tokio::test]
+    async fn rewrites_path() {
+        let svc = tower::service_fn(|request: http::Request<hyper::Body>| async move {
+            assert_eq!(request.uri(), "http://example.com/test.v2.Test/Call");
+
+            Ok::<_, hyper::Error>(hyper::Response::new(hyper::Body::empty()))
+        });
+
+        let request = http::Request::builder()
+            .uri("http://example.com/test.Test/Call")
+            .body(hyper::Body::empty())
+            .unwrap();
+
+        RewritePath::new(svc, versioned)
+            .oneshot(request)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_paths() {
+        for path in &[
+            "test.Test/Call",
+            "/test.Test",
+            "/test.Test/",
+            "/a/b/c",
+            "/a/b?c",
+            "/a b/c",
+        ] {
+            let svc = tower::service_fn(|_: http::Request<hyper::Body>| async move {
+                Ok::<_, hyper::Error>(hyper::Response::new(hyper::Body::empty()))
+            });
+
+            let path = path.to_string();
+            let error = RewritePath::new(svc, move |_: &str| path.clone())
+                .oneshot(http::Request::new(hyper::Body::empty()))
+                .await
+                .unwrap_err();
+
+            let status = error.downcast::<Status>().unwrap();
+            assert_eq!(status.code(), crate::Code::Internal);
+        }
+    }
+}
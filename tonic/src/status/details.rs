@@ -0,0 +1,353 @@
+//! The standard error details of the [rich error model].
+//!
+//! A `Status` carries its details in the `grpc-status-details-bin` header as
+//! an encoded `google.rpc.Status`, a list of detail messages that clients
+//! written in any language know how to read. The `with_*` methods of
+//! [`Status`] add the messages defined in [`error_details.proto`] to that
+//! list, and [`Status::detail`] reads them back:
+//!
+//! ```
+//! use tonic::{status::details::BadRequest, Status};
+//!
+//! let status = Status::invalid_argument("bad request")
+//!     .with_bad_request_violation("name", "must not be empty")
+//!     .with_bad_request_violation("age", "must be positive");
+//!
+//! let bad_request = status.detail::<BadRequest>().unwrap();
+//! assert_eq!(bad_request.field_violations.len(), 2);
+//! ```
+//!
+//! [rich error model]: https://cloud.google.com/apis/design/errors#error_model
+//! [`error_details.proto`]: https://github.com/googleapis/googleapis/blob/master/google/rpc/error_details.proto
+
+use super::Status;
+use bytes::Bytes;
+use prost1::Message;
+use prost_types::Any;
+use std::{collections::HashMap, time::Duration};
+
+/// A message of the standard error details.
+pub trait Detail: Message + Default {
+    /// The type url identifying this message in the details of a `Status`.
+    const TYPE_URL: &'static str;
+}
+
+macro_rules! detail {
+    ($ty:ident, $name:literal) => {
+        impl Detail for $ty {
+            const TYPE_URL: &'static str = concat!("type.googleapis.com/google.rpc.", $name);
+        }
+    };
+}
+
+/// Describes violations in a client request, focusing on the syntactic
+/// aspects of the request.
+#[derive(Clone, PartialEq, prost_derive::Message)]
+pub struct BadRequest {
+    /// Describes all violations in a client request.
+    #[prost(message, repeated, tag = "1")]
+    pub field_violations: Vec<FieldViolation>,
+}
+
+detail!(BadRequest, "BadRequest");
+
+/// A single bad request field of a [`BadRequest`].
+#[derive(Clone, PartialEq, prost_derive::Message)]
+pub struct FieldViolation {
+    /// A path leading to a field in the request body.
+    #[prost(string, tag = "1")]
+    pub field: String,
+    /// A description of why the request element is bad.
+    #[prost(string, tag = "2")]
+    pub description: String,
+}
+
+/// Describes how a quota check failed.
+#[derive(Clone, PartialEq, prost_derive::Message)]
+pub struct QuotaFailure {
+    /// Describes all quota violations.
+    #[prost(message, repeated, tag = "1")]
+    pub violations: Vec<QuotaViolation>,
+}
+
+detail!(QuotaFailure, "QuotaFailure");
+
+/// A single quota violation of a [`QuotaFailure`].
+#[derive(Clone, PartialEq, prost_derive::Message)]
+pub struct QuotaViolation {
+    /// The subject on which the quota check failed.
+    #[prost(string, tag = "1")]
+    pub subject: String,
+    /// A description of how the quota check failed.
+    #[prost(string, tag = "2")]
+    pub description: String,
+}
+
+/// Describes what preconditions have failed.
+#[derive(Clone, PartialEq, prost_derive::Message)]
+pub struct PreconditionFailure {
+    /// Describes all precondition violations.
+    #[prost(message, repeated, tag = "1")]
+    pub violations: Vec<PreconditionViolation>,
+}
+
+detail!(PreconditionFailure, "PreconditionFailure");
+
+/// A single precondition violation of a [`PreconditionFailure`].
+#[derive(Clone, PartialEq, prost_derive::Message)]
+pub struct PreconditionViolation {
+    /// The type of the precondition failure, for example `TOS`.
+    #[prost(string, tag = "1")]
+    pub r#type: String,
+    /// The subject, relative to the type, that failed.
+    #[prost(string, tag = "2")]
+    pub subject: String,
+    /// A description of how the precondition failed.
+    #[prost(string, tag = "3")]
+    pub description: String,
+}
+
+/// Describes the cause of the error with structured details.
+#[derive(Clone, PartialEq, prost_derive::Message)]
+pub struct ErrorInfo {
+    /// The reason of the error, a constant value that identifies the
+    /// proximate cause of the error.
+    #[prost(string, tag = "1")]
+    pub reason: String,
+    /// The logical grouping to which the reason belongs, typically the
+    /// registered service name of the tool or product that generated the
+    /// error.
+    #[prost(string, tag = "2")]
+    pub domain: String,
+    /// Additional structured details about this error.
+    #[prost(map = "string, string", tag = "3")]
+    pub metadata: HashMap<String, String>,
+}
+
+detail!(ErrorInfo, "ErrorInfo");
+
+/// Describes when the client can retry a failed request.
+#[derive(Clone, PartialEq, prost_derive::Message)]
+pub struct RetryInfo {
+    /// Clients should wait at least this long between retrying the same
+    /// request.
+    #[prost(message, optional, tag = "1")]
+    pub retry_delay: Option<prost_types::Duration>,
+}
+
+detail!(RetryInfo, "RetryInfo");
+
+/// Provides a localized error message that is safe to return to the user.
+#[derive(Clone, PartialEq, prost_derive::Message)]
+pub struct LocalizedMessage {
+    /// The locale used following the specification defined at
+    /// <http://www.rfc-editor.org/rfc/bcp/bcp47.txt>, for example `en-US`.
+    #[prost(string, tag = "1")]
+    pub locale: String,
+    /// The localized error message in the above locale.
+    #[prost(string, tag = "2")]
+    pub message: String,
+}
+
+detail!(LocalizedMessage, "LocalizedMessage");
+
+/// The `google.rpc.Status` carried in the `grpc-status-details-bin` header.
+#[derive(Clone, PartialEq, prost_derive::Message)]
+struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    code: i32,
+    #[prost(string, tag = "2")]
+    message: String,
+    #[prost(message, repeated, tag = "3")]
+    details: Vec<Any>,
+}
+
+/// Returns the detail messages of encoded `details`, if they are an encoded
+/// `google.rpc.Status`.
+#[cfg(feature = "serde")]
+pub(super) fn decode(details: &[u8]) -> Option<Vec<Any>> {
+    RpcStatus::decode(details).ok().map(|status| status.details)
+}
+
+impl Status {
+    /// Add a detail message to the details of this `Status`, replacing any
+    /// detail of the same type.
+    ///
+    /// The details are kept as an encoded `google.rpc.Status`. Details that
+    /// were set before and are not an encoded `google.rpc.Status` are
+    /// discarded.
+    pub fn with_detail<D: Detail>(self, detail: D) -> Status {
+        self.update_detail(|existing: &mut D| *existing = detail)
+    }
+
+    /// Returns the detail message of type `D` in the details of this
+    /// `Status`, if there is one.
+    pub fn detail<D: Detail>(&self) -> Option<D> {
+        let status = RpcStatus::decode(self.details()).ok()?;
+
+        status
+            .details
+            .iter()
+            .find(|any| any.type_url == D::TYPE_URL)
+            .and_then(|any| D::decode(&any.value[..]).ok())
+    }
+
+    /// Add a violation to the [`BadRequest`] detail of this `Status`.
+    pub fn with_bad_request_violation(
+        self,
+        field: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Status {
+        let violation = FieldViolation {
+            field: field.into(),
+            description: description.into(),
+        };
+
+        self.update_detail(|bad_request: &mut BadRequest| {
+            bad_request.field_violations.push(violation)
+        })
+    }
+
+    /// Add a violation to the [`QuotaFailure`] detail of this `Status`.
+    pub fn with_quota_failure_violation(
+        self,
+        subject: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Status {
+        let violation = QuotaViolation {
+            subject: subject.into(),
+            description: description.into(),
+        };
+
+        self.update_detail(|failure: &mut QuotaFailure| failure.violations.push(violation))
+    }
+
+    /// Add a violation to the [`PreconditionFailure`] detail of this `Status`.
+    pub fn with_precondition_failure_violation(
+        self,
+        r#type: impl Into<String>,
+        subject: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Status {
+        let violation = PreconditionViolation {
+            r#type: r#type.into(),
+            subject: subject.into(),
+            description: description.into(),
+        };
+
+        self.update_detail(|failure: &mut PreconditionFailure| failure.violations.push(violation))
+    }
+
+    /// Set the [`ErrorInfo`] detail of this `Status`.
+    pub fn with_error_info<K, V>(
+        self,
+        reason: impl Into<String>,
+        domain: impl Into<String>,
+        metadata: impl IntoIterator<Item = (K, V)>,
+    ) -> Status
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.with_detail(ErrorInfo {
+            reason: reason.into(),
+            domain: domain.into(),
+            metadata: metadata
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        })
+    }
+
+    /// Set the [`RetryInfo`] detail of this `Status`.
+    pub fn with_retry_info(self, retry_delay: Option<Duration>) -> Status {
+        self.with_detail(RetryInfo {
+            retry_delay: retry_delay.map(Into::into),
+        })
+    }
+
+    /// Set the [`LocalizedMessage`] detail of this `Status`.
+    pub fn with_localized_message(
+        self,
+        locale: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Status {
+        self.with_detail(LocalizedMessage {
+            locale: locale.into(),
+            message: message.into(),
+        })
+    }
+
+    fn update_detail<D: Detail>(mut self, f: impl FnOnce(&mut D)) -> Status {
+        let mut status = RpcStatus::decode(self.details()).unwrap_or_default();
+        status.code = self.code() as i32;
+        status.message = self.message().to_owned();
+
+        let index = match status
+            .details
+            .iter()
+            .position(|any| any.type_url == D::TYPE_URL)
+        {
+            Some(index) => index,
+            None => {
+                status.details.push(Any {
+                    type_url: D::TYPE_URL.to_owned(),
+                    value: Vec::new(),
+                });
+                status.details.len() - 1
+            }
+        };
+
+        let any = &mut status.details[index];
+        let mut detail = D::decode(&any.value[..]).unwrap_or_default();
+        f(&mut detail);
+        any.value = detail.encode_to_vec();
+
+        self.details = Bytes::from(status.encode_to_vec());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Code;
+
+    #[test]
+    fn packs_details() {
+        let status = Status::failed_precondition("not yet")
+            .with_precondition_failure_violation("TOS", "user", "not accepted")
+            .with_retry_info(Some(Duration::from_secs(2)))
+            .with_localized_message("en-US", "Please accept the terms first.")
+            .with_retry_info(Some(Duration::from_secs(3)));
+
+        let packed = RpcStatus::decode(status.details()).unwrap();
+        assert_eq!(packed.code, Code::FailedPrecondition as i32);
+        assert_eq!(packed.message, "not yet");
+        assert_eq!(packed.details.len(), 3);
+
+        let retry_info = status.detail::<RetryInfo>().unwrap();
+        assert_eq!(retry_info.retry_delay.unwrap().seconds, 3);
+
+        let failure = status.detail::<PreconditionFailure>().unwrap();
+        assert_eq!(failure.violations[0].r#type, "TOS");
+
+        assert!(status.detail::<BadRequest>().is_none());
+    }
+
+    #[test]
+    fn survives_header_round_trip() {
+        let status = Status::resource_exhausted("slow down")
+            .with_quota_failure_violation("project:1", "too many requests")
+            .with_error_info("RATE_LIMITED", "example.com", vec![("limit", "10")]);
+
+        let status = Status::from_header_map(&status.to_header_map().unwrap()).unwrap();
+
+        let info = status.detail::<ErrorInfo>().unwrap();
+        assert_eq!(info.reason, "RATE_LIMITED");
+        assert_eq!(info.metadata["limit"], "10");
+
+        let failure = status.detail::<QuotaFailure>().unwrap();
+        assert_eq!(failure.violations[0].subject, "project:1");
+    }
+}
@@ -1,7 +1,20 @@
+//! gRPC status codes and errors.
+//!
+//! See [`Status`] for more details.
+
+#[cfg(feature = "prost")]
+#[cfg_attr(docsrs, doc(cfg(feature = "prost")))]
+pub mod details;
+#[cfg(feature = "serde")]
+mod serialize;
+
 use crate::body::BoxBody;
 use crate::metadata::MetadataMap;
 use bytes::Bytes;
-use http::header::{HeaderMap, HeaderValue};
+use http::{
+    header::{HeaderMap, HeaderValue},
+    Uri,
+};
 use percent_encoding::{percent_decode, percent_encode, AsciiSet, CONTROLS};
 use std::{borrow::Cow, error::Error, fmt};
 use tracing::{debug, trace, warn};
@@ -20,6 +33,7 @@ const ENCODING_SET: &AsciiSet = &CONTROLS
 const GRPC_STATUS_HEADER_CODE: &str = "grpc-status";
 const GRPC_STATUS_MESSAGE_HEADER: &str = "grpc-message";
 const GRPC_STATUS_DETAILS_HEADER: &str = "grpc-status-details-bin";
+const REDIRECT_KEY: &str = "tonic-redirect";
 
 /// A gRPC status describing the result of an RPC call.
 ///
@@ -292,6 +306,25 @@ impl Status {
         Status::new(Code::Unavailable, message)
     }
 
+    /// The service is unavailable here, but the call may be retried at
+    /// `endpoint`.
+    ///
+    /// This is an `UNAVAILABLE` status that names the alternate endpoint in
+    /// the `tonic-redirect` metadata entry. Clients using the `Redirect`
+    /// service from `tonic::transport::channel` retry the call there, other
+    /// clients treat it like any other `UNAVAILABLE` status.
+    pub fn redirect(message: impl Into<String>, endpoint: &Uri) -> Status {
+        let mut status = Status::unavailable(message);
+        status.metadata.insert(
+            REDIRECT_KEY,
+            endpoint
+                .to_string()
+                .parse()
+                .expect("a uri is a valid metadata value"),
+        );
+        status
+    }
+
     /// Unrecoverable data loss or corruption.
     pub fn data_loss(message: impl Into<String>) -> Status {
         Status::new(Code::DataLoss, message)
@@ -346,6 +379,7 @@ impl Status {
             | Some(h2::Reason::SETTINGS_TIMEOUT)
             | Some(h2::Reason::COMPRESSION_ERROR)
             | Some(h2::Reason::CONNECT_ERROR) => Code::Internal,
+            // the peer did not process the stream, so it is safe to retry
             Some(h2::Reason::REFUSED_STREAM) => Code::Unavailable,
             Some(h2::Reason::CANCEL) => Code::Cancelled,
             Some(h2::Reason::ENHANCE_YOUR_CALM) => Code::ResourceExhausted,
@@ -456,6 +490,16 @@ impl Status {
         &mut self.metadata
     }
 
+    /// Get the endpoint to retry the call at, if this is a
+    /// [redirect](Status::redirect).
+    pub fn redirect_endpoint(&self) -> Option<Uri> {
+        if self.code != Code::Unavailable {
+            return None;
+        }
+
+        self.metadata.get(REDIRECT_KEY)?.to_str().ok()?.parse().ok()
+    }
+
     pub(crate) fn to_header_map(&self) -> Result<HeaderMap, Self> {
         let mut header_map = HeaderMap::with_capacity(3 + self.metadata.len());
         self.add_header(&mut header_map)?;
@@ -491,6 +535,9 @@ impl Status {
     }
 
     /// Create a new `Status` with the associated code, message, and binary details field.
+    ///
+    /// The details are sent in the `grpc-status-details-bin` header, both when a unary call
+    /// fails and when a streaming response ends with this status in its trailers.
     pub fn with_details(code: Code, message: impl Into<String>, details: Bytes) -> Status {
         Self::with_details_and_metadata(code, message, details, MetadataMap::new())
     }
@@ -530,6 +577,63 @@ impl Status {
 
         http::Response::from_parts(parts, crate::body::empty_body())
     }
+
+    /// Build a plain HTTP response carrying this `Status`, for gateways
+    /// answering HTTP requests on behalf of a gRPC service.
+    ///
+    /// Unlike [`to_http`](Status::to_http), which builds a gRPC response, the
+    /// HTTP status of the response is mapped from the code, for example to
+    /// `404 Not Found` for [`Code::NotFound`]. The `grpc-status`,
+    /// `grpc-message` and `grpc-status-details-bin` headers and the metadata
+    /// are set like in a gRPC response, so [`Status::from_http_parts`] can
+    /// restore the status. The body is left to the caller:
+    ///
+    /// ```
+    /// # use tonic::Status;
+    /// let response = Status::not_found("no such user")
+    ///     .to_http_response()
+    ///     .map(|()| "{\"error\":\"no such user\"}");
+    ///
+    /// assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    /// ```
+    pub fn to_http_response(&self) -> http::Response<()> {
+        let (mut parts, ()) = http::Response::new(()).into_parts();
+
+        parts.status = self.code.to_http_status();
+        self.add_header(&mut parts.headers).unwrap();
+
+        http::Response::from_parts(parts, ())
+    }
+
+    /// Extract a `Status` from the head of an HTTP response, like one built
+    /// by [`Status::to_http_response`].
+    ///
+    /// The status is read from the `grpc-status` headers if present.
+    /// Otherwise its code is mapped from the HTTP status as described in
+    /// [HTTP to gRPC Status Code Mapping], with any successful HTTP status
+    /// mapped to [`Code::Ok`].
+    ///
+    /// [HTTP to gRPC Status Code Mapping]: https://github.com/grpc/grpc/blob/master/doc/http-grpc-status-mapping.md
+    pub fn from_http_parts(parts: &http::response::Parts) -> Status {
+        if let Some(status) = Status::from_header_map(&parts.headers) {
+            return status;
+        }
+
+        let code = if parts.status.is_success() {
+            Code::Ok
+        } else {
+            Code::from_http_status(parts.status)
+        };
+
+        Status::with_metadata(
+            code,
+            format!(
+                "grpc-status header missing, mapped from HTTP status code {}",
+                parts.status.as_u16(),
+            ),
+            MetadataMap::from_headers(parts.headers.clone()),
+        )
+    }
 }
 
 fn find_status_in_source_chain(err: &(dyn Error + 'static)) -> Option<Status> {
@@ -553,6 +657,13 @@ fn find_status_in_source_chain(err: &(dyn Error + 'static)) -> Option<Status> {
             return Some(Status::cancelled(timeout.to_string()));
         }
 
+        // streams reset by the peer are usually reported through a
+        // `hyper::Error` wrapping the `h2::Error`
+        #[cfg(feature = "transport")]
+        if let Some(h2) = err.downcast_ref::<h2::Error>() {
+            return Some(Status::from_h2_error(h2));
+        }
+
         source = err.source();
     }
 
@@ -669,15 +780,6 @@ pub(crate) fn infer_grpc_status(
     }
     trace!("trailers missing grpc-status");
     let code = match status_code {
-        // Borrowed from https://github.com/grpc/grpc/blob/master/doc/http-grpc-status-mapping.md
-        http::StatusCode::BAD_REQUEST => Code::Internal,
-        http::StatusCode::UNAUTHORIZED => Code::Unauthenticated,
-        http::StatusCode::FORBIDDEN => Code::PermissionDenied,
-        http::StatusCode::NOT_FOUND => Code::Unimplemented,
-        http::StatusCode::TOO_MANY_REQUESTS
-        | http::StatusCode::BAD_GATEWAY
-        | http::StatusCode::SERVICE_UNAVAILABLE
-        | http::StatusCode::GATEWAY_TIMEOUT => Code::Unavailable,
         // We got a 200 but no trailers, we can infer that this request is finished.
         //
         // This can happen when a streaming response sends two Status but
@@ -685,7 +787,7 @@ pub(crate) fn infer_grpc_status(
         //
         // https://github.com/hyperium/tonic/issues/681
         http::StatusCode::OK => return Err(None),
-        _ => Code::Unknown,
+        status_code => Code::from_http_status(status_code),
     };
 
     let msg = format!(
@@ -699,6 +801,43 @@ pub(crate) fn infer_grpc_status(
 // ===== impl Code =====
 
 impl Code {
+    fn from_http_status(status_code: http::StatusCode) -> Code {
+        match status_code {
+            // Borrowed from https://github.com/grpc/grpc/blob/master/doc/http-grpc-status-mapping.md
+            http::StatusCode::BAD_REQUEST => Code::Internal,
+            http::StatusCode::UNAUTHORIZED => Code::Unauthenticated,
+            http::StatusCode::FORBIDDEN => Code::PermissionDenied,
+            http::StatusCode::NOT_FOUND => Code::Unimplemented,
+            http::StatusCode::TOO_MANY_REQUESTS
+            | http::StatusCode::BAD_GATEWAY
+            | http::StatusCode::SERVICE_UNAVAILABLE
+            | http::StatusCode::GATEWAY_TIMEOUT => Code::Unavailable,
+            _ => Code::Unknown,
+        }
+    }
+
+    fn to_http_status(self) -> http::StatusCode {
+        // The mapping of https://cloud.google.com/apis/design/errors#handling_errors
+        match self {
+            Code::Ok => http::StatusCode::OK,
+            Code::Cancelled => http::StatusCode::from_u16(499).unwrap(),
+            Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => {
+                http::StatusCode::BAD_REQUEST
+            }
+            Code::DeadlineExceeded => http::StatusCode::GATEWAY_TIMEOUT,
+            Code::NotFound => http::StatusCode::NOT_FOUND,
+            Code::AlreadyExists | Code::Aborted => http::StatusCode::CONFLICT,
+            Code::PermissionDenied => http::StatusCode::FORBIDDEN,
+            Code::Unauthenticated => http::StatusCode::UNAUTHORIZED,
+            Code::ResourceExhausted => http::StatusCode::TOO_MANY_REQUESTS,
+            Code::Unimplemented => http::StatusCode::NOT_IMPLEMENTED,
+            Code::Unavailable => http::StatusCode::SERVICE_UNAVAILABLE,
+            Code::Unknown | Code::Internal | Code::DataLoss => {
+                http::StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
     /// Get the `Code` that represents the integer, if known.
     ///
     /// If not known, returns `Code::Unknown` (surprise!).
@@ -863,6 +1002,23 @@ mod tests {
         assert_eq!(source.reason(), Some(h2::Reason::CANCEL));
     }
 
+    #[test]
+    #[cfg(feature = "transport")]
+    fn from_error_nested_h2() {
+        use std::error::Error as _;
+
+        let orig = Nested(Box::new(h2::Error::from(h2::Reason::REFUSED_STREAM)));
+        let found = Status::from_error(Box::new(orig));
+
+        assert_eq!(found.code(), Code::Unavailable);
+
+        let source = found
+            .source()
+            .and_then(|err| err.downcast_ref::<h2::Error>())
+            .unwrap();
+        assert_eq!(source.reason(), Some(h2::Reason::REFUSED_STREAM));
+    }
+
     #[test]
     #[cfg(feature = "transport")]
     fn to_h2_error() {
@@ -933,4 +1089,35 @@ mod tests {
 
         assert_eq!(status.details(), DETAILS);
     }
+
+    #[test]
+    fn http_response_round_trip() {
+        let status = Status::with_details(Code::NotFound, "no such user", Bytes::from_static(&[1]));
+
+        let (parts, ()) = status.to_http_response().into_parts();
+        assert_eq!(parts.status, http::StatusCode::NOT_FOUND);
+
+        let status = Status::from_http_parts(&parts);
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "no such user");
+        assert_eq!(status.details(), &[1]);
+    }
+
+    #[test]
+    fn from_http_parts_maps_http_status() {
+        let parts = |status| {
+            let mut response = http::Response::new(());
+            *response.status_mut() = status;
+            response.into_parts().0
+        };
+
+        for &(http_status, code) in &[
+            (http::StatusCode::NO_CONTENT, Code::Ok),
+            (http::StatusCode::UNAUTHORIZED, Code::Unauthenticated),
+            (http::StatusCode::SERVICE_UNAVAILABLE, Code::Unavailable),
+            (http::StatusCode::IM_A_TEAPOT, Code::Unknown),
+        ] {
+            assert_eq!(Status::from_http_parts(&parts(http_status)).code(), code);
+        }
+    }
 }
@@ -0,0 +1,137 @@
+//! A structured form of `Status` for logs.
+
+use super::{Code, Status};
+use serde::ser::{Serialize, SerializeSeq, SerializeStruct, Serializer};
+
+/// Serializes the code by number and name, the message, and the details.
+///
+/// With the `prost` feature the details are the list of detail messages, by
+/// type url and base64 encoded value. Otherwise, or if the details are not
+/// an encoded `google.rpc.Status`, they are a single entry without a type
+/// url holding the raw details. This is only meant for logs, the wire
+/// representation of a `Status` is not affected:
+///
+/// ```
+/// use tonic::Status;
+///
+/// let status = Status::not_found("no such feature");
+///
+/// assert_eq!(
+///     serde_json::to_string(&status).unwrap(),
+///     r#"{"code":5,"code_name":"NOT_FOUND","message":"no such feature","details":[]}"#,
+/// );
+/// ```
+impl Serialize for Status {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut status = serializer.serialize_struct("Status", 4)?;
+        status.serialize_field("code", &(self.code() as i32))?;
+        status.serialize_field("code_name", code_name(self.code()))?;
+        status.serialize_field("message", self.message())?;
+        status.serialize_field("details", &Details(self.details()))?;
+        status.end()
+    }
+}
+
+struct Details<'a>(&'a [u8]);
+
+impl Serialize for Details<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[cfg(feature = "prost")]
+        {
+            if let Some(details) = super::details::decode(self.0) {
+                let mut seq = serializer.serialize_seq(Some(details.len()))?;
+                for detail in &details {
+                    seq.serialize_element(&Detail {
+                        type_url: Some(&detail.type_url),
+                        value: &detail.value,
+                    })?;
+                }
+                return seq.end();
+            }
+        }
+
+        if self.0.is_empty() {
+            return serializer.serialize_seq(Some(0))?.end();
+        }
+
+        let mut seq = serializer.serialize_seq(Some(1))?;
+        seq.serialize_element(&Detail {
+            type_url: None,
+            value: self.0,
+        })?;
+        seq.end()
+    }
+}
+
+struct Detail<'a> {
+    type_url: Option<&'a str>,
+    value: &'a [u8],
+}
+
+impl Serialize for Detail<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut detail = serializer.serialize_struct("Detail", 2)?;
+        match self.type_url {
+            Some(type_url) => detail.serialize_field("type_url", type_url)?,
+            None => detail.skip_field("type_url")?,
+        }
+        detail.serialize_field("value", &base64::encode(self.value))?;
+        detail.end()
+    }
+}
+
+/// The name of `code` as used by the gRPC specification.
+fn code_name(code: Code) -> &'static str {
+    match code {
+        Code::Ok => "OK",
+        Code::Cancelled => "CANCELLED",
+        Code::Unknown => "UNKNOWN",
+        Code::InvalidArgument => "INVALID_ARGUMENT",
+        Code::DeadlineExceeded => "DEADLINE_EXCEEDED",
+        Code::NotFound => "NOT_FOUND",
+        Code::AlreadyExists => "ALREADY_EXISTS",
+        Code::PermissionDenied => "PERMISSION_DENIED",
+        Code::ResourceExhausted => "RESOURCE_EXHAUSTED",
+        Code::FailedPrecondition => "FAILED_PRECONDITION",
+        Code::Aborted => "ABORTED",
+        Code::OutOfRange => "OUT_OF_RANGE",
+        Code::Unimplemented => "UNIMPLEMENTED",
+        Code::Internal => "INTERNAL",
+        Code::Unavailable => "UNAVAILABLE",
+        Code::DataLoss => "DATA_LOSS",
+        Code::Unauthenticated => "UNAUTHENTICATED",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+
+    #[cfg(feature = "prost")]
+    #[test]
+    fn serializes_detail_messages() {
+        let status = Status::invalid_argument("bad request")
+            .with_bad_request_violation("name", "must not be empty");
+
+        let json = serde_json::to_value(&status).unwrap();
+
+        assert_eq!(json["code"], 3);
+        assert_eq!(json["code_name"], "INVALID_ARGUMENT");
+        assert_eq!(
+            json["details"][0]["type_url"],
+            "type.googleapis.com/google.rpc.BadRequest"
+        );
+        assert!(json["details"][0]["value"].is_string());
+    }
+
+    #[test]
+    fn serializes_raw_details() {
+        // not an encoded `google.rpc.Status`
+        let status = Status::with_details(Code::Internal, "oops", Bytes::from_static(&[0xff]));
+
+        let json = serde_json::to_value(&status).unwrap();
+
+        assert_eq!(json["details"], serde_json::json!([{ "value": "/w==" }]));
+    }
+}
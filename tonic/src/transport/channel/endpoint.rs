@@ -1,4 +1,4 @@
-use super::super::service;
+use super::super::service::{self, ConnectTimings, TimedResolver};
 use super::Channel;
 #[cfg(feature = "tls")]
 use super::ClientTlsConfig;
@@ -10,10 +10,14 @@ use http::{
     uri::{InvalidUri, Uri},
     HeaderValue,
 };
+use hyper::client::connect::{dns::GaiResolver, HttpConnector};
 use std::{
+    collections::HashMap,
     convert::{TryFrom, TryInto},
     fmt,
+    net::SocketAddr,
     str::FromStr,
+    sync::Arc,
     time::Duration,
 };
 use tower::make::MakeConnection;
@@ -25,6 +29,7 @@ use tower::make::MakeConnection;
 pub struct Endpoint {
     pub(crate) uri: Uri,
     pub(crate) user_agent: Option<HeaderValue>,
+    pub(crate) path_prefix: Option<Arc<str>>,
     pub(crate) timeout: Option<Duration>,
     pub(crate) concurrency_limit: Option<usize>,
     pub(crate) rate_limit: Option<(u64, Duration)>,
@@ -35,11 +40,15 @@ pub struct Endpoint {
     pub(crate) init_connection_window_size: Option<u32>,
     pub(crate) tcp_keepalive: Option<Duration>,
     pub(crate) tcp_nodelay: bool,
+    pub(crate) tos: Option<u8>,
     pub(crate) http2_keep_alive_interval: Option<Duration>,
     pub(crate) http2_keep_alive_timeout: Option<Duration>,
     pub(crate) http2_keep_alive_while_idle: Option<bool>,
     pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) idle_connection_timeout: Option<Duration>,
     pub(crate) http2_adaptive_window: Option<bool>,
+    pub(crate) resolver_overrides: Option<Arc<HashMap<String, SocketAddr>>>,
+    pub(crate) grpc_proto_content_type: bool,
 }
 
 impl Endpoint {
@@ -105,6 +114,50 @@ impl Endpoint {
             .map_err(|_| Error::new_invalid_user_agent())
     }
 
+    /// Prepend `prefix` to the path of every call.
+    ///
+    /// This reaches servers that serve their services under a prefix, like
+    /// a [`Server`] configured with the same [`path_prefix`], or that sit
+    /// behind an ingress routing on it. A call to `/package.Service/Method`
+    /// is sent to `/v1/package.Service/Method`.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.path_prefix("/v1").expect("/v1 should be a valid path prefix");
+    /// ```
+    ///
+    /// `prefix` must be a valid URI path without a query or a fragment or
+    /// building the endpoint will fail.
+    ///
+    /// [`Server`]: crate::transport::Server
+    /// [`path_prefix`]: crate::transport::Server::path_prefix
+    pub fn path_prefix(self, prefix: &str) -> Result<Self, Error> {
+        service::path_prefix::normalize(prefix).map(|path_prefix| Endpoint {
+            path_prefix,
+            ..self
+        })
+    }
+
+    /// Send requests with the `application/grpc+proto` content-type.
+    ///
+    /// Some gateways route on the exact subtype and require `+proto`. By
+    /// default requests are sent with the bare `application/grpc`, which
+    /// means the same. Requests of codecs that set their own subtype are
+    /// left alone.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.grpc_proto_content_type(true);
+    /// ```
+    pub fn grpc_proto_content_type(self, enabled: bool) -> Self {
+        Endpoint {
+            grpc_proto_content_type: enabled,
+            ..self
+        }
+    }
+
     /// Apply a timeout to each request.
     ///
     /// ```
@@ -137,6 +190,31 @@ impl Endpoint {
         }
     }
 
+    /// Close the connection once no call was made on it for `dur`.
+    ///
+    /// The next call reopens the connection, so bursty clients do not keep
+    /// connections open between bursts. A call made with a tonic client keeps
+    /// the connection open until its response body ends or is dropped, so a
+    /// long streaming response does not count as idle. A response read
+    /// through the `Channel` directly only counts until its headers were
+    /// received. With a balanced channel this applies to the connection of
+    /// each endpoint.
+    ///
+    /// Defaults to keeping connections open.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// # use std::time::Duration;
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.idle_connection_timeout(Duration::from_secs(60));
+    /// ```
+    pub fn idle_connection_timeout(self, dur: Duration) -> Self {
+        Endpoint {
+            idle_connection_timeout: Some(dur),
+            ..self
+        }
+    }
+
     /// Set whether TCP keepalive messages are enabled on accepted connections.
     ///
     /// If `None` is specified, keepalive is disabled, otherwise the duration
@@ -226,6 +304,27 @@ impl Endpoint {
         }
     }
 
+    /// Set the `IP_TOS` option of new connections, or `IPV6_TCLASS` for IPv6
+    /// connections, which carries the DSCP and ECN bits used to prioritize
+    /// traffic on managed networks.
+    ///
+    /// This is supported on Unix platforms and Windows, though not every
+    /// platform supports both options. A warning is logged when the option
+    /// cannot be set.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// // DSCP class EF (expedited forwarding)
+    /// builder.tos(46 << 2);
+    /// ```
+    pub fn tos(self, tos: u8) -> Self {
+        Endpoint {
+            tos: Some(tos),
+            ..self
+        }
+    }
+
     /// Set http2 KEEP_ALIVE_INTERVAL. Uses `hyper`'s default otherwise.
     pub fn http2_keep_alive_interval(self, interval: Duration) -> Self {
         Endpoint {
@@ -258,12 +357,35 @@ impl Endpoint {
         }
     }
 
+    /// Override name resolution for the given hosts.
+    ///
+    /// Connections to a host found in `overrides` are made to the mapped
+    /// address instead of resolving the host through DNS. The endpoint uri is
+    /// still used for the `:authority` header and the TLS server name, which
+    /// makes hostname-dependent setups testable against local servers.
+    ///
+    /// This only applies to the built-in connector and is ignored by
+    /// [`connect_with_connector`](Endpoint::connect_with_connector).
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// # use std::collections::HashMap;
+    /// let mut overrides = HashMap::new();
+    /// overrides.insert("routeguide.test".to_string(), "127.0.0.1:50051".parse().unwrap());
+    ///
+    /// Endpoint::from_static("http://routeguide.test").with_resolver_overrides(overrides);
+    /// ```
+    pub fn with_resolver_overrides(self, overrides: HashMap<String, SocketAddr>) -> Self {
+        Endpoint {
+            resolver_overrides: Some(Arc::new(overrides)),
+            ..self
+        }
+    }
+
     /// Create a channel from this config.
     pub async fn connect(&self) -> Result<Channel, Error> {
-        let mut http = hyper::client::connect::HttpConnector::new();
-        http.enforce_http(false);
-        http.set_nodelay(self.tcp_nodelay);
-        http.set_keepalive(self.tcp_keepalive);
+        let timings = ConnectTimings::default();
+        let http = self.http_connector(timings.clone());
 
         #[cfg(feature = "tls")]
         let connector = service::connector(http, self.tls.clone());
@@ -274,9 +396,9 @@ impl Endpoint {
         if let Some(connect_timeout) = self.connect_timeout {
             let mut connector = hyper_timeout::TimeoutConnector::new(connector);
             connector.set_connect_timeout(Some(connect_timeout));
-            Channel::connect(connector, self.clone()).await
+            Channel::connect(connector, self.clone(), timings).await
         } else {
-            Channel::connect(connector, self.clone()).await
+            Channel::connect(connector, self.clone(), timings).await
         }
     }
 
@@ -285,10 +407,8 @@ impl Endpoint {
     /// The channel returned by this method does not attempt to connect to the endpoint until first
     /// use.
     pub fn connect_lazy(&self) -> Result<Channel, Error> {
-        let mut http = hyper::client::connect::HttpConnector::new();
-        http.enforce_http(false);
-        http.set_nodelay(self.tcp_nodelay);
-        http.set_keepalive(self.tcp_keepalive);
+        let timings = ConnectTimings::default();
+        let http = self.http_connector(timings.clone());
 
         #[cfg(feature = "tls")]
         let connector = service::connector(http, self.tls.clone());
@@ -299,9 +419,9 @@ impl Endpoint {
         if let Some(connect_timeout) = self.connect_timeout {
             let mut connector = hyper_timeout::TimeoutConnector::new(connector);
             connector.set_connect_timeout(Some(connect_timeout));
-            Ok(Channel::new(connector, self.clone()))
+            Ok(Channel::new(connector, self.clone(), timings))
         } else {
-            Ok(Channel::new(connector, self.clone()))
+            Ok(Channel::new(connector, self.clone(), timings))
         }
     }
 
@@ -319,6 +439,8 @@ impl Endpoint {
         C::Future: Send + 'static,
         crate::Error: From<C::Error> + Send + 'static,
     {
+        let timings = ConnectTimings::default();
+
         #[cfg(feature = "tls")]
         let connector = service::connector(connector, self.tls.clone());
 
@@ -328,9 +450,9 @@ impl Endpoint {
         if let Some(connect_timeout) = self.connect_timeout {
             let mut connector = hyper_timeout::TimeoutConnector::new(connector);
             connector.set_connect_timeout(Some(connect_timeout));
-            Channel::connect(connector, self.clone()).await
+            Channel::connect(connector, self.clone(), timings).await
         } else {
-            Channel::connect(connector, self.clone()).await
+            Channel::connect(connector, self.clone(), timings).await
         }
     }
 
@@ -348,13 +470,21 @@ impl Endpoint {
         C::Future: Send + 'static,
         crate::Error: From<C::Error> + Send + 'static,
     {
-        #[cfg(feature = "tls")]
-        let connector = service::connector(connector, self.tls.clone());
-
-        #[cfg(not(feature = "tls"))]
-        let connector = service::connector(connector);
+        Ok(Channel::with_connector(self.clone(), connector))
+    }
 
-        Ok(Channel::new(connector, self.clone()))
+    pub(crate) fn http_connector(
+        &self,
+        timings: ConnectTimings,
+    ) -> service::SetTos<service::ResolverOverrides<HttpConnector<TimedResolver<GaiResolver>>>>
+    {
+        let resolver = TimedResolver::new(GaiResolver::new(), timings);
+        let mut http = HttpConnector::new_with_resolver(resolver);
+        http.enforce_http(false);
+        http.set_nodelay(self.tcp_nodelay);
+        http.set_keepalive(self.tcp_keepalive);
+        let http = service::ResolverOverrides::new(http, self.resolver_overrides.clone());
+        service::SetTos::new(http, self.tos)
     }
 
     /// Get the endpoint uri.
@@ -376,6 +506,7 @@ impl From<Uri> for Endpoint {
         Self {
             uri,
             user_agent: None,
+            path_prefix: None,
             concurrency_limit: None,
             rate_limit: None,
             timeout: None,
@@ -386,11 +517,15 @@ impl From<Uri> for Endpoint {
             init_connection_window_size: None,
             tcp_keepalive: None,
             tcp_nodelay: true,
+            tos: None,
             http2_keep_alive_interval: None,
             http2_keep_alive_timeout: None,
             http2_keep_alive_while_idle: None,
             connect_timeout: None,
+            idle_connection_timeout: None,
             http2_adaptive_window: None,
+            resolver_overrides: None,
+            grpc_proto_content_type: false,
         }
     }
 }
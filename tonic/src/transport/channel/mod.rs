@@ -1,21 +1,28 @@
 //! Client implementation and builder.
 
 mod endpoint;
+mod redirect;
 #[cfg(feature = "tls")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
 mod tls;
 
 pub use endpoint::Endpoint;
+pub use redirect::Redirect;
 #[cfg(feature = "tls")]
 pub use tls::ClientTlsConfig;
 
-use super::service::{Connection, DynamicServiceStream};
-use crate::body::BoxBody;
+use super::service::{
+    self, ConnectTimings, Connection, Dequeue, DynamicServiceStream, PendingRequests,
+    TimeoutExpired,
+};
+use crate::{body::BoxBody, Status};
 use bytes::Bytes;
 use http::{
+    header::{HeaderValue, CONTENT_TYPE, TE},
     uri::{InvalidUri, Uri},
     Request, Response,
 };
+use http_body::Body as _;
 use hyper::client::connect::Connection as HyperConnection;
 use std::{
     fmt,
@@ -23,6 +30,7 @@ use std::{
     hash::Hash,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use tokio::{
     io::{AsyncRead, AsyncWrite},
@@ -33,14 +41,21 @@ use tower::balance::p2c::Balance;
 use tower::{
     buffer::{self, Buffer},
     discover::{Change, Discover},
+    make::MakeConnection,
     util::{BoxService, Either},
-    Service,
+    Service, ServiceExt,
 };
 
 type Svc = Either<Connection, BoxService<Request<BoxBody>, Response<hyper::Body>, crate::Error>>;
 
 const DEFAULT_BUFFER_SIZE: usize = 1024;
 
+// The request sent by `Channel::wait_until_ready`, a health check of the
+// whole server with an empty, uncompressed `HealthCheckRequest`.
+const READY_PROBE_PATH: &str = "/grpc.health.v1.Health/Check";
+const READY_PROBE_MESSAGE: &[u8] = &[0, 0, 0, 0, 0];
+const READY_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
 /// A default batteries included `transport` channel.
 ///
 /// This provides a fully featured http2 gRPC client based on [`hyper::Client`]
@@ -66,14 +81,16 @@ const DEFAULT_BUFFER_SIZE: usize = 1024;
 /// cloning the `Channel` type is cheap and encouraged.
 #[derive(Clone)]
 pub struct Channel {
-    svc: Buffer<Svc, Request<BoxBody>>,
+    svc: Buffer<Dequeue<Svc>, Request<BoxBody>>,
+    pending: PendingRequests,
+    max_pending: Option<usize>,
 }
 
 /// A future that resolves to an HTTP response.
 ///
 /// This is returned by the `Service::call` on [`Channel`].
 pub struct ResponseFuture {
-    inner: buffer::future::ResponseFuture<<Svc as Service<Request<BoxBody>>>::Future>,
+    inner: Option<buffer::future::ResponseFuture<<Svc as Service<Request<BoxBody>>>::Future>>,
 }
 
 impl Channel {
@@ -130,36 +147,165 @@ impl Channel {
         (Self::balance(list, DEFAULT_BUFFER_SIZE), tx)
     }
 
-    pub(crate) fn new<C>(connector: C, endpoint: Endpoint) -> Self
+    /// Create a channel that obtains its connections from `connector`.
+    ///
+    /// `connector` is called with the uri of `endpoint` whenever a new
+    /// connection is needed and returns the byte stream to run HTTP/2 and
+    /// gRPC on, for example a Unix socket, an established tunnel or an
+    /// in-memory pipe. TLS from the endpoint, if configured, is applied on
+    /// top of it. Like [`Endpoint::connect_lazy`] the channel does not
+    /// connect until first use.
+    ///
+    /// ```
+    /// # use tonic::transport::{Channel, Endpoint, Uri};
+    /// # use tower::service_fn;
+    /// # async fn example() {
+    /// let channel = Channel::with_connector(
+    ///     Endpoint::from_static("http://[::]:50051"),
+    ///     service_fn(|_: Uri| async {
+    ///         let (client, _server) = tokio::io::duplex(64 * 1024);
+    ///         Ok::<_, std::io::Error>(client)
+    ///     }),
+    /// );
+    /// # }
+    /// ```
+    pub fn with_connector<C>(endpoint: Endpoint, connector: C) -> Self
+    where
+        C: MakeConnection<Uri> + Send + 'static,
+        C::Connection: Unpin + Send + 'static,
+        C::Future: Send + 'static,
+        crate::Error: From<C::Error> + Send + 'static,
+    {
+        #[cfg(feature = "tls")]
+        let connector = service::connector(connector, endpoint.tls.clone());
+
+        #[cfg(not(feature = "tls"))]
+        let connector = service::connector(connector);
+
+        Channel::new(connector, endpoint, ConnectTimings::default())
+    }
+
+    /// Returns the number of requests waiting to be dispatched.
+    ///
+    /// Requests queue up in the channel's buffer while no connection, or for
+    /// a balanced channel no endpoint, is ready to accept them.
+    pub fn balance_queue_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Limit the number of requests waiting to be dispatched.
+    ///
+    /// Once `limit` requests are queued, further requests fail immediately
+    /// with `RESOURCE_EXHAUSTED` instead of waiting for a connection to
+    /// become ready. The limit is shared by all clones of this channel.
+    ///
+    /// ```
+    /// # use tonic::transport::{Channel, Endpoint};
+    /// # fn example(endpoints: Vec<Endpoint>) {
+    /// let channel = Channel::balance_list(endpoints.into_iter()).max_pending_requests(64);
+    /// # }
+    /// ```
+    pub fn max_pending_requests(self, limit: usize) -> Self {
+        Channel {
+            max_pending: Some(limit),
+            ..self
+        }
+    }
+
+    /// Wait until the server behind this channel can be reached.
+    ///
+    /// This connects to the server if the channel is not connected yet and
+    /// sends a `grpc.health.v1.Health/Check` request for the whole server.
+    /// Any response, including `UNIMPLEMENTED` from servers that do not host
+    /// the health service, means the server is reachable. Failed attempts are
+    /// retried until `timeout` elapses, after which an error is returned.
+    ///
+    /// To also wait for a service to report itself as serving, use the health
+    /// client from `tonic-health` once this resolves.
+    ///
+    /// ```no_run
+    /// # use tonic::transport::Channel;
+    /// # use std::time::Duration;
+    /// # async fn example() -> Result<(), tonic::transport::Error> {
+    /// let channel = Channel::from_static("http://[::1]:50051").connect_lazy()?;
+    ///
+    /// channel.wait_until_ready(Duration::from_secs(5)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn wait_until_ready(&self, timeout: Duration) -> Result<(), super::Error> {
+        let mut svc = self.clone();
+
+        let probe = async move {
+            loop {
+                let mut request = Request::builder()
+                    .method(http::Method::POST)
+                    .uri(READY_PROBE_PATH)
+                    .body(
+                        http_body::Full::new(Bytes::from_static(READY_PROBE_MESSAGE))
+                            .map_err(|err| match err {})
+                            .boxed(),
+                    )
+                    .expect("valid probe request");
+
+                request
+                    .headers_mut()
+                    .insert(TE, HeaderValue::from_static("trailers"));
+                request
+                    .headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+
+                match svc.ready().await {
+                    Ok(svc) => match svc.call(request).await {
+                        Ok(_) => return,
+                        Err(err) => tracing::debug!("channel not ready: {}", err),
+                    },
+                    Err(err) => tracing::debug!("channel not ready: {}", err),
+                }
+
+                tokio::time::sleep(READY_RETRY_INTERVAL).await;
+            }
+        };
+
+        tokio::time::timeout(timeout, probe)
+            .await
+            .map_err(|_| super::Error::from_source(TimeoutExpired::new()))
+    }
+
+    pub(crate) fn new<C>(connector: C, endpoint: Endpoint, timings: ConnectTimings) -> Self
     where
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::Error> + Send,
-        C::Future: Unpin + Send,
+        C::Future: Unpin + Send + 'static,
         C::Response: AsyncRead + AsyncWrite + HyperConnection + Unpin + Send + 'static,
     {
         let buffer_size = endpoint.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE);
 
-        let svc = Connection::lazy(connector, endpoint);
-        let svc = Buffer::new(Either::A(svc), buffer_size);
+        let svc = Connection::lazy(connector, endpoint, timings);
+        let svc = Buffer::new(Dequeue::new(Either::A(svc)), buffer_size);
 
-        Channel { svc }
+        Channel::from_buffer(svc)
     }
 
-    pub(crate) async fn connect<C>(connector: C, endpoint: Endpoint) -> Result<Self, super::Error>
+    pub(crate) async fn connect<C>(
+        connector: C,
+        endpoint: Endpoint,
+        timings: ConnectTimings,
+    ) -> Result<Self, super::Error>
     where
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::Error> + Send,
-        C::Future: Unpin + Send,
+        C::Future: Unpin + Send + 'static,
         C::Response: AsyncRead + AsyncWrite + HyperConnection + Unpin + Send + 'static,
     {
         let buffer_size = endpoint.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE);
 
-        let svc = Connection::connect(connector, endpoint)
+        let svc = Connection::connect(connector, endpoint, timings)
             .await
             .map_err(super::Error::from_source)?;
-        let svc = Buffer::new(Either::A(svc), buffer_size);
+        let svc = Buffer::new(Dequeue::new(Either::A(svc)), buffer_size);
 
-        Ok(Channel { svc })
+        Ok(Channel::from_buffer(svc))
     }
 
     pub(crate) fn balance<D>(discover: D, buffer_size: usize) -> Self
@@ -171,9 +317,17 @@ impl Channel {
         let svc = Balance::new(discover);
 
         let svc = BoxService::new(svc);
-        let svc = Buffer::new(Either::B(svc), buffer_size);
+        let svc = Buffer::new(Dequeue::new(Either::B(svc)), buffer_size);
 
-        Channel { svc }
+        Channel::from_buffer(svc)
+    }
+
+    fn from_buffer(svc: Buffer<Dequeue<Svc>, Request<BoxBody>>) -> Self {
+        Channel {
+            svc,
+            pending: PendingRequests::default(),
+            max_pending: None,
+        }
     }
 }
 
@@ -186,10 +340,18 @@ impl Service<http::Request<BoxBody>> for Channel {
         Service::poll_ready(&mut self.svc, cx).map_err(super::Error::from_source)
     }
 
-    fn call(&mut self, request: http::Request<BoxBody>) -> Self::Future {
+    fn call(&mut self, mut request: http::Request<BoxBody>) -> Self::Future {
+        if let Some(max) = self.max_pending {
+            if self.pending.len() >= max {
+                tracing::debug!("shedding request, {} requests pending", max);
+                return ResponseFuture { inner: None };
+            }
+        }
+
+        request.extensions_mut().insert(self.pending.enqueue());
         let inner = Service::call(&mut self.svc, request);
 
-        ResponseFuture { inner }
+        ResponseFuture { inner: Some(inner) }
     }
 }
 
@@ -197,8 +359,16 @@ impl Future for ResponseFuture {
     type Output = Result<Response<hyper::Body>, super::Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let val = futures_util::ready!(Pin::new(&mut self.inner).poll(cx))
-            .map_err(super::Error::from_source)?;
+        let inner = match self.inner.as_mut() {
+            Some(inner) => inner,
+            None => {
+                let status = Status::resource_exhausted("too many pending requests");
+                return Poll::Ready(Err(super::Error::from_source(status)));
+            }
+        };
+
+        let val =
+            futures_util::ready!(Pin::new(inner).poll(cx)).map_err(super::Error::from_source)?;
         Ok(val).into()
     }
 }
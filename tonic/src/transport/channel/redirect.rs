@@ -0,0 +1,264 @@
+use super::{Channel, Endpoint};
+use crate::{body::BoxBody, transport::BoxFuture, Status};
+use bytes::Bytes;
+use http::{request::Parts, Extensions, Request, Response, Uri};
+use http_body::Body as _;
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tower::{Service, ServiceExt};
+
+/// A channel that retries calls at the endpoint named by a
+/// [`Status::redirect`].
+///
+/// Calls are sent on the wrapped channel first. When the server rejects one
+/// with a redirect, the call is sent again to the endpoint it names, up to
+/// [`max_redirects`](Redirect::max_redirects) times. Channels to alternate
+/// endpoints are opened lazily, with the settings of the given [`Endpoint`],
+/// and reused for later redirects to the same endpoint, keeping up to
+/// [`max_cached_channels`](Redirect::max_cached_channels) of them.
+///
+/// To be able to send a call again its request body is read completely
+/// before the call is sent, so this is meant for unary and server streaming
+/// calls. Only redirects that reject a call before any response message was
+/// sent are followed.
+///
+/// The first attempt of a call is sent with the extensions of its request.
+/// Since extensions cannot be cloned in general, the calls sent again only
+/// carry copies of the extension types registered with
+/// [`copy_extension`](Redirect::copy_extension).
+///
+/// ```no_run
+/// # use tonic::transport::{channel::Redirect, Endpoint};
+/// # async fn example() -> Result<(), tonic::transport::Error> {
+/// let endpoint = Endpoint::from_static("http://primary.test");
+/// let channel = Redirect::new(endpoint.connect().await?, endpoint);
+/// // let client = GreeterClient::new(channel);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Redirect {
+    channel: Channel,
+    endpoint: Endpoint,
+    max_redirects: usize,
+    max_cached_channels: usize,
+    copy_extensions: Vec<CopyExtension>,
+    // the channels to alternate endpoints, the most recently used last
+    redirected: Arc<Mutex<VecDeque<(Uri, Channel)>>>,
+}
+
+type CopyExtension = Arc<dyn Fn(&Extensions, &mut Extensions) + Send + Sync>;
+
+impl Redirect {
+    /// Follow redirects of calls sent on `channel`, opening channels to
+    /// alternate endpoints with the settings of `endpoint`.
+    pub fn new(channel: Channel, endpoint: Endpoint) -> Self {
+        Redirect {
+            channel,
+            endpoint,
+            max_redirects: 1,
+            max_cached_channels: 8,
+            copy_extensions: Vec::new(),
+            redirected: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Set how many redirects of a single call are followed.
+    ///
+    /// Default is `1`.
+    pub fn max_redirects(self, max_redirects: usize) -> Self {
+        Redirect {
+            max_redirects,
+            ..self
+        }
+    }
+
+    /// Set how many channels to alternate endpoints are kept for later
+    /// redirects. When a channel to another endpoint is opened, the one
+    /// that was used the longest time ago is closed.
+    ///
+    /// Default is `8`.
+    pub fn max_cached_channels(self, max_cached_channels: usize) -> Self {
+        Redirect {
+            max_cached_channels,
+            ..self
+        }
+    }
+
+    /// Copy the extension of type `T` of a request to the calls sent again
+    /// when it is redirected.
+    pub fn copy_extension<T>(mut self) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.copy_extensions.push(Arc::new(|from, to| {
+            if let Some(extension) = from.get::<T>() {
+                to.insert(extension.clone());
+            }
+        }));
+        self
+    }
+
+    fn channel_to(&self, uri: Uri) -> Result<Channel, super::super::Error> {
+        let mut redirected = self.redirected.lock().unwrap();
+
+        if let Some(i) = redirected.iter().position(|(cached, _)| *cached == uri) {
+            let entry = redirected.remove(i).expect("position is in bounds");
+            let channel = entry.1.clone();
+            redirected.push_back(entry);
+            return Ok(channel);
+        }
+
+        let channel = Endpoint {
+            uri: uri.clone(),
+            ..self.endpoint.clone()
+        }
+        .connect_lazy()?;
+
+        while !redirected.is_empty() && redirected.len() >= self.max_cached_channels {
+            redirected.pop_front();
+        }
+        if self.max_cached_channels > 0 {
+            redirected.push_back((uri, channel.clone()));
+        }
+
+        Ok(channel)
+    }
+
+    fn copy_extensions(&self, from: &Extensions) -> Extensions {
+        let mut extensions = Extensions::new();
+        for copy in &self.copy_extensions {
+            copy(from, &mut extensions);
+        }
+        extensions
+    }
+}
+
+impl Service<Request<BoxBody>> for Redirect {
+    type Response = Response<hyper::Body>;
+    type Error = super::super::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.channel.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<BoxBody>) -> Self::Future {
+        // the clone is not ready, keep it and use the channel that is
+        let clone = self.channel.clone();
+        let mut channel = std::mem::replace(&mut self.channel, clone);
+        let redirect = self.clone();
+
+        Box::pin(async move {
+            let (mut parts, body) = request.into_parts();
+            let body = hyper::body::to_bytes(body)
+                .await
+                .map_err(super::super::Error::from_source)?;
+
+            let extensions = std::mem::take(&mut parts.extensions);
+            let copied = redirect.copy_extensions(&extensions);
+
+            let mut first = replay(&parts, &body);
+            *first.extensions_mut() = extensions;
+            let mut response = channel.call(first).await?;
+
+            for _ in 0..redirect.max_redirects {
+                let uri = match Status::from_header_map(response.headers())
+                    .and_then(|status| status.redirect_endpoint())
+                {
+                    Some(uri) => uri,
+                    None => break,
+                };
+
+                tracing::debug!("following redirect to {}", uri);
+
+                let mut request = replay(&parts, &body);
+                *request.extensions_mut() = redirect.copy_extensions(&copied);
+
+                let mut channel = redirect.channel_to(uri)?;
+                response = channel.ready().await?.call(request).await?;
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+fn replay(parts: &Parts, body: &Bytes) -> Request<BoxBody> {
+    let mut request = Request::new(
+        http_body::Full::new(body.clone())
+            .map_err(|err| match err {})
+            .boxed(),
+    );
+    *request.method_mut() = parts.method.clone();
+    *request.uri_mut() = parts.uri.clone();
+    *request.version_mut() = parts.version;
+    *request.headers_mut() = parts.headers.clone();
+    request
+}
+
+impl fmt::Debug for Redirect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Redirect")
+            .field("endpoint", &self.endpoint.uri)
+            .field("max_redirects", &self.max_redirects)
+            .field("max_cached_channels", &self.max_cached_channels)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redirect() -> Redirect {
+        let endpoint = Endpoint::from_static("http://primary.test");
+        Redirect::new(endpoint.connect_lazy().unwrap(), endpoint)
+    }
+
+    #[tokio::test]
+    async fn copies_registered_extensions() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Copied;
+        #[derive(Debug, Clone, PartialEq)]
+        struct NotCopied;
+
+        let redirect = redirect().copy_extension::<Copied>();
+
+        let mut extensions = Extensions::new();
+        extensions.insert(Copied);
+        extensions.insert(NotCopied);
+
+        let copied = redirect.copy_extensions(&extensions);
+        assert_eq!(copied.get::<Copied>(), Some(&Copied));
+        assert_eq!(copied.get::<NotCopied>(), None);
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_used_channels() {
+        let redirect = redirect().max_cached_channels(2);
+        let cached = || {
+            redirect
+                .redirected
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(uri, _)| uri.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        for uri in &["http://a.test", "http://b.test", "http://a.test"] {
+            redirect.channel_to(Uri::from_static(uri)).unwrap();
+        }
+        assert_eq!(cached(), ["http://b.test/", "http://a.test/"]);
+
+        redirect
+            .channel_to(Uri::from_static("http://c.test"))
+            .unwrap();
+        assert_eq!(cached(), ["http://a.test/", "http://c.test/"]);
+    }
+}
@@ -22,6 +22,7 @@ pub(crate) enum Kind {
     Transport,
     InvalidUri,
     InvalidUserAgent,
+    InvalidPathPrefix,
 }
 
 impl Error {
@@ -48,11 +49,16 @@ impl Error {
         Error::new(Kind::InvalidUserAgent)
     }
 
+    pub(crate) fn new_invalid_path_prefix() -> Self {
+        Error::new(Kind::InvalidPathPrefix)
+    }
+
     fn description(&self) -> &str {
         match &self.inner.kind {
             Kind::Transport => "transport error",
             Kind::InvalidUri => "invalid URI",
             Kind::InvalidUserAgent => "user agent is not a valid header value",
+            Kind::InvalidPathPrefix => "path prefix is not a valid URI path",
         }
     }
 }
@@ -103,6 +103,8 @@ pub use self::service::TimeoutExpired;
 pub use self::tls::{Certificate, Identity};
 pub use hyper::{Body, Uri};
 
+pub(crate) use self::service::{is_blocking, spawn_handler, InFlightBody};
+
 #[cfg(feature = "tls")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
 pub use self::channel::ClientTlsConfig;
@@ -0,0 +1,154 @@
+use http::HeaderMap;
+use http_body::Body;
+use pin_project::pin_project;
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+use tokio::sync::Notify;
+
+/// Counts the calls of a server, for the [`ShutdownSummary`] and
+/// [`Router::serve_n`].
+///
+/// [`ShutdownSummary`]: super::ShutdownSummary
+/// [`Router::serve_n`]: super::Router::serve_n
+#[derive(Debug)]
+pub(crate) struct Calls {
+    in_flight: AtomicUsize,
+    completed: AtomicUsize,
+    limit: Option<usize>,
+    limit_reached: Notify,
+}
+
+impl Calls {
+    pub(crate) fn new(limit: Option<usize>) -> Self {
+        Calls {
+            in_flight: AtomicUsize::new(0),
+            completed: AtomicUsize::new(0),
+            limit,
+            limit_reached: Notify::new(),
+        }
+    }
+
+    /// Wait until the limit of completed calls has been reached, forever if
+    /// there is none.
+    pub(crate) async fn limit_reached(&self) {
+        match self.limit {
+            Some(0) => {}
+            Some(_) => self.limit_reached.notified().await,
+            None => futures_util::future::pending().await,
+        }
+    }
+
+    /// Count a new call, which is in flight until the returned guard is
+    /// dropped. It only counts as completed if its response was sent
+    /// completely by then, and not if it was cancelled or reset before.
+    pub(crate) fn start(self: &Arc<Self>) -> CallGuard {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        CallGuard {
+            calls: self.clone(),
+            finished: false,
+        }
+    }
+
+    pub(crate) fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn completed(&self) -> usize {
+        self.completed.load(Ordering::Acquire)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct CallGuard {
+    calls: Arc<Calls>,
+    finished: bool,
+}
+
+impl CallGuard {
+    /// Mark the response of the call as sent completely.
+    fn finish(&mut self) {
+        self.finished = true;
+    }
+}
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        let calls = &self.calls;
+        calls.in_flight.fetch_sub(1, Ordering::AcqRel);
+
+        if !self.finished {
+            return;
+        }
+
+        let completed = calls.completed.fetch_add(1, Ordering::AcqRel) + 1;
+
+        if Some(completed) == calls.limit {
+            // stores a permit in case the server is not waiting yet
+            calls.limit_reached.notify_one();
+        }
+    }
+}
+
+/// A response body that ends its call when dropped, which completes the
+/// call if the body ended.
+#[pin_project]
+#[derive(Debug)]
+pub(crate) struct Tracked<B> {
+    #[pin]
+    inner: B,
+    guard: CallGuard,
+}
+
+impl<B: Body> Tracked<B> {
+    pub(crate) fn new(inner: B, mut guard: CallGuard) -> Self {
+        // Trailers-Only responses are sent without polling the body.
+        if inner.is_end_stream() {
+            guard.finish();
+        }
+
+        Tracked { inner, guard }
+    }
+}
+
+impl<B: Body> Body for Tracked<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let mut this = self.project();
+        let data = futures_util::ready!(this.inner.as_mut().poll_data(cx));
+
+        if data.is_none() && this.inner.is_end_stream() {
+            this.guard.finish();
+        }
+
+        Poll::Ready(data)
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let this = self.project();
+        let trailers = futures_util::ready!(this.inner.poll_trailers(cx));
+
+        if trailers.is_ok() {
+            this.guard.finish();
+        }
+
+        Poll::Ready(trailers)
+    }
+}
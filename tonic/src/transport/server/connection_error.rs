@@ -0,0 +1,111 @@
+use super::TcpConnectInfo;
+#[cfg(feature = "tls")]
+use super::TlsConnectInfo;
+use std::{any::Any, error::Error as StdError, fmt, net::SocketAddr, sync::Arc};
+
+type Handler = Arc<dyn Fn(&ConnectionError) + Send + Sync + 'static>;
+
+/// An error on a connection that is not tied to a single call.
+///
+/// These are passed to the handler set with
+/// [`Server::on_connection_error`](super::Server::on_connection_error).
+#[derive(Debug)]
+pub struct ConnectionError {
+    kind: ConnectionErrorKind,
+    remote_addr: Option<SocketAddr>,
+    source: crate::Error,
+}
+
+/// The kind of a [`ConnectionError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConnectionErrorKind {
+    /// Accepting a new connection failed.
+    Accept,
+    /// The TLS handshake of a new connection failed.
+    Tls,
+    /// An established connection failed, for example because of an HTTP/2
+    /// protocol error or because it was reset by the peer.
+    Connection,
+}
+
+impl ConnectionError {
+    pub(crate) fn new(
+        kind: ConnectionErrorKind,
+        remote_addr: Option<SocketAddr>,
+        source: impl Into<crate::Error>,
+    ) -> Self {
+        ConnectionError {
+            kind,
+            remote_addr,
+            source: source.into(),
+        }
+    }
+
+    /// Get the kind of this error.
+    pub fn kind(&self) -> ConnectionErrorKind {
+        self.kind
+    }
+
+    /// Get the address of the peer, if known.
+    ///
+    /// This is only available for TCP connections, with or without TLS.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+}
+
+impl fmt::Display for ConnectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            ConnectionErrorKind::Accept => "accept error",
+            ConnectionErrorKind::Tls => "tls handshake error",
+            ConnectionErrorKind::Connection => "connection error",
+        };
+
+        match self.remote_addr {
+            Some(addr) => write!(f, "{} from {}: {}", kind, addr, self.source),
+            None => write!(f, "{}: {}", kind, self.source),
+        }
+    }
+}
+
+impl StdError for ConnectionError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+/// Reports connection errors to the user's handler, or logs them.
+#[derive(Clone, Default)]
+pub(crate) struct ConnectionErrorHandler(Option<Handler>);
+
+impl ConnectionErrorHandler {
+    pub(crate) fn new(f: Handler) -> Self {
+        ConnectionErrorHandler(Some(f))
+    }
+
+    pub(crate) fn report(&self, err: ConnectionError) {
+        match &self.0 {
+            Some(f) => f(&err),
+            None => tracing::warn!(message = "Connection error.", error = %err),
+        }
+    }
+}
+
+/// Get the remote address out of a connection's `ConnectInfo`, this only
+/// knows about the connection info types provided by tonic.
+pub(crate) fn remote_addr(connect_info: &dyn Any) -> Option<SocketAddr> {
+    if let Some(info) = connect_info.downcast_ref::<TcpConnectInfo>() {
+        return info.remote_addr();
+    }
+
+    #[cfg(feature = "tls")]
+    {
+        if let Some(info) = connect_info.downcast_ref::<TlsConnectInfo<TcpConnectInfo>>() {
+            return info.get_ref().remote_addr();
+        }
+    }
+
+    None
+}
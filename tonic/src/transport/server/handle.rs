@@ -0,0 +1,95 @@
+use futures_core::Stream;
+use futures_util::{future, StreamExt};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Controls a running server.
+///
+/// A handle is obtained from [`Server::handle`] before the server is started
+/// and can be cloned and used from anywhere while it runs.
+///
+/// ```no_run
+/// # use tonic::transport::Server;
+/// # fn example() {
+/// let server = Server::builder();
+/// let handle = server.handle();
+///
+/// // during an incident, stop taking new connections
+/// handle.pause_accept();
+///
+/// // and once it is over, take them again
+/// handle.resume_accept();
+/// # }
+/// ```
+///
+/// [`Server::handle`]: super::Server::handle
+#[derive(Debug, Clone)]
+pub struct ServerHandle {
+    paused_tx: Arc<watch::Sender<bool>>,
+    paused_rx: watch::Receiver<bool>,
+}
+
+impl ServerHandle {
+    pub(crate) fn new() -> Self {
+        let (paused_tx, paused_rx) = watch::channel(false);
+
+        ServerHandle {
+            paused_tx: Arc::new(paused_tx),
+            paused_rx,
+        }
+    }
+
+    /// Stop accepting new connections.
+    ///
+    /// The connections already accepted keep being served. New connection
+    /// attempts wait in the backlog of the listening socket until accepting
+    /// is resumed, and may be refused by the OS once it is full.
+    pub fn pause_accept(&self) {
+        let _ = self.paused_tx.send(true);
+    }
+
+    /// Resume accepting new connections after [`pause_accept`].
+    ///
+    /// [`pause_accept`]: ServerHandle::pause_accept
+    pub fn resume_accept(&self) {
+        let _ = self.paused_tx.send(false);
+    }
+
+    /// Returns `true` if accepting new connections is paused.
+    pub fn is_accept_paused(&self) -> bool {
+        *self.paused_rx.borrow()
+    }
+
+    /// Accept the next item of `incoming`, waiting while accepting is
+    /// paused.
+    pub(crate) async fn accept<S>(&self, incoming: &mut S) -> Option<S::Item>
+    where
+        S: Stream + Unpin,
+    {
+        loop {
+            self.paused(false).await;
+
+            tokio::select! {
+                item = incoming.next() => return item,
+                _ = self.paused(true) => {}
+            }
+        }
+    }
+
+    /// Wait until accepting new connections is paused or not.
+    async fn paused(&self, paused: bool) {
+        let mut rx = self.paused_rx.clone();
+
+        while *rx.borrow() != paused {
+            if rx.changed().await.is_err() {
+                future::pending::<()>().await;
+            }
+        }
+    }
+}
+
+impl Default for ServerHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
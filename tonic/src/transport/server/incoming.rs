@@ -1,5 +1,8 @@
+#[cfg(feature = "tls")]
+use super::connection_error::remote_addr;
+use super::connection_error::{ConnectionError, ConnectionErrorKind};
 use super::{Connected, Server};
-use crate::transport::service::ServerIo;
+use crate::transport::service::{set_tos, ServerIo};
 use futures_core::Stream;
 use futures_util::stream::TryStreamExt;
 use hyper::server::{
@@ -17,7 +20,7 @@ use tokio::io::{AsyncRead, AsyncWrite};
 #[cfg(not(feature = "tls"))]
 pub(crate) fn tcp_incoming<IO, IE, L>(
     incoming: impl Stream<Item = Result<IO, IE>>,
-    _server: Server<L>,
+    server: Server<L>,
 ) -> impl Stream<Item = Result<ServerIo<IO>, crate::Error>>
 where
     IO: AsyncRead + AsyncWrite + Connected + Unpin + Send + 'static,
@@ -26,8 +29,12 @@ where
     async_stream::try_stream! {
         futures_util::pin_mut!(incoming);
 
-        while let Some(stream) = incoming.try_next().await? {
-            yield ServerIo::new_io(stream);
+        loop {
+            match incoming.try_next().await {
+                Ok(Some(stream)) => yield ServerIo::new_io(stream),
+                Ok(None) => break,
+                Err(e) => server.connection_error_handler.report(accept_error(e)),
+            }
         }
     }
 }
@@ -52,9 +59,12 @@ where
                 SelectOutput::Incoming(stream) => {
                     if let Some(tls) = &server.tls {
                         let tls = tls.clone();
+                        let addr = remote_addr(&stream.connect_info());
 
                         let accept = tokio::spawn(async move {
-                            let io = tls.accept(stream).await?;
+                            let io = tls.accept(stream).await.map_err(|e| {
+                                ConnectionError::new(ConnectionErrorKind::Tls, addr, e)
+                            })?;
                             Ok(ServerIo::new_tls_io(io))
                         });
 
@@ -69,7 +79,7 @@ where
                 }
 
                 SelectOutput::Err(e) => {
-                    tracing::debug!(message = "Accept loop error.", error = %e);
+                    server.connection_error_handler.report(e);
                 }
 
                 SelectOutput::Done => {
@@ -84,7 +94,7 @@ where
 async fn select<IO, IE>(
     incoming: &mut (impl Stream<Item = Result<IO, IE>> + Unpin),
     tasks: &mut futures_util::stream::futures_unordered::FuturesUnordered<
-        tokio::task::JoinHandle<Result<ServerIo<IO>, crate::Error>>,
+        tokio::task::JoinHandle<Result<ServerIo<IO>, ConnectionError>>,
     >,
 ) -> SelectOutput<IO>
 where
@@ -96,7 +106,7 @@ where
         return match incoming.try_next().await {
             Ok(Some(stream)) => SelectOutput::Incoming(stream),
             Ok(None) => SelectOutput::Done,
-            Err(e) => SelectOutput::Err(e.into()),
+            Err(e) => SelectOutput::Err(accept_error(e)),
         };
     }
 
@@ -105,7 +115,7 @@ where
             match stream {
                 Ok(Some(stream)) => SelectOutput::Incoming(stream),
                 Ok(None) => SelectOutput::Done,
-                Err(e) => SelectOutput::Err(e.into()),
+                Err(e) => SelectOutput::Err(accept_error(e)),
             }
         }
 
@@ -113,22 +123,28 @@ where
             match accept.expect("FuturesUnordered stream should never end") {
                 Ok(Ok(io)) => SelectOutput::Io(io),
                 Ok(Err(e)) => SelectOutput::Err(e),
-                Err(e) => SelectOutput::Err(e.into()),
+                Err(e) => SelectOutput::Err(ConnectionError::new(ConnectionErrorKind::Tls, None, e)),
             }
         }
     }
 }
 
+fn accept_error(e: impl Into<crate::Error>) -> ConnectionError {
+    ConnectionError::new(ConnectionErrorKind::Accept, None, e)
+}
+
 #[cfg(feature = "tls")]
 enum SelectOutput<A> {
     Incoming(A),
     Io(ServerIo<A>),
-    Err(crate::Error),
+    Err(ConnectionError),
     Done,
 }
 
 pub(crate) struct TcpIncoming {
     inner: AddrIncoming,
+    #[cfg_attr(windows, allow(dead_code))]
+    tos: Option<u8>,
 }
 
 impl TcpIncoming {
@@ -136,18 +152,46 @@ impl TcpIncoming {
         addr: SocketAddr,
         nodelay: bool,
         keepalive: Option<Duration>,
+        tos: Option<u8>,
     ) -> Result<Self, crate::Error> {
-        let mut inner = AddrIncoming::bind(&addr)?;
+        let mut inner = bind(addr, tos)?;
         inner.set_nodelay(nodelay);
         inner.set_keepalive(keepalive);
-        Ok(TcpIncoming { inner })
+        Ok(TcpIncoming { inner, tos })
+    }
+}
+
+#[cfg(not(windows))]
+fn bind(addr: SocketAddr, _tos: Option<u8>) -> Result<AddrIncoming, crate::Error> {
+    Ok(AddrIncoming::bind(&addr)?)
+}
+
+// The accepted streams do not expose their socket on Windows, but they
+// inherit the options of the listening socket.
+#[cfg(windows)]
+fn bind(addr: SocketAddr, tos: Option<u8>) -> Result<AddrIncoming, crate::Error> {
+    let listener = std::net::TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+
+    if let Some(tos) = tos {
+        set_tos(&listener, tos);
     }
+
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+    Ok(AddrIncoming::from_listener(listener)?)
 }
 
 impl Stream for TcpIncoming {
     type Item = Result<AddrStream, std::io::Error>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        Pin::new(&mut self.inner).poll_accept(cx)
+        let stream = futures_util::ready!(Pin::new(&mut self.inner).poll_accept(cx));
+
+        #[cfg(not(windows))]
+        if let (Some(Ok(stream)), Some(tos)) = (&stream, self.tos) {
+            set_tos(stream, tos);
+        }
+
+        Poll::Ready(stream)
     }
 }
@@ -1,13 +1,22 @@
 //! Server implementation and builder.
 
+mod calls;
 mod conn;
+mod connection_error;
+mod handle;
 mod incoming;
 mod recover_error;
+mod sampler;
+mod shutdown;
 #[cfg(feature = "tls")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tls")))]
 mod tls;
 
 pub use conn::{Connected, TcpConnectInfo};
+pub use connection_error::{ConnectionError, ConnectionErrorKind};
+pub use handle::ServerHandle;
+pub use sampler::{FinishedCall, Sampler, SizeSampler, TraceSampler};
+pub use shutdown::ShutdownSummary;
 #[cfg(feature = "tls")]
 pub use tls::ServerTlsConfig;
 
@@ -25,9 +34,19 @@ pub(crate) use tokio_rustls::server::TlsStream;
 #[cfg(feature = "tls")]
 use crate::transport::Error;
 
+use self::calls::{CallGuard, Calls, Tracked};
+use self::connection_error::{remote_addr, ConnectionErrorHandler};
 use self::recover_error::RecoverError;
-use super::service::{GrpcTimeout, Or, Routes, ServerIo};
+use self::sampler::{TailSampled, Unsampled};
+use super::service::{
+    path_prefix, set_proto_subtype, EchoDeadline, GrpcTimeout, MarkBlocking, Or, Routes, ServerIo,
+    StreamRate, StreamRateLimit,
+};
 use crate::body::BoxBody;
+use crate::cancellation::CancelScope;
+use crate::codec::{DecodeErrorHandler, UnknownFields};
+use crate::server::CallLabels;
+use crate::Status;
 use bytes::Bytes;
 use futures_core::Stream;
 use futures_util::{
@@ -36,15 +55,19 @@ use futures_util::{
 };
 use http::{Request, Response};
 use http_body::Body as _;
-use hyper::{server::accept, Body};
+use hyper::{server::conn::Http, Body};
 use pin_project::pin_project;
 use std::{
+    collections::HashSet,
     fmt,
     future::Future,
     marker::PhantomData,
     net::SocketAddr,
     pin::Pin,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
     time::Duration,
 };
@@ -62,17 +85,23 @@ const DEFAULT_HTTP2_KEEPALIVE_TIMEOUT_SECS: u64 = 20;
 
 /// A default batteries included `transport` server.
 ///
-/// This is a wrapper around [`hyper::Server`] and provides an easy builder
-/// pattern style builder [`Server`]. This builder exposes easy configuration parameters
-/// for providing a fully featured http2 based gRPC server. This should provide
-/// a very good out of the box http2 server for use with tonic but is also a
-/// reference implementation that should be a good starting point for anyone
-/// wanting to create a more complex and/or specific implementation.
+/// This serves each accepted connection with [`hyper`]'s HTTP/2 connection
+/// driver and provides an easy builder pattern style builder [`Server`]. This
+/// builder exposes easy configuration parameters for providing a fully
+/// featured http2 based gRPC server. This should provide a very good out of
+/// the box http2 server for use with tonic but is also a reference
+/// implementation that should be a good starting point for anyone wanting to
+/// create a more complex and/or specific implementation.
 #[derive(Default, Clone)]
 pub struct Server<L = Identity> {
     trace_interceptor: Option<TraceInterceptor>,
+    trace_sampler: Option<Arc<dyn Sampler>>,
+    connection_error_handler: ConnectionErrorHandler,
     concurrency_limit: Option<usize>,
+    stream_rate: Option<StreamRate>,
     timeout: Option<Duration>,
+    echo_deadlines: bool,
+    path_prefix: Option<Arc<str>>,
     #[cfg(feature = "tls")]
     tls: Option<TlsAcceptor>,
     init_stream_window_size: Option<u32>,
@@ -80,10 +109,18 @@ pub struct Server<L = Identity> {
     max_concurrent_streams: Option<u32>,
     tcp_keepalive: Option<Duration>,
     tcp_nodelay: bool,
+    tos: Option<u8>,
     http2_keepalive_interval: Option<Duration>,
     http2_keepalive_timeout: Option<Duration>,
     max_frame_size: Option<u32>,
     accept_http1: bool,
+    grpc_proto_content_type: bool,
+    track_unknown_fields: bool,
+    decode_error_handler: Option<DecodeErrorHandler>,
+    call_limit: Option<usize>,
+    drain_timeout: Option<Duration>,
+    handle: ServerHandle,
+    blocking_methods: Arc<HashSet<String>>,
     layer: L,
 }
 
@@ -178,6 +215,37 @@ impl<L> Server<L> {
         }
     }
 
+    /// Limit the rate at which each connection may open new streams.
+    ///
+    /// A connection may open `burst` streams at once, and another one for
+    /// each `per / streams` that passes. Streams opened faster than that are
+    /// rejected with `RESOURCE_EXHAUSTED`, whether or not the calls made
+    /// before them are still in flight. Unlike
+    /// [`concurrency_limit_per_connection`], this protects against clients
+    /// opening and closing many short streams.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `streams`, `per` or `burst` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// // 100 streams per second, with bursts of up to 20
+    /// builder.stream_rate_limit_per_connection(100, Duration::from_secs(1), 20);
+    /// ```
+    ///
+    /// [`concurrency_limit_per_connection`]: Server::concurrency_limit_per_connection
+    pub fn stream_rate_limit_per_connection(self, streams: u64, per: Duration, burst: u64) -> Self {
+        Server {
+            stream_rate: Some(StreamRate::new(streams, per, burst)),
+            ..self
+        }
+    }
+
     /// Set a timeout on for all request handlers.
     ///
     /// # Example
@@ -194,6 +262,92 @@ impl<L> Server<L> {
         self
     }
 
+    /// Strip `prefix` from the path of calls before routing them.
+    ///
+    /// This serves the services under a prefix, such as behind an ingress
+    /// adding `/v1` to the path, or for clients configured with the same
+    /// [`path_prefix`]. A call to `/v1/package.Service/Method` is routed to
+    /// `/package.Service/Method`, while calls without the prefix are still
+    /// routed as they are.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.path_prefix("/v1");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `prefix` is not a valid URI path, or has a
+    /// query or a fragment.
+    ///
+    /// [`path_prefix`]: crate::transport::Endpoint::path_prefix
+    pub fn path_prefix(self, prefix: &str) -> Self {
+        let path_prefix = path_prefix::normalize(prefix)
+            .unwrap_or_else(|_| panic!("invalid path prefix: {:?}", prefix));
+
+        Server {
+            path_prefix,
+            ..self
+        }
+    }
+
+    /// Echo the deadline of each call back in the response headers.
+    ///
+    /// The `grpc-timeout` the server received is echoed in
+    /// `tonic-received-timeout`, and the time that was left until the
+    /// deadline when the call was answered, accounting for the server
+    /// [`timeout`], in `tonic-deadline-remaining`, in the same format. This
+    /// is meant for debugging deadline propagation, such as finding proxies
+    /// that strip or rewrite the timeout, and is disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.echo_deadlines(true);
+    /// ```
+    ///
+    /// [`timeout`]: Server::timeout
+    pub fn echo_deadlines(self, enabled: bool) -> Self {
+        Server {
+            echo_deadlines: enabled,
+            ..self
+        }
+    }
+
+    /// Set how long a graceful shutdown waits for open connections to
+    /// close.
+    ///
+    /// Connections still open once it elapsed are closed, cancelling the
+    /// calls in flight on them, which is reported in the
+    /// [`ShutdownSummary`]. By default a shutdown waits until all
+    /// connections are closed.
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.drain_timeout(Duration::from_secs(10));
+    /// ```
+    pub fn drain_timeout(self, drain_timeout: Duration) -> Self {
+        Server {
+            drain_timeout: Some(drain_timeout),
+            ..self
+        }
+    }
+
+    /// Returns a [`ServerHandle`] to control this server once it runs.
+    ///
+    /// All handles of a server, including those of its clones, control the
+    /// same server.
+    pub fn handle(&self) -> ServerHandle {
+        self.handle.clone()
+    }
+
     /// Sets the [`SETTINGS_INITIAL_WINDOW_SIZE`][spec] option for HTTP2
     /// stream-level flow control.
     ///
@@ -283,6 +437,28 @@ impl<L> Server<L> {
         }
     }
 
+    /// Set the `IP_TOS` option of accepted connections, or `IPV6_TCLASS` for
+    /// IPv6 connections, which carries the DSCP and ECN bits used to
+    /// prioritize traffic on managed networks.
+    ///
+    /// This is supported on Unix platforms and Windows, though not every
+    /// platform supports both options. A warning is logged when the option
+    /// cannot be set. Like the other TCP options it does not apply to
+    /// connections passed to [`Router::serve_with_incoming`].
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// // DSCP class EF (expedited forwarding)
+    /// builder.tos(46 << 2);
+    /// ```
+    pub fn tos(self, tos: u8) -> Self {
+        Server {
+            tos: Some(tos),
+            ..self
+        }
+    }
+
     /// Sets the maximum frame size to use for HTTP2.
     ///
     /// Passing `None` will do nothing.
@@ -310,6 +486,89 @@ impl<L> Server<L> {
         }
     }
 
+    /// Send responses with the `application/grpc+proto` content-type.
+    ///
+    /// Some gateways route on the exact subtype and require `+proto`. By
+    /// default responses are sent with the bare `application/grpc`, which
+    /// means the same. Requests are accepted with either content-type.
+    ///
+    /// Default is `false`.
+    pub fn grpc_proto_content_type(self, enabled: bool) -> Self {
+        Server {
+            grpc_proto_content_type: enabled,
+            ..self
+        }
+    }
+
+    /// Count the fields of request messages that the server's schema does
+    /// not know about.
+    ///
+    /// Every request then carries an [`UnknownFields`] counter in its
+    /// extensions. This encodes every decoded message again and is only
+    /// supported by the prost codec.
+    ///
+    /// Default is `false`.
+    ///
+    /// [`UnknownFields`]: crate::codec::UnknownFields
+    pub fn track_unknown_fields(self, enabled: bool) -> Self {
+        Server {
+            track_unknown_fields: enabled,
+            ..self
+        }
+    }
+
+    /// Choose the status returned when a request message fails to decode.
+    ///
+    /// `f` is given the status the codec failed with, which for the prost codec
+    /// is `INTERNAL` as recommended by the gRPC spec, and returns the status
+    /// sent to the client instead. It is also given the status of messages that
+    /// cannot be read off the request stream: truncated frames, invalid
+    /// compression flags and messages that fail to decompress. A compressed
+    /// message received without compression support is not malformed and is
+    /// always rejected with `UNIMPLEMENTED`. Some services prefer to attribute
+    /// malformed messages to the client:
+    ///
+    /// ```
+    /// # use tonic::{transport::Server, Status};
+    /// # let builder = Server::builder();
+    /// builder.on_decode_error(|status| Status::invalid_argument(status.message()));
+    /// ```
+    pub fn on_decode_error<F>(self, f: F) -> Self
+    where
+        F: Fn(Status) -> Status + Send + Sync + 'static,
+    {
+        Server {
+            decode_error_handler: Some(DecodeErrorHandler::new(Arc::new(f))),
+            ..self
+        }
+    }
+
+    /// Run the handler of the given method on the blocking thread pool.
+    ///
+    /// `path` is the full method path, for example
+    /// `/routeguide.RouteGuide/GetFeature`. The handler of a service
+    /// generated by `tonic-build` is run on a thread from
+    /// [`tokio::task::spawn_blocking`] so that CPU heavy or blocking handlers
+    /// do not stall the other requests served by the runtime. Decoding the
+    /// request and encoding the response stay on the runtime, but a handler
+    /// of a client or bidirectional streaming method reads its request stream
+    /// from the blocking thread.
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.blocking_method("/routeguide.RouteGuide/GetFeature");
+    /// ```
+    pub fn blocking_method(self, path: impl Into<String>) -> Self {
+        let mut blocking_methods = self.blocking_methods;
+        Arc::make_mut(&mut blocking_methods).insert(path.into());
+
+        Server {
+            blocking_methods,
+            ..self
+        }
+    }
+
     /// Intercept inbound headers and add a [`tracing::Span`] to each response future.
     pub fn trace_fn<F>(self, f: F) -> Self
     where
@@ -321,6 +580,43 @@ impl<L> Server<L> {
         }
     }
 
+    /// Only add a span from [`trace_fn`](Server::trace_fn) to the calls
+    /// chosen by `sampler`.
+    ///
+    /// See [`Sampler`] for how calls are chosen, and [`TraceSampler`] for a
+    /// sampler choosing calls at a fixed rate.
+    pub fn trace_sampler(self, sampler: impl Sampler) -> Self {
+        Server {
+            trace_sampler: Some(Arc::new(sampler)),
+            ..self
+        }
+    }
+
+    /// Handle errors of connections rather than of single calls.
+    ///
+    /// `f` is called when accepting a connection fails, when the TLS
+    /// handshake of a new connection fails, and when an established
+    /// connection fails, for example because of an HTTP/2 protocol error or
+    /// because it was reset by the peer. By default these errors are logged
+    /// with `tracing` at the `WARN` level.
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.on_connection_error(|err| {
+    ///     eprintln!("{:?} from {:?}: {}", err.kind(), err.remote_addr(), err);
+    /// });
+    /// ```
+    pub fn on_connection_error<F>(self, f: F) -> Self
+    where
+        F: Fn(&ConnectionError) + Send + Sync + 'static,
+    {
+        Server {
+            connection_error_handler: ConnectionErrorHandler::new(Arc::new(f)),
+            ..self
+        }
+    }
+
     /// Create a router with the `S` typed service as the first service.
     ///
     /// This will clone the `Server` builder and create a router that will
@@ -433,8 +729,13 @@ impl<L> Server<L> {
         Server {
             layer: new_layer,
             trace_interceptor: self.trace_interceptor,
+            trace_sampler: self.trace_sampler,
+            connection_error_handler: self.connection_error_handler,
             concurrency_limit: self.concurrency_limit,
+            stream_rate: self.stream_rate,
             timeout: self.timeout,
+            echo_deadlines: self.echo_deadlines,
+            path_prefix: self.path_prefix,
             #[cfg(feature = "tls")]
             tls: self.tls,
             init_stream_window_size: self.init_stream_window_size,
@@ -442,10 +743,18 @@ impl<L> Server<L> {
             max_concurrent_streams: self.max_concurrent_streams,
             tcp_keepalive: self.tcp_keepalive,
             tcp_nodelay: self.tcp_nodelay,
+            tos: self.tos,
             http2_keepalive_interval: self.http2_keepalive_interval,
             http2_keepalive_timeout: self.http2_keepalive_timeout,
             max_frame_size: self.max_frame_size,
             accept_http1: self.accept_http1,
+            grpc_proto_content_type: self.grpc_proto_content_type,
+            track_unknown_fields: self.track_unknown_fields,
+            decode_error_handler: self.decode_error_handler,
+            call_limit: self.call_limit,
+            drain_timeout: self.drain_timeout,
+            handle: self.handle,
+            blocking_methods: self.blocking_methods,
         }
     }
 
@@ -454,7 +763,7 @@ impl<L> Server<L> {
         svc: S,
         incoming: I,
         signal: Option<F>,
-    ) -> Result<(), super::Error>
+    ) -> Result<ShutdownSummary, super::Error>
     where
         L: Layer<S>,
         L::Service: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
@@ -469,34 +778,56 @@ impl<L> Server<L> {
         ResBody::Error: Into<crate::Error>,
     {
         let trace_interceptor = self.trace_interceptor.clone();
+        let trace_sampler = self.trace_sampler.clone();
         let concurrency_limit = self.concurrency_limit;
+        let stream_rate = self.stream_rate;
         let init_connection_window_size = self.init_connection_window_size;
         let init_stream_window_size = self.init_stream_window_size;
         let max_concurrent_streams = self.max_concurrent_streams;
         let timeout = self.timeout;
+        let echo_deadlines = self.echo_deadlines;
+        let path_prefix = self.path_prefix.clone();
+        let blocking_methods = self.blocking_methods.clone();
         let max_frame_size = self.max_frame_size;
         let http2_only = !self.accept_http1;
+        let grpc_proto_content_type = self.grpc_proto_content_type;
+        let track_unknown_fields = self.track_unknown_fields;
+        let decode_error_handler = self.decode_error_handler.clone();
+        let calls = Arc::new(Calls::new(self.call_limit));
+        let shuts_down = signal.is_some() || self.call_limit.is_some();
+        let drain_timeout = self.drain_timeout;
+        let handle = self.handle.clone();
 
         let http2_keepalive_interval = self.http2_keepalive_interval;
         let http2_keepalive_timeout = self
             .http2_keepalive_timeout
             .unwrap_or_else(|| Duration::new(DEFAULT_HTTP2_KEEPALIVE_TIMEOUT_SECS, 0));
 
+        let connection_error_handler = self.connection_error_handler.clone();
+
         let svc = self.layer.layer(svc);
 
         let tcp = incoming::tcp_incoming(incoming, self);
-        let incoming = accept::from_stream::<_, _, crate::Error>(tcp);
 
-        let svc = MakeSvc {
+        let mut svc = MakeSvc {
             inner: svc,
             concurrency_limit,
+            stream_rate,
             timeout,
+            echo_deadlines,
+            path_prefix,
+            blocking_methods,
+            grpc_proto_content_type,
+            track_unknown_fields,
+            decode_error_handler,
+            calls: calls.clone(),
             trace_interceptor,
+            trace_sampler,
             _io: PhantomData,
         };
 
-        let server = hyper::Server::builder(incoming)
-            .http2_only(http2_only)
+        let mut http = Http::new();
+        http.http2_only(http2_only)
             .http2_initial_connection_window_size(init_connection_window_size)
             .http2_initial_stream_window_size(init_stream_window_size)
             .http2_max_concurrent_streams(max_concurrent_streams)
@@ -504,17 +835,112 @@ impl<L> Server<L> {
             .http2_keep_alive_timeout(http2_keepalive_timeout)
             .http2_max_frame_size(max_frame_size);
 
-        if let Some(signal) = signal {
-            server
-                .serve(svc)
-                .with_graceful_shutdown(signal)
-                .await
-                .map_err(super::Error::from_source)?
+        let signal = {
+            let calls = calls.clone();
+
+            async move {
+                let signal = async move {
+                    match signal {
+                        Some(signal) => signal.await,
+                        None => future::pending().await,
+                    }
+                };
+
+                tokio::select! {
+                    _ = signal => {}
+                    _ = calls.limit_reached() => {}
+                }
+            }
+        };
+
+        // Connections are asked to shut down gracefully through `shutdown_tx`,
+        // and closed forcibly through `force_tx` once the drain timed out.
+        // Each holds a clone of `drain_tx` until it is closed.
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(());
+        let (force_tx, force_rx) = tokio::sync::watch::channel(());
+        let (drain_tx, mut drain_rx) = tokio::sync::mpsc::channel::<()>(1);
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        futures_util::pin_mut!(tcp);
+        futures_util::pin_mut!(signal);
+
+        let incoming_ended = loop {
+            let io = tokio::select! {
+                _ = &mut signal => break false,
+                io = handle.accept(&mut tcp) => match io.transpose().map_err(super::Error::from_source)? {
+                    Some(io) => io,
+                    None => break true,
+                },
+            };
+
+            let addr = match io.connect_info() {
+                Either::A(info) => remote_addr(&info),
+                Either::B(info) => remote_addr(&info),
+            };
+
+            let svc = svc.call(&io).await.map_err(super::Error::from_source)?;
+
+            tokio::spawn(ServeConnection {
+                conn: http.serve_connection(io, svc),
+                shutdown: Some(Box::pin(signalled(shutdown_rx.clone()))),
+                force: Box::pin(signalled(force_rx.clone())),
+                remote_addr: addr,
+                connection_error_handler: connection_error_handler.clone(),
+                _open: OpenConnection::new(drain_tx.clone(), connections.clone()),
+            });
+        };
+
+        // Without a shutdown signal there is nothing to wait for, and the open
+        // connections are served in the background.
+        if incoming_ended && !shuts_down {
+            return Ok(ShutdownSummary::default());
+        }
+
+        let open = connections.load(Ordering::Acquire);
+        let completed = calls.completed();
+        drop(drain_tx);
+
+        // Once the incoming stream ended the open connections are left to
+        // close on their own, unless the signal asks them to close first.
+        let signalled = !incoming_ended
+            || tokio::select! {
+                _ = &mut signal => true,
+                _ = drain_rx.recv() => false,
+            };
+
+        let drained = if signalled {
+            let _ = shutdown_tx.send(());
+
+            match drain_timeout {
+                Some(drain_timeout) => tokio::time::timeout(drain_timeout, drain_rx.recv())
+                    .await
+                    .is_ok(),
+                None => {
+                    let _ = drain_rx.recv().await;
+                    true
+                }
+            }
         } else {
-            server.serve(svc).await.map_err(super::Error::from_source)?;
+            true
+        };
+
+        let mut summary = ShutdownSummary {
+            calls_completed: calls.completed() - completed,
+            ..ShutdownSummary::default()
+        };
+
+        if !drained {
+            summary.timed_out = true;
+            summary.connections_closed = connections.load(Ordering::Acquire);
+            summary.calls_cancelled = calls.in_flight();
+
+            let _ = force_tx.send(());
+            let _ = drain_rx.recv().await;
         }
 
-        Ok(())
+        summary.connections_drained = open.saturating_sub(summary.connections_closed);
+
+        Ok(summary)
     }
 }
 
@@ -530,15 +956,9 @@ impl<S, L> Router<S, Unimplemented, L> {
         S::Error: Into<crate::Error> + Send,
     {
         let svc_name = <S as NamedService>::NAME;
-        let svc_route = format!("/{}", svc_name);
-        let pred = move |req: &Request<Body>| {
-            let path = req.uri().path();
-
-            path.starts_with(&svc_route)
-        };
         Self {
             server,
-            routes: Routes::new(pred, svc, Unimplemented::default()),
+            routes: Routes::new(svc_name, svc, Unimplemented::default()),
         }
     }
 }
@@ -548,9 +968,6 @@ where
     A: Service<Request<Body>, Response = Response<BoxBody>> + Clone + Send + 'static,
     A::Future: Send + 'static,
     A::Error: Into<crate::Error> + Send,
-    B: Service<Request<Body>, Response = Response<BoxBody>> + Clone + Send + 'static,
-    B::Future: Send + 'static,
-    B::Error: Into<crate::Error> + Send,
 {
     /// Add a new service to this router.
     pub fn add_service<S>(self, svc: S) -> Router<S, Or<A, B, Request<Body>>, L>
@@ -566,13 +983,7 @@ where
         let Self { routes, server } = self;
 
         let svc_name = <S as NamedService>::NAME;
-        let svc_route = format!("/{}", svc_name);
-        let pred = move |req: &Request<Body>| {
-            let path = req.uri().path();
-
-            path.starts_with(&svc_route)
-        };
-        let routes = routes.push(pred, svc);
+        let routes = routes.push(svc_name, svc);
 
         Router { server, routes }
     }
@@ -599,24 +1010,60 @@ where
         let Self { routes, server } = self;
 
         let svc_name = <S as NamedService>::NAME;
-        let svc_route = format!("/{}", svc_name);
-        let pred = move |req: &Request<Body>| {
-            let path = req.uri().path();
-
-            path.starts_with(&svc_route)
-        };
         let svc = match svc {
             Some(some) => Either::A(some),
             None => Either::B(Unimplemented::default()),
         };
-        let routes = routes.push(pred, svc);
+        let routes = routes.push(svc_name, svc);
 
         Router { server, routes }
     }
 
+    /// Route the requests to services that were not added to this router
+    /// to `svc`, instead of answering them with `UNIMPLEMENTED`.
+    ///
+    /// The added services still take precedence for their paths, so a
+    /// gateway can serve the services it knows and forward everything else:
+    ///
+    /// ```
+    /// # use tonic::{body::BoxBody, transport::{Body, Server}};
+    /// # use tower::service_fn;
+    /// # fn example<S>(known: S)
+    /// # where
+    /// #     S: tower::Service<http::Request<Body>, Response = http::Response<BoxBody>, Error = std::convert::Infallible>
+    /// #         + tonic::transport::NamedService + Clone + Send + 'static,
+    /// #     S::Future: Send + 'static,
+    /// # {
+    /// let forward = service_fn(|request: http::Request<Body>| async move {
+    ///     // send `request` upstream
+    /// #   drop(request);
+    /// #   Ok::<_, std::convert::Infallible>(http::Response::new(tonic::body::empty_body()))
+    /// });
+    ///
+    /// let router = Server::builder().add_service(known).fallback(forward);
+    /// # }
+    /// ```
+    pub fn fallback<F>(self, svc: F) -> Self
+    where
+        F: Service<Request<Body>, Response = Response<BoxBody>> + Clone + Send + 'static,
+        F::Future: Send + 'static,
+        F::Error: Into<crate::Error> + Send,
+    {
+        let Self { routes, server } = self;
+
+        Router {
+            server,
+            routes: routes.fallback(svc),
+        }
+    }
+
     /// Consume this [`Server`] creating a future that will execute the server
     /// on [tokio]'s default executor.
     ///
+    /// Failing to accept a connection does not stop the server. The error is
+    /// reported to the [connection error handler](Server::on_connection_error)
+    /// and the server carries on accepting connections.
+    ///
     /// [`Server`]: struct.Server.html
     /// [tokio]: https://docs.rs/tokio
     pub async fn serve<ResBody>(self, addr: SocketAddr) -> Result<(), super::Error>
@@ -630,28 +1077,41 @@ where
         ResBody: http_body::Body<Data = Bytes> + Send + Sync + 'static,
         ResBody::Error: Into<crate::Error>,
     {
-        let incoming = TcpIncoming::new(addr, self.server.tcp_nodelay, self.server.tcp_keepalive)
-            .map_err(super::Error::from_source)?;
+        let incoming = TcpIncoming::new(
+            addr,
+            self.server.tcp_nodelay,
+            self.server.tcp_keepalive,
+            self.server.tos,
+        )
+        .map_err(super::Error::from_source)?;
         self.server
             .serve_with_shutdown::<_, _, future::Ready<()>, _, _, ResBody>(
                 self.routes,
                 incoming,
                 None,
             )
-            .await
+            .await?;
+
+        Ok(())
     }
 
     /// Consume this [`Server`] creating a future that will execute the server
     /// on [tokio]'s default executor. And shutdown when the provided signal
     /// is received.
     ///
+    /// The returned future resolves with a [`ShutdownSummary`] once the
+    /// server has shut down.
+    ///
+    /// Errors accepting connections are reported and skipped, like with
+    /// [`serve`](Router::serve).
+    ///
     /// [`Server`]: struct.Server.html
     /// [tokio]: https://docs.rs/tokio
     pub async fn serve_with_shutdown<F: Future<Output = ()>, ResBody>(
         self,
         addr: SocketAddr,
         signal: F,
-    ) -> Result<(), super::Error>
+    ) -> Result<ShutdownSummary, super::Error>
     where
         L: Layer<Routes<A, B, Request<Body>>>,
         L::Service: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
@@ -662,16 +1122,75 @@ where
         ResBody: http_body::Body<Data = Bytes> + Send + Sync + 'static,
         ResBody::Error: Into<crate::Error>,
     {
-        let incoming = TcpIncoming::new(addr, self.server.tcp_nodelay, self.server.tcp_keepalive)
-            .map_err(super::Error::from_source)?;
+        let incoming = TcpIncoming::new(
+            addr,
+            self.server.tcp_nodelay,
+            self.server.tcp_keepalive,
+            self.server.tos,
+        )
+        .map_err(super::Error::from_source)?;
         self.server
             .serve_with_shutdown(self.routes, incoming, Some(signal))
             .await
     }
 
+    /// Consume this [`Server`] creating a future that will execute the server
+    /// on [tokio]'s default executor until `n` calls have completed.
+    ///
+    /// A call completes once its response has been sent completely, calls that
+    /// were cancelled or reset before do not count. The server then shuts down
+    /// gracefully, which lets calls that are still in flight finish. This is
+    /// meant for examples and tests that should exit on their own.
+    ///
+    /// Errors accepting connections are reported and skipped, like with
+    /// [`serve`](Router::serve).
+    ///
+    /// [`Server`]: struct.Server.html
+    /// [tokio]: https://docs.rs/tokio
+    pub async fn serve_n<ResBody>(mut self, addr: SocketAddr, n: usize) -> Result<(), super::Error>
+    where
+        L: Layer<Routes<A, B, Request<Body>>>,
+        L::Service: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+        <<L as Layer<Routes<A, B, Request<Body>>>>::Service as Service<Request<Body>>>::Future:
+            Send + 'static,
+        <<L as Layer<Routes<A, B, Request<Body>>>>::Service as Service<Request<Body>>>::Error:
+            Into<crate::Error> + Send,
+        ResBody: http_body::Body<Data = Bytes> + Send + Sync + 'static,
+        ResBody::Error: Into<crate::Error>,
+    {
+        let incoming = TcpIncoming::new(
+            addr,
+            self.server.tcp_nodelay,
+            self.server.tcp_keepalive,
+            self.server.tos,
+        )
+        .map_err(super::Error::from_source)?;
+
+        self.server.call_limit = Some(n);
+
+        self.server
+            .serve_with_shutdown::<_, _, future::Ready<()>, _, _, ResBody>(
+                self.routes,
+                incoming,
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
     /// Consume this [`Server`] creating a future that will execute the server on
     /// the provided incoming stream of `AsyncRead + AsyncWrite`.
     ///
+    /// The returned future resolves once the incoming stream ended, while the
+    /// connections that are still open keep being served in the background.
+    ///
+    /// An error from `incoming` does not stop the server. It is
+    /// reported to the [connection error handler](Server::on_connection_error)
+    /// and `incoming` is polled again right away, so a stream that keeps
+    /// failing, for example while the process is out of file descriptors,
+    /// should back off by itself.
+    ///
     /// [`Server`]: struct.Server.html
     pub async fn serve_with_incoming<I, IO, IE, ResBody>(
         self,
@@ -697,7 +1216,9 @@ where
                 incoming,
                 None,
             )
-            .await
+            .await?;
+
+        Ok(())
     }
 
     /// Consume this [`Server`] creating a future that will execute the server on
@@ -705,12 +1226,20 @@ where
     /// `serve_with_shutdown` this method will also take a signal future to
     /// gracefully shutdown the server.
     ///
+    /// The returned future resolves with a [`ShutdownSummary`] once the
+    /// server has shut down. If the incoming stream ends before the signal,
+    /// the server keeps serving the open connections and resolves once they
+    /// all closed, or drains them gracefully when the signal arrives first.
+    ///
+    /// Errors from `incoming` are reported and skipped, like with
+    /// [`serve_with_incoming`](Router::serve_with_incoming).
+    ///
     /// [`Server`]: struct.Server.html
     pub async fn serve_with_incoming_shutdown<I, IO, IE, F, ResBody>(
         self,
         incoming: I,
         signal: F,
-    ) -> Result<(), super::Error>
+    ) -> Result<ShutdownSummary, super::Error>
     where
         I: Stream<Item = Result<IO, IE>>,
         IO: AsyncRead + AsyncWrite + Connected + Unpin + Send + 'static,
@@ -757,6 +1286,8 @@ impl<L> fmt::Debug for Server<L> {
 struct Svc<S> {
     inner: S,
     trace_interceptor: Option<TraceInterceptor>,
+    trace_sampler: Option<Arc<dyn Sampler>>,
+    calls: Arc<Calls>,
 }
 
 impl<S, ResBody> Service<Request<Body>> for Svc<S>
@@ -775,23 +1306,41 @@ where
     }
 
     fn call(&mut self, mut req: Request<Body>) -> Self::Future {
-        let span = if let Some(trace_interceptor) = &self.trace_interceptor {
-            let (parts, body) = req.into_parts();
-            let bodyless_request = Request::from_parts(parts, ());
+        let mut unsampled = None;
+
+        let span = match &self.trace_interceptor {
+            Some(trace_interceptor) => match &self.trace_sampler {
+                Some(sampler) if !sampler.sample(req.uri().path()) => {
+                    // keep what the span is made from in case the call
+                    // fails or is slow
+                    unsampled = Some(Unsampled::new(
+                        &req,
+                        trace_interceptor.clone(),
+                        sampler.clone(),
+                    ));
+
+                    tracing::Span::none()
+                }
+                _ => {
+                    let (parts, body) = req.into_parts();
+                    let bodyless_request = Request::from_parts(parts, ());
 
-            let span = trace_interceptor(&bodyless_request);
+                    let span = trace_interceptor(&bodyless_request);
 
-            let (parts, _) = bodyless_request.into_parts();
-            req = Request::from_parts(parts, body);
+                    let (parts, _) = bodyless_request.into_parts();
+                    req = Request::from_parts(parts, body);
 
-            span
-        } else {
-            tracing::Span::none()
+                    span
+                }
+            },
+            None => tracing::Span::none(),
         };
 
         SvcFuture {
             inner: self.inner.call(req),
             span,
+            unsampled,
+            call: Some(self.calls.start()),
         }
     }
 }
@@ -801,6 +1350,9 @@ struct SvcFuture<F> {
     #[pin]
     inner: F,
     span: tracing::Span,
+    unsampled: Option<Unsampled>,
+    // completes the call once the response body is dropped
+    call: Option<CallGuard>,
 }
 
 impl<F, E, ResBody> Future for SvcFuture<F>
@@ -816,8 +1368,27 @@ where
         let this = self.project();
         let _guard = this.span.enter();
 
-        let response: Response<ResBody> = ready!(this.inner.poll(cx)).map_err(Into::into)?;
-        let response = response.map(|body| body.map_err(Into::into).boxed());
+        let result: Result<Response<ResBody>, crate::Error> =
+            ready!(this.inner.poll(cx)).map_err(Into::into);
+
+        let call = this.call.take().expect("polled after completion");
+
+        let response = match this.unsampled.take() {
+            Some(unsampled) => match result {
+                Ok(response) => {
+                    let (parts, body) = response.into_parts();
+                    let body =
+                        TailSampled::new(Tracked::new(body, call), &parts.headers, unsampled);
+                    Response::from_parts(parts, body.map_err(Into::into).boxed())
+                }
+                Err(error) => {
+                    unsampled.finish(Some("error"));
+                    return Poll::Ready(Err(error));
+                }
+            },
+            None => result?.map(|body| Tracked::new(body, call).map_err(Into::into).boxed()),
+        };
+
         Poll::Ready(Ok(response))
     }
 }
@@ -830,9 +1401,18 @@ impl<S> fmt::Debug for Svc<S> {
 
 struct MakeSvc<S, IO> {
     concurrency_limit: Option<usize>,
+    stream_rate: Option<StreamRate>,
     timeout: Option<Duration>,
+    echo_deadlines: bool,
+    path_prefix: Option<Arc<str>>,
+    blocking_methods: Arc<HashSet<String>>,
+    grpc_proto_content_type: bool,
+    track_unknown_fields: bool,
+    decode_error_handler: Option<DecodeErrorHandler>,
+    calls: Arc<Calls>,
     inner: S,
     trace_interceptor: Option<TraceInterceptor>,
+    trace_sampler: Option<Arc<dyn Sampler>>,
     _io: PhantomData<fn() -> IO>,
 }
 
@@ -858,13 +1438,26 @@ where
 
         let svc = self.inner.clone();
         let concurrency_limit = self.concurrency_limit;
+        let stream_rate = self.stream_rate;
         let timeout = self.timeout;
+        let echo_deadlines = self.echo_deadlines;
+        let path_prefix = self.path_prefix.clone();
+        let blocking_methods = self.blocking_methods.clone();
+        let grpc_proto_content_type = self.grpc_proto_content_type;
+        let track_unknown_fields = self.track_unknown_fields;
+        let decode_error_handler = self.decode_error_handler.clone();
+        let calls = self.calls.clone();
         let trace_interceptor = self.trace_interceptor.clone();
+        let trace_sampler = self.trace_sampler.clone();
 
         let svc = ServiceBuilder::new()
+            .layer_fn(|s| EchoDeadline::new(s, echo_deadlines, timeout))
             .layer_fn(RecoverError::new)
+            .layer_fn(|s| StreamRateLimit::new(s, stream_rate))
             .option_layer(concurrency_limit.map(ConcurrencyLimitLayer::new))
             .layer_fn(|s| GrpcTimeout::new(s, timeout))
+            .layer_fn(|s| MarkBlocking::new(s, blocking_methods.clone()))
+            .layer_fn(CancelScope::new)
             .service(svc);
 
         let svc = ServiceBuilder::new()
@@ -890,17 +1483,120 @@ where
                     }
                 }
 
+                if let Some(prefix) = &path_prefix {
+                    let uri = std::mem::take(request.uri_mut());
+                    *request.uri_mut() = path_prefix::strip(uri, prefix);
+                }
+
+                request.extensions_mut().insert(CallLabels::new());
+
+                if track_unknown_fields {
+                    request.extensions_mut().insert(UnknownFields::new());
+                }
+
+                if let Some(decode_error_handler) = &decode_error_handler {
+                    request
+                        .extensions_mut()
+                        .insert(decode_error_handler.clone());
+                }
+
                 request
             })
+            .map_response(move |mut response: Response<BoxHttpBody>| {
+                if grpc_proto_content_type {
+                    set_proto_subtype(response.headers_mut());
+                }
+
+                response
+            })
             .service(Svc {
                 inner: svc,
                 trace_interceptor,
+                trace_sampler,
+                calls,
             });
 
         future::ready(Ok(svc))
     }
 }
 
+// Drives a single connection, shutting it down gracefully once the server
+// is shut down.
+#[pin_project]
+struct ServeConnection<IO> {
+    #[pin]
+    conn: hyper::server::conn::Connection<ServerIo<IO>, BoxService>,
+    shutdown: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
+    force: Pin<Box<dyn Future<Output = ()> + Send>>,
+    remote_addr: Option<SocketAddr>,
+    connection_error_handler: ConnectionErrorHandler,
+    // dropped once the connection is closed
+    _open: OpenConnection,
+}
+
+// Resolves once `rx` is signalled. The sender is only dropped without
+// sending if the server stopped because the incoming stream ended.
+async fn signalled(mut rx: tokio::sync::watch::Receiver<()>) {
+    if rx.changed().await.is_err() {
+        future::pending::<()>().await;
+    }
+}
+
+// Counts an open connection and holds the server's drain channel open.
+struct OpenConnection {
+    _drain: tokio::sync::mpsc::Sender<()>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl OpenConnection {
+    fn new(drain: tokio::sync::mpsc::Sender<()>, connections: Arc<AtomicUsize>) -> Self {
+        connections.fetch_add(1, Ordering::AcqRel);
+        OpenConnection {
+            _drain: drain,
+            connections,
+        }
+    }
+}
+
+impl Drop for OpenConnection {
+    fn drop(&mut self) {
+        self.connections.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<IO> Future for ServeConnection<IO>
+where
+    IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+
+        if let Some(shutdown) = this.shutdown {
+            if shutdown.as_mut().poll(cx).is_ready() {
+                this.conn.as_mut().graceful_shutdown();
+                *this.shutdown = None;
+            }
+        }
+
+        // dropping the connection cancels the calls still in flight
+        if this.force.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(());
+        }
+
+        if let Err(err) = ready!(this.conn.poll(cx)) {
+            this.connection_error_handler.report(ConnectionError::new(
+                ConnectionErrorKind::Connection,
+                *this.remote_addr,
+                err,
+            ));
+        }
+
+        Poll::Ready(())
+    }
+}
+
 #[derive(Default, Clone, Debug)]
 #[doc(hidden)]
 pub struct Unimplemented {
@@ -0,0 +1,438 @@
+use super::TraceInterceptor;
+use bytes::Buf;
+use http::{HeaderMap, Request};
+use http_body::Body;
+use pin_project::{pin_project, pinned_drop};
+use std::{
+    collections::HashMap,
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+/// Decides which calls get a span from [`Server::trace_fn`].
+///
+/// A call that is [sampled](Sampler::sample) gets its span when it starts.
+/// A call that was not sampled can still get a span once it has finished,
+/// if the sampler [keeps](Sampler::keep) it. Since the outcome is only known
+/// once the response has been sent, the span of such a call is created at
+/// that point and only contains an event recording the status and the
+/// elapsed time, not the events of the handler. The headers of calls that
+/// were not sampled are not kept, so the request the span is made from only
+/// has the method, URI and version of the call.
+///
+/// [`TraceSampler`] samples calls at a fixed rate and [`SizeSampler`] keeps
+/// the calls with large responses.
+///
+/// [`Server::trace_fn`]: super::Server::trace_fn
+pub trait Sampler: Send + Sync + 'static {
+    /// Returns `true` if the call to the method with the full `path` gets a
+    /// span when it starts.
+    fn sample(&self, path: &str) -> bool;
+
+    /// Returns `true` if `call`, which was not sampled, still gets a span.
+    ///
+    /// By default only calls that failed get one.
+    fn keep(&self, call: &FinishedCall<'_>) -> bool {
+        call.status().is_some()
+    }
+}
+
+/// A call that was not sampled, once its response has been sent.
+#[derive(Debug)]
+pub struct FinishedCall<'a> {
+    path: &'a str,
+    status: Option<&'a str>,
+    elapsed: Duration,
+    response_size: u64,
+}
+
+impl<'a> FinishedCall<'a> {
+    /// The full path of the method called.
+    pub fn path(&self) -> &'a str {
+        self.path
+    }
+
+    /// The `grpc-status` of a call that failed, `None` if it succeeded.
+    ///
+    /// This is `"error"` if the response body failed.
+    pub fn status(&self) -> Option<&'a str> {
+        self.status
+    }
+
+    /// The time from the start of the call until its response was sent.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// The number of bytes of the response body that were sent.
+    pub fn response_size(&self) -> u64 {
+        self.response_size
+    }
+}
+
+/// A [`Sampler`] that samples calls at a fixed rate, which can be overridden
+/// per method. Sampling is deterministic: with a rate of `0.01` every
+/// hundredth call of a method is sampled.
+///
+/// Calls that were not sampled still get a span when they fail, that is
+/// when the response carries a `grpc-status` other than `OK` in its
+/// headers or trailers, or when the call took longer than the
+/// [slow threshold](TraceSampler::slow_threshold).
+///
+/// ```
+/// # use tonic::transport::{server::TraceSampler, Server};
+/// # use std::time::Duration;
+/// let sampler = TraceSampler::new(1.0)
+///     .method_rate("/routeguide.RouteGuide/GetFeature", 0.01)
+///     .slow_threshold(Duration::from_millis(200));
+///
+/// let builder = Server::builder()
+///     .trace_fn(|_| tracing::info_span!("grpc"))
+///     .trace_sampler(sampler);
+/// ```
+pub struct TraceSampler {
+    default: Rate,
+    methods: HashMap<String, Rate>,
+    slow_threshold: Option<Duration>,
+}
+
+struct Rate {
+    rate: f64,
+    calls: AtomicU64,
+}
+
+impl TraceSampler {
+    /// Create a sampler that samples calls at `rate`, between `0.0` and
+    /// `1.0`.
+    pub fn new(rate: f64) -> Self {
+        TraceSampler {
+            default: Rate::new(rate),
+            methods: HashMap::new(),
+            slow_threshold: None,
+        }
+    }
+
+    /// Sample calls to the method with the full `path` at `rate` instead.
+    pub fn method_rate(mut self, path: impl Into<String>, rate: f64) -> Self {
+        self.methods.insert(path.into(), Rate::new(rate));
+        self
+    }
+
+    /// Give calls whose response takes longer than `threshold` a span even
+    /// if they were not sampled.
+    pub fn slow_threshold(self, threshold: Duration) -> Self {
+        TraceSampler {
+            slow_threshold: Some(threshold),
+            ..self
+        }
+    }
+}
+
+impl Sampler for TraceSampler {
+    fn sample(&self, path: &str) -> bool {
+        self.methods.get(path).unwrap_or(&self.default).sample()
+    }
+
+    fn keep(&self, call: &FinishedCall<'_>) -> bool {
+        let slow = match self.slow_threshold {
+            Some(threshold) => call.elapsed() >= threshold,
+            None => false,
+        };
+
+        call.status().is_some() || slow
+    }
+}
+
+/// A [`Sampler`] that gives a span to the calls with large responses.
+///
+/// No call is sampled when it starts. Once a call has finished, it gets a
+/// span if its response body had at least `threshold` bytes, or if it failed.
+///
+/// ```
+/// # use tonic::transport::{server::SizeSampler, Server};
+/// let builder = Server::builder()
+///     .trace_fn(|_| tracing::info_span!("grpc"))
+///     .trace_sampler(SizeSampler::new(1024 * 1024));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SizeSampler {
+    threshold: u64,
+}
+
+impl SizeSampler {
+    /// Create a sampler that keeps calls whose response body had at least
+    /// `threshold` bytes.
+    pub fn new(threshold: u64) -> Self {
+        SizeSampler { threshold }
+    }
+}
+
+impl Sampler for SizeSampler {
+    fn sample(&self, _path: &str) -> bool {
+        false
+    }
+
+    fn keep(&self, call: &FinishedCall<'_>) -> bool {
+        call.status().is_some() || call.response_size() >= self.threshold
+    }
+}
+
+impl Rate {
+    fn new(rate: f64) -> Self {
+        Rate {
+            rate: rate.clamp(0.0, 1.0),
+            calls: AtomicU64::new(0),
+        }
+    }
+
+    fn sample(&self) -> bool {
+        if self.rate >= 1.0 {
+            return true;
+        }
+
+        if self.rate <= 0.0 {
+            return false;
+        }
+
+        // Sample the calls at which the expected number of samples crosses
+        // the next whole number.
+        let n = self.calls.fetch_add(1, Ordering::Relaxed) as f64;
+        ((n + 1.0) * self.rate).floor() > (n * self.rate).floor()
+    }
+}
+
+// A call that was not sampled, which still gets a span once it turns out
+// to have failed or to be slow.
+pub(crate) struct Unsampled {
+    request: Request<()>,
+    trace_interceptor: TraceInterceptor,
+    sampler: Arc<dyn Sampler>,
+    start: Instant,
+    response_size: u64,
+}
+
+impl Unsampled {
+    pub(crate) fn new<B>(
+        req: &Request<B>,
+        trace_interceptor: TraceInterceptor,
+        sampler: Arc<dyn Sampler>,
+    ) -> Self {
+        let mut request = Request::new(());
+        *request.method_mut() = req.method().clone();
+        *request.uri_mut() = req.uri().clone();
+        *request.version_mut() = req.version();
+
+        Unsampled {
+            request,
+            trace_interceptor,
+            sampler,
+            start: Instant::now(),
+            response_size: 0,
+        }
+    }
+
+    pub(crate) fn finish(self, status: Option<&str>) {
+        let elapsed = self.start.elapsed();
+        let call = FinishedCall {
+            path: self.request.uri().path(),
+            status,
+            elapsed,
+            response_size: self.response_size,
+        };
+
+        if !self.sampler.keep(&call) {
+            return;
+        }
+
+        let span = (self.trace_interceptor)(&self.request);
+        tracing::info!(
+            parent: &span,
+            grpc.status = status.unwrap_or("0"),
+            elapsed_ms = elapsed.as_millis() as u64,
+            "unsampled call failed or was slow"
+        );
+    }
+}
+
+// The `grpc-status` of `headers`, unless it is missing or `OK`.
+fn failed_status(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("grpc-status")
+        .and_then(|status| status.to_str().ok())
+        .filter(|status| *status != "0")
+}
+
+/// The response body of a call that was not sampled, which finishes the
+/// call once the status in its headers or trailers is known.
+#[pin_project(PinnedDrop)]
+pub(crate) struct TailSampled<B> {
+    #[pin]
+    inner: B,
+    unsampled: Option<Unsampled>,
+}
+
+impl<B: Body> TailSampled<B> {
+    pub(crate) fn new(inner: B, headers: &HeaderMap, unsampled: Unsampled) -> Self {
+        let mut unsampled = Some(unsampled);
+
+        // Trailers-Only responses carry the status in their headers.
+        if let Some(status) = failed_status(headers) {
+            unsampled.take().unwrap().finish(Some(status));
+        } else if inner.is_end_stream() {
+            unsampled.take().unwrap().finish(None);
+        }
+
+        TailSampled { inner, unsampled }
+    }
+}
+
+impl<B: Body> Body for TailSampled<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let this = self.project();
+        let data = futures_util::ready!(this.inner.poll_data(cx));
+
+        match &data {
+            Some(Ok(data)) => {
+                if let Some(unsampled) = this.unsampled {
+                    unsampled.response_size += data.remaining() as u64;
+                }
+            }
+            Some(Err(_)) => {
+                if let Some(unsampled) = this.unsampled.take() {
+                    unsampled.finish(Some("error"));
+                }
+            }
+            None => {}
+        }
+
+        Poll::Ready(data)
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let this = self.project();
+        let trailers = futures_util::ready!(this.inner.poll_trailers(cx));
+
+        if let Some(unsampled) = this.unsampled.take() {
+            match &trailers {
+                Ok(Some(trailers)) => unsampled.finish(failed_status(trailers)),
+                Ok(None) => unsampled.finish(None),
+                Err(_) => unsampled.finish(Some("error")),
+            }
+        }
+
+        Poll::Ready(trailers)
+    }
+}
+
+#[pinned_drop]
+impl<B> PinnedDrop for TailSampled<B> {
+    fn drop(self: Pin<&mut Self>) {
+        // the response was not sent completely, which is only traced if slow
+        if let Some(unsampled) = self.project().unsampled.take() {
+            unsampled.finish(None);
+        }
+    }
+}
+
+impl fmt::Debug for TraceSampler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let methods = self
+            .methods
+            .iter()
+            .map(|(path, rate)| (path, rate.rate))
+            .collect::<HashMap<_, _>>();
+
+        f.debug_struct("TraceSampler")
+            .field("rate", &self.default.rate)
+            .field("methods", &methods)
+            .field("slow_threshold", &self.slow_threshold)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_at_rate() {
+        let sampler = TraceSampler::new(0.25).method_rate("/a.A/B", 0.0);
+
+        let sampled = (0..100).filter(|_| sampler.sample("/a.A/A")).count();
+        assert_eq!(sampled, 25);
+
+        assert!(!(0..100).any(|_| sampler.sample("/a.A/B")));
+        assert!((0..100).all(|_| TraceSampler::new(1.0).sample("/a.A/A")));
+    }
+
+    #[test]
+    fn keeps_failed_slow_and_large_calls() {
+        let call = |status, elapsed, response_size| FinishedCall {
+            path: "/a.A/A",
+            status,
+            elapsed: Duration::from_millis(elapsed),
+            response_size,
+        };
+
+        let sampler = TraceSampler::new(0.0).slow_threshold(Duration::from_millis(100));
+        assert!(!sampler.keep(&call(None, 10, 0)));
+        assert!(sampler.keep(&call(None, 100, 0)));
+        assert!(sampler.keep(&call(Some("14"), 10, 0)));
+
+        let sampler = SizeSampler::new(1024);
+        assert!(!sampler.sample("/a.A/A"));
+        assert!(!sampler.keep(&call(None, 10, 1023)));
+        assert!(sampler.keep(&call(None, 10, 1024)));
+        assert!(sampler.keep(&call(Some("14"), 10, 0)));
+    }
+
+    #[tokio::test]
+    async fn counts_response_size() {
+        let spans = Arc::new(AtomicU64::new(0));
+
+        let trace = |threshold| {
+            let spans = spans.clone();
+            let trace_interceptor: TraceInterceptor = Arc::new(move |_: &Request<()>| {
+                spans.fetch_add(1, Ordering::SeqCst);
+                tracing::Span::none()
+            });
+            let request = Request::post("/a.A/A").body(()).unwrap();
+            let unsampled = Unsampled::new(
+                &request,
+                trace_interceptor,
+                Arc::new(SizeSampler::new(threshold)),
+            );
+
+            TailSampled::new(hyper::Body::from(vec![0; 10]), &HeaderMap::new(), unsampled)
+        };
+
+        let mut body = trace(11);
+        while body.data().await.is_some() {}
+        body.trailers().await.unwrap();
+        assert_eq!(spans.load(Ordering::SeqCst), 0);
+
+        let mut body = trace(10);
+        while body.data().await.is_some() {}
+        body.trailers().await.unwrap();
+        assert_eq!(spans.load(Ordering::SeqCst), 1);
+    }
+}
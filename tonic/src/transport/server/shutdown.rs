@@ -0,0 +1,64 @@
+/// What happened while a server shut down.
+///
+/// This is returned by [`Router::serve_with_shutdown`] and
+/// [`Router::serve_with_incoming_shutdown`] once the shutdown completed.
+/// After the shutdown signal the server stops accepting connections and
+/// asks the open ones to close gracefully, which lets the calls in flight
+/// finish. With a [`drain_timeout`], the connections still open when it
+/// elapses are closed forcibly, cancelling their calls.
+///
+/// A server also stops accepting connections once its incoming stream
+/// ended, but leaves the open ones to close on their own, unless the
+/// shutdown signal asks them to close first. The summary then counts from
+/// the end of the incoming stream.
+///
+/// ```
+/// # use tonic::transport::server::ShutdownSummary;
+/// # fn example(summary: ShutdownSummary) {
+/// if summary.timed_out() {
+///     eprintln!("cancelled {} calls on shutdown", summary.calls_cancelled());
+/// }
+/// # }
+/// ```
+///
+/// [`Router::serve_with_shutdown`]: super::Router::serve_with_shutdown
+/// [`Router::serve_with_incoming_shutdown`]: super::Router::serve_with_incoming_shutdown
+/// [`drain_timeout`]: super::Server::drain_timeout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShutdownSummary {
+    pub(crate) connections_drained: usize,
+    pub(crate) connections_closed: usize,
+    pub(crate) calls_completed: usize,
+    pub(crate) calls_cancelled: usize,
+    pub(crate) timed_out: bool,
+}
+
+impl ShutdownSummary {
+    /// Number of connections that were open at the shutdown signal and
+    /// closed gracefully.
+    pub fn connections_drained(&self) -> usize {
+        self.connections_drained
+    }
+
+    /// Number of connections that were closed forcibly because the drain
+    /// timed out.
+    pub fn connections_closed(&self) -> usize {
+        self.connections_closed
+    }
+
+    /// Number of calls that completed after the shutdown signal.
+    pub fn calls_completed(&self) -> usize {
+        self.calls_completed
+    }
+
+    /// Number of calls that were cancelled because the drain timed out.
+    pub fn calls_cancelled(&self) -> usize {
+        self.calls_cancelled
+    }
+
+    /// Returns `true` if connections were still open when the
+    /// [`drain_timeout`](super::Server::drain_timeout) elapsed.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+}
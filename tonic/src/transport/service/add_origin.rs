@@ -1,16 +1,25 @@
+use super::path_prefix;
 use http::{Request, Uri};
-use std::task::{Context, Poll};
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
 use tower_service::Service;
 
 #[derive(Debug)]
 pub(crate) struct AddOrigin<T> {
     inner: T,
     origin: Uri,
+    path_prefix: Option<Arc<str>>,
 }
 
 impl<T> AddOrigin<T> {
-    pub(crate) fn new(inner: T, origin: Uri) -> Self {
-        Self { inner, origin }
+    pub(crate) fn new(inner: T, origin: Uri, path_prefix: Option<Arc<str>>) -> Self {
+        Self {
+            inner,
+            origin,
+            path_prefix,
+        }
     }
 }
 
@@ -41,6 +50,10 @@ where
         // Update the the request URI
         head.uri = http::Uri::from_parts(uri).expect("valid uri");
 
+        if let Some(prefix) = &self.path_prefix {
+            head.uri = path_prefix::prepend(head.uri, prefix);
+        }
+
         let request = Request::from_parts(head, body);
 
         self.inner.call(request)
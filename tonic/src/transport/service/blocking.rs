@@ -0,0 +1,75 @@
+use crate::Status;
+use http::Request;
+use std::{
+    collections::HashSet,
+    future::Future,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::runtime::Handle;
+use tower_service::Service;
+
+/// Marks the requests for the given methods, so that generated servers run
+/// their handlers on the blocking thread pool with [`spawn_handler`].
+///
+/// Only the handler runs there. Decoding the request and encoding the
+/// response stay on the runtime, like all other I/O of the call.
+#[derive(Debug, Clone)]
+pub(crate) struct MarkBlocking<S> {
+    inner: S,
+    methods: Arc<HashSet<String>>,
+}
+
+impl<S> MarkBlocking<S> {
+    pub(crate) fn new(inner: S, methods: Arc<HashSet<String>>) -> Self {
+        Self { inner, methods }
+    }
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for MarkBlocking<S>
+where
+    S: Service<Request<ReqBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        if !self.methods.is_empty() && self.methods.contains(req.uri().path()) {
+            req.extensions_mut().insert(BlockingHandler);
+        }
+
+        self.inner.call(req)
+    }
+}
+
+/// Marks a request whose handler runs on the blocking thread pool.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BlockingHandler;
+
+/// Returns `true` if the handler of `request` runs on the blocking pool.
+pub(crate) fn is_blocking<T>(request: &crate::Request<T>) -> bool {
+    request.extensions().get::<BlockingHandler>().is_some()
+}
+
+/// Run `handler` on a thread of the blocking pool.
+///
+/// The thread drives the handler future with [`Handle::block_on`], so the
+/// handler can still await, but it holds the thread until it is done.
+pub(crate) fn spawn_handler<F, R>(handler: F) -> super::super::BoxFuture<R, Status>
+where
+    F: Future<Output = Result<R, Status>> + Send + 'static,
+    R: Send + 'static,
+{
+    let handle = Handle::current();
+    let task = tokio::task::spawn_blocking(move || handle.block_on(handler));
+
+    Box::pin(async move {
+        task.await
+            .unwrap_or_else(|err| Err(Status::from_error(err.into())))
+    })
+}
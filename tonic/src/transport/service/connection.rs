@@ -1,5 +1,11 @@
 use super::super::BoxFuture;
-use super::{grpc_timeout::GrpcTimeout, reconnect::Reconnect, AddOrigin, UserAgent};
+use super::{
+    grpc_timeout::GrpcTimeout,
+    idle::IdleTimeout,
+    reconnect::Reconnect,
+    timings::{ConnectTimings, RecordTimings, TimedConnect},
+    AddOrigin, ProtoContentType, UserAgent,
+};
 use crate::{body::BoxBody, transport::Endpoint};
 use http::Uri;
 use hyper::client::conn::Builder;
@@ -27,11 +33,11 @@ pub(crate) struct Connection {
 }
 
 impl Connection {
-    fn new<C>(connector: C, endpoint: Endpoint, is_lazy: bool) -> Self
+    fn new<C>(connector: C, endpoint: Endpoint, timings: ConnectTimings, is_lazy: bool) -> Self
     where
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::Error> + Send,
-        C::Future: Unpin + Send,
+        C::Future: Unpin + Send + 'static,
         C::Response: AsyncRead + AsyncWrite + HyperConnection + Unpin + Send + 'static,
     {
         let mut settings = Builder::new()
@@ -54,14 +60,17 @@ impl Connection {
         }
 
         let stack = ServiceBuilder::new()
-            .layer_fn(|s| AddOrigin::new(s, endpoint.uri.clone()))
+            .layer_fn(|s| AddOrigin::new(s, endpoint.uri.clone(), endpoint.path_prefix.clone()))
             .layer_fn(|s| UserAgent::new(s, endpoint.user_agent.clone()))
+            .layer_fn(|s| ProtoContentType::new(s, endpoint.grpc_proto_content_type))
             .layer_fn(|s| GrpcTimeout::new(s, endpoint.timeout))
             .option_layer(endpoint.concurrency_limit.map(ConcurrencyLimitLayer::new))
             .option_layer(endpoint.rate_limit.map(|(l, d)| RateLimitLayer::new(l, d)))
+            .layer_fn(|s| RecordTimings::new(s, timings.clone()))
             .into_inner();
 
-        let connector = HyperConnect::new(connector, settings);
+        let connector = HyperConnect::new(TimedConnect::new(connector, timings.clone()), settings);
+        let connector = IdleTimeout::new(connector, endpoint.idle_connection_timeout);
         let conn = Reconnect::new(connector, endpoint.uri.clone(), is_lazy);
 
         let inner = stack.layer(conn);
@@ -71,24 +80,30 @@ impl Connection {
         }
     }
 
-    pub(crate) async fn connect<C>(connector: C, endpoint: Endpoint) -> Result<Self, crate::Error>
+    pub(crate) async fn connect<C>(
+        connector: C,
+        endpoint: Endpoint,
+        timings: ConnectTimings,
+    ) -> Result<Self, crate::Error>
     where
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::Error> + Send,
-        C::Future: Unpin + Send,
+        C::Future: Unpin + Send + 'static,
         C::Response: AsyncRead + AsyncWrite + HyperConnection + Unpin + Send + 'static,
     {
-        Self::new(connector, endpoint, false).ready_oneshot().await
+        Self::new(connector, endpoint, timings, false)
+            .ready_oneshot()
+            .await
     }
 
-    pub(crate) fn lazy<C>(connector: C, endpoint: Endpoint) -> Self
+    pub(crate) fn lazy<C>(connector: C, endpoint: Endpoint, timings: ConnectTimings) -> Self
     where
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::Error> + Send,
-        C::Future: Unpin + Send,
+        C::Future: Unpin + Send + 'static,
         C::Response: AsyncRead + AsyncWrite + HyperConnection + Unpin + Send + 'static,
     {
-        Self::new(connector, endpoint, true)
+        Self::new(connector, endpoint, timings, true)
     }
 }
 
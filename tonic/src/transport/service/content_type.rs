@@ -0,0 +1,71 @@
+use http::{header::CONTENT_TYPE, HeaderMap, HeaderValue, Request};
+use std::task::{Context, Poll};
+use tower_service::Service;
+
+const GRPC: &str = "application/grpc";
+const GRPC_PROTO: &str = "application/grpc+proto";
+
+/// Replace a bare `application/grpc` content-type with
+/// `application/grpc+proto`. Other content-types, for example of calls using
+/// a codec other than protobuf, are left alone.
+pub(crate) fn set_proto_subtype(headers: &mut HeaderMap) {
+    if let Some(content_type) = headers.get_mut(CONTENT_TYPE) {
+        if content_type == GRPC {
+            *content_type = HeaderValue::from_static(GRPC_PROTO);
+        }
+    }
+}
+
+/// Sends requests with the `application/grpc+proto` content-type.
+#[derive(Debug)]
+pub(crate) struct ProtoContentType<T> {
+    inner: T,
+    enabled: bool,
+}
+
+impl<T> ProtoContentType<T> {
+    pub(crate) fn new(inner: T, enabled: bool) -> Self {
+        Self { inner, enabled }
+    }
+}
+
+impl<T, ReqBody> Service<Request<ReqBody>> for ProtoContentType<T>
+where
+    T: Service<Request<ReqBody>>,
+{
+    type Response = T::Response;
+    type Error = T::Error;
+    type Future = T::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        if self.enabled {
+            set_proto_subtype(req.headers_mut());
+        }
+
+        self.inner.call(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_replaces_bare_grpc() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(GRPC));
+        set_proto_subtype(&mut headers);
+        assert_eq!(headers[CONTENT_TYPE], GRPC_PROTO);
+
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/grpc+json"),
+        );
+        set_proto_subtype(&mut headers);
+        assert_eq!(headers[CONTENT_TYPE], "application/grpc+json");
+    }
+}
@@ -1,5 +1,6 @@
 use super::super::service;
 use super::connection::Connection;
+use super::ConnectTimings;
 use crate::transport::Endpoint;
 
 use std::{
@@ -33,16 +34,14 @@ impl<K: Hash + Eq + Clone> Stream for DynamicServiceStream<K> {
             Poll::Pending | Poll::Ready(None) => Poll::Pending,
             Poll::Ready(Some(change)) => match change {
                 Change::Insert(k, endpoint) => {
-                    let mut http = hyper::client::connect::HttpConnector::new();
-                    http.set_nodelay(endpoint.tcp_nodelay);
-                    http.set_keepalive(endpoint.tcp_keepalive);
-                    http.enforce_http(false);
+                    let timings = ConnectTimings::default();
+                    let http = endpoint.http_connector(timings.clone());
                     #[cfg(feature = "tls")]
                     let connector = service::connector(http, endpoint.tls.clone());
 
                     #[cfg(not(feature = "tls"))]
                     let connector = service::connector(http);
-                    let connection = Connection::lazy(connector, endpoint);
+                    let connection = Connection::lazy(connector, endpoint, timings);
                     let change = Ok(Change::Insert(k, connection));
                     Poll::Ready(Some(change))
                 }
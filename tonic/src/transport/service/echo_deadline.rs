@@ -0,0 +1,120 @@
+use crate::metadata::GRPC_TIMEOUT_HEADER;
+use crate::request::{duration_to_grpc_timeout, try_parse_grpc_timeout};
+use http::{HeaderValue, Request, Response};
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower_service::Service;
+
+/// The `grpc-timeout` the server received, echoed as is.
+const RECEIVED_TIMEOUT_HEADER: &str = "tonic-received-timeout";
+/// The time left until the deadline of the call once it was answered.
+const DEADLINE_REMAINING_HEADER: &str = "tonic-deadline-remaining";
+
+/// Echoes the deadline the server observed for each call in the response
+/// headers.
+///
+/// The received `grpc-timeout` is echoed in `tonic-received-timeout`, and the
+/// time left until the deadline, which also accounts for the server timeout,
+/// in `tonic-deadline-remaining`, in the `grpc-timeout` format. Nothing is
+/// echoed for calls without a deadline.
+#[derive(Debug, Clone)]
+pub(crate) struct EchoDeadline<S> {
+    inner: S,
+    enabled: bool,
+    server_timeout: Option<Duration>,
+}
+
+impl<S> EchoDeadline<S> {
+    pub(crate) fn new(inner: S, enabled: bool, server_timeout: Option<Duration>) -> Self {
+        Self {
+            inner,
+            enabled,
+            server_timeout,
+        }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for EchoDeadline<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let echo = if self.enabled {
+            let received = req.headers().get(GRPC_TIMEOUT_HEADER).cloned();
+            let client_timeout = try_parse_grpc_timeout(req.headers()).unwrap_or(None);
+
+            let timeout = match (client_timeout, self.server_timeout) {
+                (Some(client), Some(server)) => Some(client.min(server)),
+                (client, server) => client.or(server),
+            };
+
+            Some(Echo {
+                received,
+                deadline: timeout.map(|timeout| Instant::now() + timeout),
+            })
+        } else {
+            None
+        };
+
+        ResponseFuture {
+            inner: self.inner.call(req),
+            echo,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Echo {
+    received: Option<HeaderValue>,
+    deadline: Option<Instant>,
+}
+
+#[pin_project]
+#[derive(Debug)]
+pub(crate) struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+    echo: Option<Echo>,
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = Result<Response<ResBody>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut response = futures_util::ready!(this.inner.poll(cx))?;
+
+        if let Some(echo) = this.echo.take() {
+            let headers = response.headers_mut();
+
+            if let Some(received) = echo.received {
+                headers.insert(RECEIVED_TIMEOUT_HEADER, received);
+            }
+
+            if let Some(deadline) = echo.deadline {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                let value = HeaderValue::from_str(&duration_to_grpc_timeout(remaining))
+                    .expect("grpc-timeout is a valid header value");
+                headers.insert(DEADLINE_REMAINING_HEADER, value);
+            }
+        }
+
+        Poll::Ready(Ok(response))
+    }
+}
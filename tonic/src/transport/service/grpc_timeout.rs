@@ -1,13 +1,13 @@
-use crate::metadata::GRPC_TIMEOUT_HEADER;
+use crate::request::{try_parse_grpc_timeout, Deadline};
 use crate::util::{OptionPin, OptionPinProj};
-use http::{HeaderMap, HeaderValue, Request};
+use http::Request;
 use pin_project::pin_project;
 use std::{
     fmt,
     future::Future,
     pin::Pin,
     task::{Context, Poll},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tokio::time::Sleep;
 use tower_service::Service;
@@ -40,7 +40,7 @@ where
         self.inner.poll_ready(cx).map_err(Into::into)
     }
 
-    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
         let client_timeout = try_parse_grpc_timeout(req.headers()).unwrap_or_else(|e| {
             tracing::trace!("Error parsing `grpc-timeout` header {:?}", e);
             None
@@ -57,6 +57,11 @@ where
             }
         };
 
+        if let Some(timeout) = timeout_duration {
+            req.extensions_mut()
+                .insert(Deadline(Instant::now() + timeout));
+        }
+
         ResponseFuture {
             inner: self.inner.call(req),
             sleep: timeout_duration
@@ -98,59 +103,6 @@ where
     }
 }
 
-const SECONDS_IN_HOUR: u64 = 60 * 60;
-const SECONDS_IN_MINUTE: u64 = 60;
-
-/// Tries to parse the `grpc-timeout` header if it is present. If we fail to parse, returns
-/// the value we attempted to parse.
-///
-/// Follows the [gRPC over HTTP2 spec](https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-HTTP2.md).
-fn try_parse_grpc_timeout(
-    headers: &HeaderMap<HeaderValue>,
-) -> Result<Option<Duration>, &HeaderValue> {
-    match headers.get(GRPC_TIMEOUT_HEADER) {
-        Some(val) => {
-            let (timeout_value, timeout_unit) = val
-                .to_str()
-                .map_err(|_| val)
-                .and_then(|s| if s.is_empty() { Err(val) } else { Ok(s) })?
-                // `HeaderValue::to_str` only returns `Ok` if the header contains ASCII so this
-                // `split_at` will never panic from trying to split in the middle of a character.
-                // See https://docs.rs/http/0.2.4/http/header/struct.HeaderValue.html#method.to_str
-                //
-                // `len - 1` also wont panic since we just checked `s.is_empty`.
-                .split_at(val.len() - 1);
-
-            // gRPC spec specifies `TimeoutValue` will be at most 8 digits
-            // Caping this at 8 digits also prevents integer overflow from ever occurring
-            if timeout_value.len() > 8 {
-                return Err(val);
-            }
-
-            let timeout_value: u64 = timeout_value.parse().map_err(|_| val)?;
-
-            let duration = match timeout_unit {
-                // Hours
-                "H" => Duration::from_secs(timeout_value * SECONDS_IN_HOUR),
-                // Minutes
-                "M" => Duration::from_secs(timeout_value * SECONDS_IN_MINUTE),
-                // Seconds
-                "S" => Duration::from_secs(timeout_value),
-                // Milliseconds
-                "m" => Duration::from_millis(timeout_value),
-                // Microseconds
-                "u" => Duration::from_micros(timeout_value),
-                // Nanoseconds
-                "n" => Duration::from_nanos(timeout_value),
-                _ => return Err(val),
-            };
-
-            Ok(Some(duration))
-        }
-        None => Ok(None),
-    }
-}
-
 /// Error returned if a request didn't complete within the configured timeout.
 ///
 /// Timeouts can be configured either with [`Endpoint::timeout`], [`Server::timeout`], or by
@@ -162,6 +114,12 @@ fn try_parse_grpc_timeout(
 #[derive(Debug)]
 pub struct TimeoutExpired(());
 
+impl TimeoutExpired {
+    pub(crate) fn new() -> Self {
+        TimeoutExpired(())
+    }
+}
+
 impl fmt::Display for TimeoutExpired {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Timeout expired")
@@ -174,6 +132,8 @@ impl std::error::Error for TimeoutExpired {}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::metadata::GRPC_TIMEOUT_HEADER;
+    use http::{HeaderMap, HeaderValue};
     use quickcheck::{Arbitrary, Gen};
     use quickcheck_macros::quickcheck;
 
@@ -0,0 +1,272 @@
+use super::super::BoxFuture;
+use http::HeaderMap;
+use http_body::Body;
+use pin_project::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, Weak},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower_service::Service;
+
+/// Closes the connections made by `M` once no call was made on them for the
+/// idle timeout.
+///
+/// A call is in flight until its response body ends. The guard counting it is
+/// put in the extensions of the response, and the client moves it into the
+/// body with [`InFlightBody::wrap`], so a streaming response keeps its
+/// connection open for as long as it streams.
+///
+/// A closed connection fails `poll_ready`, which makes [`Reconnect`] open a
+/// new one the next time the endpoint is used.
+///
+/// [`Reconnect`]: super::reconnect::Reconnect
+#[derive(Debug)]
+pub(crate) struct IdleTimeout<M> {
+    inner: M,
+    timeout: Option<Duration>,
+}
+
+impl<M> IdleTimeout<M> {
+    pub(crate) fn new(inner: M, timeout: Option<Duration>) -> Self {
+        Self { inner, timeout }
+    }
+}
+
+impl<M, Target> Service<Target> for IdleTimeout<M>
+where
+    M: Service<Target>,
+    M::Response: Send + 'static,
+{
+    type Response = Idle<M::Response>;
+    type Error = M::Error;
+    type Future = IdleFuture<M::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, target: Target) -> Self::Future {
+        IdleFuture {
+            inner: self.inner.call(target),
+            timeout: self.timeout,
+        }
+    }
+}
+
+#[pin_project]
+#[derive(Debug)]
+pub(crate) struct IdleFuture<F> {
+    #[pin]
+    inner: F,
+    timeout: Option<Duration>,
+}
+
+impl<F, S, E> Future for IdleFuture<F>
+where
+    F: Future<Output = Result<S, E>>,
+    S: Send + 'static,
+{
+    type Output = Result<Idle<S>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let service = futures_util::ready!(this.inner.poll(cx))?;
+        Poll::Ready(Ok(Idle::new(service, *this.timeout)))
+    }
+}
+
+/// A connection that is closed once it has been idle for too long.
+pub(crate) struct Idle<S> {
+    shared: Arc<Mutex<Shared<S>>>,
+}
+
+struct Shared<S> {
+    // `None` once the connection was closed
+    service: Option<S>,
+    last_used: Instant,
+    in_flight: usize,
+}
+
+impl<S: Send + 'static> Idle<S> {
+    fn new(service: S, timeout: Option<Duration>) -> Self {
+        let shared = Arc::new(Mutex::new(Shared {
+            service: Some(service),
+            last_used: Instant::now(),
+            in_flight: 0,
+        }));
+
+        if let Some(timeout) = timeout {
+            tokio::spawn(close_when_idle(Arc::downgrade(&shared), timeout));
+        }
+
+        Self { shared }
+    }
+}
+
+async fn close_when_idle<S>(shared: Weak<Mutex<Shared<S>>>, timeout: Duration) {
+    loop {
+        let deadline = {
+            let shared = match shared.upgrade() {
+                Some(shared) => shared,
+                // the connection was dropped
+                None => return,
+            };
+            let mut shared = shared.lock().unwrap();
+
+            if shared.in_flight > 0 {
+                Instant::now() + timeout
+            } else if shared.last_used.elapsed() >= timeout {
+                tracing::debug!("closing idle connection");
+                // dropping the handle lets the connection shut down
+                let service = shared.service.take();
+                drop(shared);
+                drop(service);
+                return;
+            } else {
+                shared.last_used + timeout
+            }
+        };
+
+        tokio::time::sleep_until(deadline.into()).await;
+    }
+}
+
+impl<S, Request, ResBody> Service<Request> for Idle<S>
+where
+    S: Service<Request, Response = http::Response<ResBody>> + Send + 'static,
+    S::Error: Into<crate::Error>,
+    S::Future: Send + 'static,
+    ResBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = crate::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let mut shared = self.shared.lock().unwrap();
+        // a ready connection is about to be used
+        shared.last_used = Instant::now();
+
+        match shared.service {
+            Some(ref mut service) => service.poll_ready(cx).map_err(Into::into),
+            None => Poll::Ready(Err(IdleClosed.into())),
+        }
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let mut shared = self.shared.lock().unwrap();
+
+        let future = match shared.service {
+            Some(ref mut service) => service.call(request),
+            None => return Box::pin(async { Err(IdleClosed.into()) }),
+        };
+
+        shared.in_flight += 1;
+        let in_flight = InFlight(self.shared.clone());
+
+        Box::pin(async move {
+            let mut response = future.await.map_err(Into::into)?;
+            // the call stays in flight while the body is read
+            response.extensions_mut().insert(InFlightCall {
+                _guard: Box::new(in_flight),
+            });
+            Ok(response)
+        })
+    }
+}
+
+impl<S> fmt::Debug for Idle<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Idle").finish()
+    }
+}
+
+/// Counts a call as in flight until it is dropped.
+struct InFlight<S>(Arc<Mutex<Shared<S>>>);
+
+impl<S> Drop for InFlight<S> {
+    fn drop(&mut self) {
+        let mut shared = self.0.lock().unwrap();
+        shared.in_flight -= 1;
+        shared.last_used = Instant::now();
+    }
+}
+
+/// The guard of a call in flight on an [`Idle`] connection, type erased so
+/// the client can take it from the response extensions.
+pub(crate) struct InFlightCall {
+    _guard: Box<dyn Send + Sync>,
+}
+
+/// A response body that counts its call as in flight until it ends.
+#[pin_project]
+pub(crate) struct InFlightBody<B> {
+    #[pin]
+    inner: B,
+    in_flight: Option<InFlightCall>,
+}
+
+impl<B> InFlightBody<B> {
+    /// Move the guard of the call in flight from the extensions of `response`
+    /// into its body.
+    pub(crate) fn wrap(mut response: http::Response<B>) -> http::Response<Self> {
+        let in_flight = response.extensions_mut().remove::<InFlightCall>();
+        response.map(|inner| InFlightBody { inner, in_flight })
+    }
+}
+
+impl<B: Body> Body for InFlightBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let this = self.project();
+        let data = futures_util::ready!(this.inner.poll_data(cx));
+
+        if let Some(Err(_)) = data {
+            this.in_flight.take();
+        }
+
+        Poll::Ready(data)
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let this = self.project();
+        let trailers = futures_util::ready!(this.inner.poll_trailers(cx));
+
+        // the trailers end the body
+        this.in_flight.take();
+        Poll::Ready(trailers)
+    }
+}
+
+impl<B> fmt::Debug for InFlightBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InFlightBody").finish()
+    }
+}
+
+#[derive(Debug)]
+struct IdleClosed;
+
+impl fmt::Display for IdleClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "connection closed after being idle")
+    }
+}
+
+impl std::error::Error for IdleClosed {}
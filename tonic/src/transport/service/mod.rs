@@ -1,24 +1,43 @@
 mod add_origin;
+mod blocking;
 mod connection;
 mod connector;
+mod content_type;
 mod discover;
+mod echo_deadline;
 mod grpc_timeout;
+mod idle;
 mod io;
+pub(crate) mod path_prefix;
+mod pending;
 mod reconnect;
+mod resolver;
 mod router;
+mod stream_rate;
+mod timings;
 #[cfg(feature = "tls")]
 mod tls;
+mod tos;
 mod user_agent;
 
 pub(crate) use self::add_origin::AddOrigin;
+pub(crate) use self::blocking::{is_blocking, spawn_handler, MarkBlocking};
 pub(crate) use self::connection::Connection;
 pub(crate) use self::connector::connector;
+pub(crate) use self::content_type::{set_proto_subtype, ProtoContentType};
 pub(crate) use self::discover::DynamicServiceStream;
+pub(crate) use self::echo_deadline::EchoDeadline;
 pub(crate) use self::grpc_timeout::GrpcTimeout;
+pub(crate) use self::idle::InFlightBody;
 pub(crate) use self::io::ServerIo;
+pub(crate) use self::pending::{Dequeue, PendingRequests};
+pub(crate) use self::resolver::ResolverOverrides;
 pub(crate) use self::router::{Or, Routes};
+pub(crate) use self::stream_rate::{StreamRate, StreamRateLimit};
+pub(crate) use self::timings::{ConnectTimings, TimedResolver};
 #[cfg(feature = "tls")]
 pub(crate) use self::tls::{TlsAcceptor, TlsConnector};
+pub(crate) use self::tos::{set_tos, SetTos};
 pub(crate) use self::user_agent::UserAgent;
 
 pub use self::grpc_timeout::TimeoutExpired;
@@ -0,0 +1,100 @@
+use crate::transport::Error;
+use http::uri::{PathAndQuery, Uri};
+use std::sync::Arc;
+
+/// Normalize a path prefix to start with a `/` and not end with one,
+/// returning `None` for an empty prefix.
+///
+/// Fails if the prefix is not a valid URI path, including prefixes with a
+/// query or a fragment, which would move the method path out of the path.
+pub(crate) fn normalize(prefix: &str) -> Result<Option<Arc<str>>, Error> {
+    let prefix = prefix.trim_matches('/');
+
+    if prefix.is_empty() {
+        return Ok(None);
+    }
+
+    let prefix = format!("/{}", prefix);
+    let valid = !prefix.contains(['?', '#']) && prefix.parse::<PathAndQuery>().is_ok();
+
+    if valid {
+        Ok(Some(prefix.into()))
+    } else {
+        Err(Error::new_invalid_path_prefix())
+    }
+}
+
+/// Prepend `prefix` to the path of `uri`.
+pub(crate) fn prepend(uri: Uri, prefix: &str) -> Uri {
+    let path_and_query = match uri.path_and_query() {
+        Some(path_and_query) => format!("{}{}", prefix, path_and_query),
+        None => prefix.to_string(),
+    };
+
+    replace_path(uri, &path_and_query)
+}
+
+/// Strip `prefix` from the path of `uri`, leaving paths without it alone.
+pub(crate) fn strip(uri: Uri, prefix: &str) -> Uri {
+    let stripped = uri
+        .path_and_query()
+        .map(PathAndQuery::as_str)
+        .and_then(|path_and_query| path_and_query.strip_prefix(prefix))
+        .filter(|rest| rest.starts_with('/'))
+        .map(str::to_owned);
+
+    match stripped {
+        Some(rest) => replace_path(uri, &rest),
+        None => uri,
+    }
+}
+
+fn replace_path(uri: Uri, path_and_query: &str) -> Uri {
+    let mut parts = uri.into_parts();
+    parts.path_and_query = Some(
+        path_and_query
+            .parse()
+            .expect("a prefixed path is a valid path"),
+    );
+    Uri::from_parts(parts).expect("valid uri")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_slashes() {
+        assert_eq!(normalize("v1").unwrap().as_deref(), Some("/v1"));
+        assert_eq!(normalize("/api/v1/").unwrap().as_deref(), Some("/api/v1"));
+        assert_eq!(normalize("/").unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_invalid_prefixes() {
+        assert!(normalize("v 1").is_err());
+        assert!(normalize("v1?x").is_err());
+        assert!(normalize("v1#a").is_err());
+    }
+
+    #[test]
+    fn strips_matching_prefix_only() {
+        let strip = |uri: &str| strip(uri.parse().unwrap(), "/v1").to_string();
+
+        assert_eq!(strip("/v1/test.Test/UnaryCall"), "/test.Test/UnaryCall");
+        assert_eq!(strip("/test.Test/UnaryCall"), "/test.Test/UnaryCall");
+        assert_eq!(
+            strip("/v10/test.Test/UnaryCall"),
+            "/v10/test.Test/UnaryCall"
+        );
+    }
+
+    #[test]
+    fn prepends_prefix() {
+        let uri = "http://example.com/test.Test/UnaryCall".parse().unwrap();
+        assert_eq!(
+            prepend(uri, "/v1").to_string(),
+            "http://example.com/v1/test.Test/UnaryCall"
+        );
+    }
+}
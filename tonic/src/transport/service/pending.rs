@@ -0,0 +1,73 @@
+use http::Request;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+use tower_service::Service;
+
+/// Counts the requests that are queued in a channel's buffer.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PendingRequests {
+    count: Arc<AtomicUsize>,
+}
+
+impl PendingRequests {
+    pub(crate) fn len(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+
+    /// Mark a request as queued until the returned guard is dropped.
+    pub(crate) fn enqueue(&self) -> PendingGuard {
+        self.count.fetch_add(1, Ordering::AcqRel);
+        PendingGuard {
+            count: self.count.clone(),
+        }
+    }
+}
+
+/// Travels with a queued request in its extensions and is dropped once the
+/// request leaves the queue, either to be dispatched or because it failed.
+#[derive(Debug)]
+pub(crate) struct PendingGuard {
+    count: Arc<AtomicUsize>,
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        self.count.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Sits behind the buffer and removes requests from the pending count as
+/// they are dispatched.
+#[derive(Debug)]
+pub(crate) struct Dequeue<S> {
+    inner: S,
+}
+
+impl<S> Dequeue<S> {
+    pub(crate) fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for Dequeue<S>
+where
+    S: Service<Request<ReqBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        drop(req.extensions_mut().remove::<PendingGuard>());
+        self.inner.call(req)
+    }
+}
@@ -0,0 +1,60 @@
+use http::Uri;
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_service::Service;
+
+/// Redirects connections to statically configured addresses.
+///
+/// Only the address that is dialed is replaced, the endpoint uri is left
+/// untouched so the `:authority` header and TLS server name still use the
+/// original host.
+#[derive(Debug, Clone)]
+pub(crate) struct ResolverOverrides<C> {
+    inner: C,
+    overrides: Option<Arc<HashMap<String, SocketAddr>>>,
+}
+
+impl<C> ResolverOverrides<C> {
+    pub(crate) fn new(inner: C, overrides: Option<Arc<HashMap<String, SocketAddr>>>) -> Self {
+        Self { inner, overrides }
+    }
+}
+
+impl<C> Service<Uri> for ResolverOverrides<C>
+where
+    C: Service<Uri>,
+{
+    type Response = C::Response;
+    type Error = C::Error;
+    type Future = C::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let addr = self
+            .overrides
+            .as_ref()
+            .and_then(|overrides| overrides.get(uri.host()?));
+
+        let uri = match addr {
+            Some(addr) => {
+                let mut parts = uri.into_parts();
+                parts.authority = Some(
+                    addr.to_string()
+                        .parse()
+                        .expect("socket address is a valid authority"),
+                );
+                Uri::from_parts(parts).expect("valid uri")
+            }
+            None => uri,
+        };
+
+        self.inner.call(uri)
+    }
+}
@@ -1,127 +1,218 @@
+use super::super::{server::Unimplemented, BoxFuture};
+use crate::{body::BoxBody, util::PathMap};
 use futures_util::{
     future::Either,
     future::{MapErr, TryFutureExt},
 };
+use http::Response;
+use hyper::Body;
 use std::{
     fmt,
+    future::Future,
+    marker::PhantomData,
     sync::Arc,
     task::{Context, Poll},
 };
 use tower_service::Service;
 
+/// Routes requests to services by the service name in their path.
+///
+/// The index of each service is looked up in a map built once when the
+/// services are added, which is scanned for a few services and hashed for
+/// more. With up to [`PathMap::SCAN_LIMIT`] services a request is passed down
+/// the typed chain of routes without boxing its future. Walking that chain
+/// costs more than boxing for more services, so their requests are called on
+/// the boxed route at their index instead, and the cost of routing a request
+/// does not grow with the number of services. Requests to services that were
+/// not added are routed to the boxed fallback.
 #[doc(hidden)]
-#[derive(Debug)]
 pub struct Routes<A, B, Request> {
+    names: Arc<PathMap>,
     routes: Or<A, B, Request>,
+    boxed: Vec<BoxRoute<Request>>,
+    fallback: BoxRoute<Request>,
 }
 
-impl<A, B, Request> Routes<A, B, Request> {
-    pub(crate) fn new(
-        predicate: impl Fn(&Request) -> bool + Send + Sync + 'static,
-        a: A,
-        b: B,
-    ) -> Self {
-        let routes = Or::new(predicate, a, b);
-        Self { routes }
+impl<A, B, Request: 'static> Routes<A, B, Request> {
+    pub(crate) fn new(name: &'static str, a: A, b: B) -> Self
+    where
+        A: Service<Request, Response = Response<BoxBody>> + Clone + Send + 'static,
+        A::Future: Send + 'static,
+        A::Error: Into<crate::Error>,
+        B: Service<Request, Response = Response<BoxBody>> + Clone + Send + 'static,
+        B::Future: Send + 'static,
+        B::Error: Into<crate::Error>,
+    {
+        let mut names = PathMap::new();
+        names.insert(name, 0);
+
+        Self {
+            names: Arc::new(names),
+            boxed: vec![Box::new(Route(a.clone()))],
+            fallback: Box::new(Route(b.clone())),
+            routes: Or::new(0, a, b),
+        }
     }
-}
 
-impl<A, B, Request> Routes<A, B, Request> {
+    /// Add a route to `service` for requests to the service `name`, replacing
+    /// any route for that name added before.
     pub(crate) fn push<C>(
         self,
-        predicate: impl Fn(&Request) -> bool + Send + Sync + 'static,
-        route: C,
-    ) -> Routes<C, Or<A, B, Request>, Request> {
-        let routes = Or::new(predicate, route, self.routes);
-        Routes { routes }
+        name: &'static str,
+        service: C,
+    ) -> Routes<C, Or<A, B, Request>, Request>
+    where
+        C: Service<Request, Response = Response<BoxBody>> + Clone + Send + 'static,
+        C::Future: Send + 'static,
+        C::Error: Into<crate::Error>,
+    {
+        let Self {
+            mut names,
+            routes,
+            mut boxed,
+            fallback,
+        } = self;
+
+        let index = routes.index + 1;
+        Arc::make_mut(&mut names).insert(name, index);
+        boxed.push(Box::new(Route(service.clone())));
+
+        Routes {
+            names,
+            routes: Or::new(index, service, routes),
+            boxed,
+            fallback,
+        }
+    }
+
+    /// Route the requests to services that were not added to `service`,
+    /// replacing the fallback answering them with `UNIMPLEMENTED`.
+    pub(crate) fn fallback<F>(self, service: F) -> Self
+    where
+        F: Service<Request, Response = Response<BoxBody>> + Clone + Send + 'static,
+        F::Future: Send + 'static,
+        F::Error: Into<crate::Error>,
+    {
+        Self {
+            fallback: Box::new(Route(service)),
+            ..self
+        }
     }
 }
 
-impl<A, B, Request> Service<Request> for Routes<A, B, Request>
+impl<A, B, ReqBody> Service<http::Request<ReqBody>> for Routes<A, B, http::Request<ReqBody>>
 where
-    A: Service<Request>,
-    A::Future: Send + 'static,
-    A::Error: Into<crate::Error>,
-    B: Service<Request, Response = A::Response>,
-    B::Future: Send + 'static,
-    B::Error: Into<crate::Error>,
+    Or<A, B, http::Request<ReqBody>>: Dispatch<http::Request<ReqBody>>,
 {
-    type Response = A::Response;
+    type Response = Response<BoxBody>;
     type Error = crate::Error;
-    type Future = <Or<A, B, Request> as Service<Request>>::Future;
+    type Future = Either<
+        <Or<A, B, http::Request<ReqBody>> as Dispatch<http::Request<ReqBody>>>::Future,
+        BoxFuture<Self::Response, Self::Error>,
+    >;
 
     fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         Ok(()).into()
     }
 
-    fn call(&mut self, req: Request) -> Self::Future {
-        self.routes.call(req)
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let route = service_name(req.uri().path()).and_then(|name| self.names.get(name));
+
+        match route {
+            Some(index) if self.boxed.len() > PathMap::SCAN_LIMIT => {
+                Either::Right(self.boxed[index].call(req))
+            }
+            Some(index) => Either::Left(self.routes.dispatch(index, req)),
+            None => Either::Right(self.fallback.call(req)),
+        }
     }
 }
 
 impl<A: Clone, B: Clone, Request> Clone for Routes<A, B, Request> {
     fn clone(&self) -> Self {
         Self {
+            names: self.names.clone(),
             routes: self.routes.clone(),
+            boxed: self.boxed.iter().map(|route| route.clone_box()).collect(),
+            fallback: self.fallback.clone_box(),
         }
     }
 }
 
+impl<A, B, Request> fmt::Debug for Routes<A, B, Request> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names = self.names.paths();
+        names.sort();
+
+        f.debug_struct("Routes").field("services", &names).finish()
+    }
+}
+
+/// The service name of a `/{service}/{method}` path.
+fn service_name(path: &str) -> Option<&str> {
+    path.strip_prefix('/')?.split('/').next()
+}
+
+/// The service added to [`Routes`] at `index`, followed by the services
+/// added before it.
 #[doc(hidden)]
 pub struct Or<A, B, Request> {
-    predicate: Arc<dyn Fn(&Request) -> bool + Send + Sync + 'static>,
+    index: usize,
     a: A,
     b: B,
+    _request: PhantomData<fn() -> Request>,
 }
 
 impl<A, B, Request> Or<A, B, Request> {
-    pub(crate) fn new<F>(predicate: F, a: A, b: B) -> Self
-    where
-        F: Fn(&Request) -> bool + Send + Sync + 'static,
-    {
-        let predicate = Arc::new(predicate);
-        Self { predicate, a, b }
+    fn new(index: usize, a: A, b: B) -> Self {
+        Self {
+            index,
+            a,
+            b,
+            _request: PhantomData,
+        }
     }
 }
 
-impl<A, B, Request> Service<Request> for Or<A, B, Request>
+/// Calls the service added to [`Routes`] at an index.
+#[doc(hidden)]
+pub trait Dispatch<Request> {
+    type Future: Future<Output = Result<Response<BoxBody>, crate::Error>>;
+
+    fn dispatch(&mut self, index: usize, req: Request) -> Self::Future;
+}
+
+impl<A, B, Request> Dispatch<Request> for Or<A, B, Request>
 where
-    A: Service<Request>,
-    A::Future: Send + 'static,
+    A: Service<Request, Response = Response<BoxBody>>,
     A::Error: Into<crate::Error>,
-    B: Service<Request, Response = A::Response>,
-    B::Future: Send + 'static,
-    B::Error: Into<crate::Error>,
+    B: Dispatch<Request>,
 {
-    type Response = A::Response;
-    type Error = crate::Error;
-
     #[allow(clippy::type_complexity)]
-    type Future = Either<
-        MapErr<A::Future, fn(A::Error) -> crate::Error>,
-        MapErr<B::Future, fn(B::Error) -> crate::Error>,
-    >;
+    type Future = Either<MapErr<A::Future, fn(A::Error) -> crate::Error>, B::Future>;
 
-    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        Ok(()).into()
-    }
-
-    fn call(&mut self, req: Request) -> Self::Future {
-        if (self.predicate)(&req) {
+    fn dispatch(&mut self, index: usize, req: Request) -> Self::Future {
+        if index == self.index {
             Either::Left(self.a.call(req).map_err(|e| e.into()))
         } else {
-            Either::Right(self.b.call(req).map_err(|e| e.into()))
+            Either::Right(self.b.dispatch(index, req))
         }
     }
 }
 
+// The service the first route is pushed onto, which is never dispatched to
+// since the first route has index 0.
+impl Dispatch<http::Request<Body>> for Unimplemented {
+    type Future = <Unimplemented as Service<http::Request<Body>>>::Future;
+
+    fn dispatch(&mut self, _index: usize, req: http::Request<Body>) -> Self::Future {
+        self.call(req)
+    }
+}
+
 impl<A: Clone, B: Clone, Request> Clone for Or<A, B, Request> {
     fn clone(&self) -> Self {
-        Self {
-            predicate: self.predicate.clone(),
-            a: self.a.clone(),
-            b: self.b.clone(),
-        }
+        Self::new(self.index, self.a.clone(), self.b.clone())
     }
 }
 
@@ -130,3 +221,46 @@ impl<A, B, Request> fmt::Debug for Or<A, B, Request> {
         write!(f, "Or {{ .. }}")
     }
 }
+
+type BoxRoute<Request> = Box<dyn CloneRoute<Request> + Send>;
+
+trait CloneRoute<Request> {
+    fn call(&mut self, req: Request) -> BoxFuture<Response<BoxBody>, crate::Error>;
+
+    fn clone_box(&self) -> BoxRoute<Request>;
+}
+
+struct Route<S>(S);
+
+impl<S, Request: 'static> CloneRoute<Request> for Route<S>
+where
+    S: Service<Request, Response = Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<crate::Error>,
+{
+    fn call(&mut self, req: Request) -> BoxFuture<Response<BoxBody>, crate::Error> {
+        Box::pin(self.0.call(req).map_err(|err| err.into()))
+    }
+
+    fn clone_box(&self) -> BoxRoute<Request> {
+        Box::new(Route(self.0.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_service_name() {
+        assert_eq!(
+            service_name("/helloworld.Greeter/SayHello"),
+            Some("helloworld.Greeter")
+        );
+        assert_eq!(
+            service_name("/helloworld.Greeter"),
+            Some("helloworld.Greeter")
+        );
+        assert_eq!(service_name("helloworld.Greeter/SayHello"), None);
+    }
+}
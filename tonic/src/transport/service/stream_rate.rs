@@ -0,0 +1,148 @@
+use crate::{service::interceptor::ResponseFuture, Status};
+use http::Request;
+use std::{
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower_service::Service;
+
+/// The rate at which a connection may open new streams.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StreamRate {
+    pub(crate) streams: u64,
+    pub(crate) per: Duration,
+    pub(crate) burst: u64,
+}
+
+/// Rejects the streams of a connection opened faster than its [`StreamRate`]
+/// with `RESOURCE_EXHAUSTED`.
+///
+/// This is a token bucket holding up to `burst` streams, refilled with
+/// `streams` every `per`.
+#[derive(Debug)]
+pub(crate) struct StreamRateLimit<S> {
+    inner: S,
+    bucket: Option<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    rate: StreamRate,
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl StreamRate {
+    pub(crate) fn new(streams: u64, per: Duration, burst: u64) -> Self {
+        assert!(streams > 0, "stream rate must allow at least one stream");
+        assert!(per > Duration::ZERO, "stream rate period must not be zero");
+        assert!(
+            burst > 0,
+            "stream rate burst must allow at least one stream"
+        );
+
+        StreamRate {
+            streams,
+            per,
+            burst,
+        }
+    }
+}
+
+impl<S> StreamRateLimit<S> {
+    pub(crate) fn new(inner: S, rate: Option<StreamRate>) -> Self {
+        Self {
+            inner,
+            bucket: rate.map(|rate| Bucket {
+                rate,
+                tokens: rate.burst as f64,
+                refilled: Instant::now(),
+            }),
+        }
+    }
+}
+
+impl Bucket {
+    fn try_acquire(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        let refill = elapsed * self.rate.streams as f64 / self.rate.per.as_secs_f64();
+
+        self.tokens = (self.tokens + refill).min(self.rate.burst as f64);
+        self.refilled = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for StreamRateLimit<S>
+where
+    S: Service<Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Error: Into<crate::Error>,
+{
+    type Response = S::Response;
+    type Error = crate::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        if let Some(bucket) = &mut self.bucket {
+            if !bucket.try_acquire(Instant::now()) {
+                tracing::debug!("rejecting stream opened past the connection stream rate");
+                return ResponseFuture::error(Status::resource_exhausted(
+                    "too many new streams on this connection",
+                ));
+            }
+        }
+
+        ResponseFuture::future(self.inner.call(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refills_at_rate_up_to_burst() {
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            rate: StreamRate::new(10, Duration::from_secs(1), 2),
+            tokens: 2.0,
+            refilled: start,
+        };
+
+        assert!(bucket.try_acquire(start));
+        assert!(bucket.try_acquire(start));
+        assert!(!bucket.try_acquire(start));
+
+        // one stream every 100ms
+        assert!(bucket.try_acquire(start + Duration::from_millis(100)));
+        assert!(!bucket.try_acquire(start + Duration::from_millis(150)));
+
+        // never more than the burst
+        let later = start + Duration::from_secs(10);
+        assert!(bucket.try_acquire(later));
+        assert!(bucket.try_acquire(later));
+        assert!(!bucket.try_acquire(later));
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_zero_burst() {
+        StreamRate::new(10, Duration::from_secs(1), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_zero_period() {
+        StreamRate::new(10, Duration::ZERO, 2);
+    }
+}
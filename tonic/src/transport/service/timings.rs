@@ -0,0 +1,165 @@
+use super::super::BoxFuture;
+use crate::client::CallTimings;
+use http::{Request, Response, Uri};
+use hyper::client::connect::dns::Name;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower_service::Service;
+
+/// Collects how long opening the connections of a channel took, to report
+/// it with the first response on each new connection.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConnectTimings {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    // the latest resolution, until the connection it was for is established
+    resolve: Option<Duration>,
+    // the latest connection, until a response on it is returned
+    connected: Option<CallTimings>,
+}
+
+impl ConnectTimings {
+    fn record_resolve(&self, resolve: Duration) {
+        self.state.lock().unwrap().resolve = Some(resolve);
+    }
+
+    fn record_connect(&self, elapsed: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let resolve = state.resolve.take();
+        let connect = elapsed - resolve.unwrap_or_default().min(elapsed);
+        state.connected = Some(CallTimings::connected(resolve, connect));
+    }
+
+    fn take(&self) -> Option<CallTimings> {
+        self.state.lock().unwrap().connected.take()
+    }
+}
+
+/// Times the name resolution of the `HttpConnector`.
+#[derive(Debug, Clone)]
+pub(crate) struct TimedResolver<R> {
+    inner: R,
+    timings: ConnectTimings,
+}
+
+impl<R> TimedResolver<R> {
+    pub(crate) fn new(inner: R, timings: ConnectTimings) -> Self {
+        Self { inner, timings }
+    }
+}
+
+impl<R> Service<Name> for TimedResolver<R>
+where
+    R: Service<Name>,
+    R::Future: Send + 'static,
+{
+    type Response = R::Response;
+    type Error = R::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<R::Response, R::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let timings = self.timings.clone();
+        let resolve = self.inner.call(name);
+
+        Box::pin(async move {
+            let start = Instant::now();
+            let addrs = resolve.await?;
+            timings.record_resolve(start.elapsed());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Times establishing a connection, including name resolution.
+#[derive(Debug)]
+pub(crate) struct TimedConnect<C> {
+    inner: C,
+    timings: ConnectTimings,
+}
+
+impl<C> TimedConnect<C> {
+    pub(crate) fn new(inner: C, timings: ConnectTimings) -> Self {
+        Self { inner, timings }
+    }
+}
+
+impl<C> Service<Uri> for TimedConnect<C>
+where
+    C: Service<Uri>,
+    C::Future: Send + 'static,
+{
+    type Response = C::Response;
+    type Error = C::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<C::Response, C::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let timings = self.timings.clone();
+        let connect = self.inner.call(uri);
+
+        Box::pin(async move {
+            let start = Instant::now();
+            let io = connect.await?;
+            timings.record_connect(start.elapsed());
+            Ok(io)
+        })
+    }
+}
+
+/// Adds the timings of a newly opened connection to the first response on
+/// it.
+#[derive(Debug)]
+pub(crate) struct RecordTimings<S> {
+    inner: S,
+    timings: ConnectTimings,
+}
+
+impl<S> RecordTimings<S> {
+    pub(crate) fn new(inner: S, timings: ConnectTimings) -> Self {
+        Self { inner, timings }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RecordTimings<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<S::Response, S::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let timings = self.timings.clone();
+        let response = self.inner.call(req);
+
+        Box::pin(async move {
+            let mut response = response.await?;
+
+            if let Some(timings) = timings.take() {
+                response.extensions_mut().insert(timings);
+            }
+
+            Ok(response)
+        })
+    }
+}
@@ -0,0 +1,193 @@
+use http::Uri;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tower_service::Service;
+
+/// Sets the `IP_TOS` option of an IPv4 socket, or the `IPV6_TCLASS` option
+/// of an IPv6 socket, to `tos`.
+#[cfg(unix)]
+pub(crate) fn set_tos<S: std::os::unix::io::AsRawFd>(socket: &S, tos: u8) {
+    // SAFETY: `socket` stays open while it is borrowed, which is only for
+    // the duration of this function.
+    let fd = unsafe { std::os::unix::io::BorrowedFd::borrow_raw(socket.as_raw_fd()) };
+    set_socket_tos(socket2::SockRef::from(&fd), tos);
+}
+
+/// Sets the `IP_TOS` option of an IPv4 socket, or the `IPV6_TCLASS` option
+/// of an IPv6 socket, to `tos`.
+#[cfg(windows)]
+pub(crate) fn set_tos<S: std::os::windows::io::AsRawSocket>(socket: &S, tos: u8) {
+    // SAFETY: `socket` stays open while it is borrowed, which is only for
+    // the duration of this function.
+    let socket =
+        unsafe { std::os::windows::io::BorrowedSocket::borrow_raw(socket.as_raw_socket()) };
+    set_socket_tos(socket2::SockRef::from(&socket), tos);
+}
+
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn set_tos<S>(_socket: &S, _tos: u8) {
+    static WARN: std::sync::Once = std::sync::Once::new();
+    WARN.call_once(|| tracing::warn!("Setting IP_TOS is not supported on this platform."));
+}
+
+#[cfg(any(unix, windows))]
+fn set_socket_tos(socket: socket2::SockRef<'_>, tos: u8) {
+    let is_ipv6 = socket.local_addr().is_ok_and(|addr| addr.is_ipv6());
+
+    if is_ipv6 {
+        if let Err(err) = set_tclass_v6(&socket, tos) {
+            tracing::warn!(message = "Failed to set IPV6_TCLASS.", error = %err);
+        }
+    } else if let Err(err) = set_tos_v4(&socket, tos) {
+        tracing::warn!(message = "Failed to set IP_TOS.", error = %err);
+    }
+}
+
+#[cfg(all(
+    any(unix, windows),
+    not(any(
+        target_os = "fuchsia",
+        target_os = "redox",
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku",
+    ))
+))]
+fn set_tos_v4(socket: &socket2::SockRef<'_>, tos: u8) -> std::io::Result<()> {
+    socket.set_tos(tos.into())
+}
+
+#[cfg(all(
+    any(unix, windows),
+    any(
+        target_os = "fuchsia",
+        target_os = "redox",
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku",
+    )
+))]
+fn set_tos_v4(_socket: &socket2::SockRef<'_>, _tos: u8) -> std::io::Result<()> {
+    Err(std::io::Error::other(
+        "setting IP_TOS is not supported on this platform",
+    ))
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "fuchsia",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+))]
+fn set_tclass_v6(socket: &socket2::SockRef<'_>, tos: u8) -> std::io::Result<()> {
+    socket.set_tclass_v6(tos.into())
+}
+
+#[cfg(all(
+    any(unix, windows),
+    not(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "fuchsia",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))
+))]
+fn set_tclass_v6(_socket: &socket2::SockRef<'_>, _tos: u8) -> std::io::Result<()> {
+    Err(std::io::Error::other(
+        "setting IPV6_TCLASS is not supported on this platform",
+    ))
+}
+
+/// Sets the `IP_TOS` or `IPV6_TCLASS` option of the connections made by the
+/// inner connector.
+#[derive(Debug, Clone)]
+pub(crate) struct SetTos<C> {
+    inner: C,
+    tos: Option<u8>,
+}
+
+impl<C> SetTos<C> {
+    pub(crate) fn new(inner: C, tos: Option<u8>) -> Self {
+        Self { inner, tos }
+    }
+}
+
+impl<C> Service<Uri> for SetTos<C>
+where
+    C: Service<Uri, Response = tokio::net::TcpStream>,
+    C::Future: Send + 'static,
+{
+    type Response = C::Response;
+    type Error = C::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<C::Response, C::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let tos = self.tos;
+        let connect = self.inner.call(uri);
+
+        Box::pin(async move {
+            let io = connect.await?;
+
+            if let Some(tos) = tos {
+                set_tos(&io, tos);
+            }
+
+            Ok(io)
+        })
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use tokio::net::{TcpListener, TcpStream};
+
+    #[tokio::test]
+    async fn sets_tos_of_connections() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let connector = tower::service_fn(move |_: Uri| TcpStream::connect(addr));
+        let mut connector = SetTos::new(connector, Some(0xb8));
+
+        let io = connector
+            .call(Uri::from_static("http://example.com"))
+            .await
+            .unwrap();
+        assert_eq!(socket2::SockRef::from(&io).tos().unwrap(), 0xb8);
+    }
+
+    #[tokio::test]
+    async fn sets_tclass_of_ipv6_connections() {
+        let listener = match TcpListener::bind("[::1]:0").await {
+            Ok(listener) => listener,
+            // IPv6 is not available
+            Err(_) => return,
+        };
+        let addr = listener.local_addr().unwrap();
+
+        let connector = tower::service_fn(move |_: Uri| TcpStream::connect(addr));
+        let mut connector = SetTos::new(connector, Some(0xb8));
+
+        let io = connector
+            .call(Uri::from_static("http://example.com"))
+            .await
+            .unwrap();
+        assert_eq!(socket2::SockRef::from(&io).tclass_v6().unwrap(), 0xb8);
+    }
+}
@@ -4,6 +4,7 @@
 #![allow(dead_code)]
 
 use pin_project::pin_project;
+use std::{collections::HashMap, iter::FromIterator};
 
 /// A pin-project compatible `Option`
 #[pin_project(project = OptionPinProj)]
@@ -11,3 +12,106 @@ pub(crate) enum OptionPin<T> {
     Some(#[pin] T),
     None,
 }
+
+/// A map from paths known up front to their index.
+///
+/// Comparing a path costs a few nanoseconds and hashing it costs as much as
+/// comparing about eight, so small maps are scanned and larger maps hashed.
+#[derive(Debug, Clone)]
+pub(crate) enum PathMap {
+    Scan(Vec<(&'static str, usize)>),
+    Hash(HashMap<&'static str, usize>),
+}
+
+impl PathMap {
+    /// The most paths that are scanned instead of hashed.
+    pub(crate) const SCAN_LIMIT: usize = 8;
+
+    pub(crate) fn new() -> Self {
+        PathMap::Scan(Vec::new())
+    }
+
+    /// Map `path` to `index`, replacing the index it was mapped to before.
+    pub(crate) fn insert(&mut self, path: &'static str, index: usize) {
+        match self {
+            PathMap::Scan(paths) => {
+                if let Some(entry) = paths.iter_mut().find(|(p, _)| *p == path) {
+                    entry.1 = index;
+                } else if paths.len() < Self::SCAN_LIMIT {
+                    paths.push((path, index));
+                } else {
+                    let mut map = paths.drain(..).collect::<HashMap<_, _>>();
+                    map.insert(path, index);
+                    *self = PathMap::Hash(map);
+                }
+            }
+            PathMap::Hash(map) => {
+                map.insert(path, index);
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, path: &str) -> Option<usize> {
+        match self {
+            PathMap::Scan(paths) => paths.iter().find(|(p, _)| *p == path).map(|(_, i)| *i),
+            PathMap::Hash(map) => map.get(path).copied(),
+        }
+    }
+
+    pub(crate) fn paths(&self) -> Vec<&'static str> {
+        match self {
+            PathMap::Scan(paths) => paths.iter().map(|(p, _)| *p).collect(),
+            PathMap::Hash(map) => map.keys().copied().collect(),
+        }
+    }
+}
+
+impl FromIterator<(&'static str, usize)> for PathMap {
+    fn from_iter<I: IntoIterator<Item = (&'static str, usize)>>(iter: I) -> Self {
+        let mut map = PathMap::new();
+
+        for (path, index) in iter {
+            map.insert(path, index);
+        }
+
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(n: usize) -> Vec<&'static str> {
+        (0..n)
+            .map(|i| &*Box::leak(format!("/test.Service/Method{}", i).into_boxed_str()))
+            .collect()
+    }
+
+    #[test]
+    fn path_map_scans_and_hashes() {
+        for n in [1, PathMap::SCAN_LIMIT, PathMap::SCAN_LIMIT + 1, 100] {
+            let paths = paths(n);
+            let map = paths.iter().copied().zip(0..).collect::<PathMap>();
+
+            assert_eq!(matches!(map, PathMap::Hash(_)), n > PathMap::SCAN_LIMIT);
+            for (index, path) in paths.iter().enumerate() {
+                assert_eq!(map.get(path), Some(index));
+            }
+            assert_eq!(map.get("/test.Service/Missing"), None);
+            assert_eq!(map.paths().len(), n);
+        }
+    }
+
+    #[test]
+    fn path_map_insert_replaces() {
+        for n in [1, 100] {
+            let paths = paths(n);
+            let mut map = paths.iter().copied().zip(0..).collect::<PathMap>();
+
+            map.insert(paths[0], 1000);
+            assert_eq!(map.get(paths[0]), Some(1000));
+            assert_eq!(map.paths().len(), n);
+        }
+    }
+}